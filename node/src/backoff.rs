@@ -0,0 +1,96 @@
+//! Backing off block authoring while finality lags behind, with Prometheus metrics showing when
+//! the node does.
+
+use node_template_runtime::BlockNumber;
+use sc_consensus_slots::{BackoffAuthoringBlocksStrategy, BackoffAuthoringOnFinalizedHeadLagging};
+use sp_consensus_aura::Slot;
+use substrate_prometheus_endpoint::{register, Counter, Gauge, PrometheusError, Registry, U64};
+
+use crate::chain_spec::BackoffSettings;
+
+/// [`BackoffAuthoringOnFinalizedHeadLagging`] reporting its decisions to Prometheus.
+pub struct MeteredBackoff {
+	strategy: BackoffAuthoringOnFinalizedHeadLagging<BlockNumber>,
+	metrics: Option<Metrics>,
+}
+
+impl MeteredBackoff {
+	/// Backs off according to `settings`, registering the metrics with `registry` if there is one.
+	pub fn new(
+		settings: &BackoffSettings,
+		registry: Option<&Registry>,
+	) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			strategy: BackoffAuthoringOnFinalizedHeadLagging {
+				max_interval: settings.max_interval,
+				unfinalized_slack: settings.unfinalized_slack,
+				authoring_bias: settings.authoring_bias,
+			},
+			metrics: registry.map(Metrics::register).transpose()?,
+		})
+	}
+}
+
+impl BackoffAuthoringBlocksStrategy<BlockNumber> for MeteredBackoff {
+	fn should_backoff(
+		&self,
+		chain_head_number: BlockNumber,
+		chain_head_slot: Slot,
+		finalized_number: BlockNumber,
+		slow_now: Slot,
+		logging_target: &str,
+	) -> bool {
+		let backoff = self.strategy.should_backoff(
+			chain_head_number,
+			chain_head_slot,
+			finalized_number,
+			slow_now,
+			logging_target,
+		);
+
+		if let Some(metrics) = &self.metrics {
+			metrics.backing_off.set(backoff as u64);
+			metrics
+				.unfinalized_blocks
+				.set(chain_head_number.saturating_sub(finalized_number).into());
+			if backoff {
+				metrics.skipped_slots.inc();
+			}
+		}
+		backoff
+	}
+}
+
+struct Metrics {
+	backing_off: Gauge<U64>,
+	unfinalized_blocks: Gauge<U64>,
+	skipped_slots: Counter<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			backing_off: register(
+				Gauge::new(
+					"authoring_backoff",
+					"Whether block authoring backs off because finality lags behind (1) or not (0)",
+				)?,
+				registry,
+			)?,
+			unfinalized_blocks: register(
+				Gauge::new(
+					"authoring_unfinalized_blocks",
+					"Number of unfinalized blocks on top of the finalized head when last authoring",
+				)?,
+				registry,
+			)?,
+			skipped_slots: register(
+				Counter::new(
+					"authoring_backoff_skipped_slots_total",
+					"Number of slots in which block authoring backed off",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
@@ -1,18 +1,176 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
+	opaque::SessionKeys, pallet_feeless::FeelessCall, AccountId, AssetId, AssetsConfig,
+	BalancesConfig, FeelessConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
 	SystemConfig, WASM_BINARY,
 };
+use sc_chain_spec::ChainSpecExtension;
 use sc_service::ChainType;
-use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use serde::{Deserialize, Serialize};
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
+#[cfg(not(any(feature = "babe", feature = "pow")))]
+use {node_template_runtime::AuraConfig, sp_consensus_aura::sr25519::AuthorityId as AuraId};
+#[cfg(feature = "babe")]
+use node_template_runtime::{
+	babe::{BabeId, BABE_GENESIS_EPOCH_CONFIG},
+	BabeConfig,
+};
+#[cfg(feature = "npos")]
+use node_template_runtime::{npos::StakerStatus, Balance, Perbill, SessionConfig, StakingConfig};
+#[cfg(feature = "pow")]
+use {
+	node_template_runtime::{pow::INITIAL_DIFFICULTY, DifficultyConfig},
+	sp_core::U256,
+};
+#[cfg(feature = "evm")]
+use {
+	node_template_runtime::{evm::NodeTemplatePrecompiles, EVMConfig},
+	sp_core::{H160, U256},
+	std::{collections::BTreeMap, str::FromStr},
+};
+
+/// Balance the initial authorities bond as validators with the `npos` feature.
+#[cfg(feature = "npos")]
+const VALIDATOR_BOND: Balance = 1 << 50;
+
+/// Id of the asset created at genesis by [`testnet_genesis`] and held by the endowed accounts.
+const DEV_ASSET: AssetId = 1;
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
+/// Node specific settings that are part of the chain spec but not of the genesis state.
+///
+/// Chain specs without these fields get the defaults.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ChainSpecExtension)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Extensions {
+	/// Tuning of the GRANDPA finality gadget.
+	pub grandpa: GrandpaSettings,
+	/// Tuning of slot based block authoring, by Aura or by BABE with the `babe` feature. Chain
+	/// specs written before BABE was supported name it `aura`.
+	#[serde(alias = "aura")]
+	pub slots: SlotSettings,
+	/// When block authoring backs off because finality lags behind.
+	pub backoff: BackoffSettings,
+}
+
+/// Tuning of the GRANDPA finality gadget.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GrandpaSettings {
+	/// Interval in milliseconds at which GRANDPA gossips votes.
+	pub gossip_duration_ms: u64,
+	/// Every this many blocks a justification is generated and stored, even if no authority set
+	/// change requires one.
+	pub justification_period: u32,
+}
+
+impl Default for GrandpaSettings {
+	fn default() -> Self {
+		Self { gossip_duration_ms: 333, justification_period: 512 }
+	}
+}
+
+impl GrandpaSettings {
+	/// Checks that the settings can be used by a node.
+	pub fn validate(&self) -> Result<(), String> {
+		if self.gossip_duration_ms == 0 {
+			return Err("the GRANDPA gossip duration must be positive".into())
+		}
+		if self.justification_period == 0 {
+			return Err("the GRANDPA justification period must be positive".into())
+		}
+		Ok(())
+	}
+}
+
+/// Tuning of slot based block authoring, shared by Aura and BABE.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SlotSettings {
+	/// Portion of a slot the author may spend proposing a block.
+	pub block_proposal_slot_portion: f32,
+	/// Upper bound of the proposal time when the slot portion is lenient after missed slots. If
+	/// unset, proposals may take a whole slot.
+	pub max_block_proposal_slot_portion: Option<f32>,
+}
+
+impl Default for SlotSettings {
+	fn default() -> Self {
+		Self { block_proposal_slot_portion: 2f32 / 3f32, max_block_proposal_slot_portion: None }
+	}
+}
+
+impl SlotSettings {
+	/// Checks that the settings can be used by a node.
+	pub fn validate(&self) -> Result<(), String> {
+		let portion = self.block_proposal_slot_portion;
+		if !(portion > 0.0 && portion <= 1.0) {
+			return Err(format!(
+				"the block proposal slot portion must be in (0, 1], got {}",
+				portion
+			))
+		}
+		if let Some(max) = self.max_block_proposal_slot_portion {
+			if !(max > 0.0 && max <= 1.0) {
+				return Err(format!(
+					"the maximum block proposal slot portion must be in (0, 1], got {}",
+					max
+				))
+			}
+			if max < portion {
+				return Err(format!(
+					"the maximum block proposal slot portion ({}) must not be smaller than the \
+					block proposal slot portion ({})",
+					max, portion
+				))
+			}
+		}
+		Ok(())
+	}
+}
+
+/// When block authoring backs off because finality lags behind the best block.
+///
+/// Once more than `unfinalized_slack` blocks are unfinalized, authors only claim a slot if the
+/// best block is older than one slot per `authoring_bias` further unfinalized blocks, and at most
+/// `max_interval` slots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackoffSettings {
+	/// Whether authors back off at all.
+	pub enabled: bool,
+	/// Number of unfinalized blocks authors tolerate before backing off.
+	pub unfinalized_slack: u32,
+	/// Number of further unfinalized blocks per slot skipped.
+	pub authoring_bias: u32,
+	/// Maximum number of slots skipped in a row.
+	pub max_interval: u32,
+}
+
+impl Default for BackoffSettings {
+	fn default() -> Self {
+		Self { enabled: false, unfinalized_slack: 50, authoring_bias: 2, max_interval: 100 }
+	}
+}
+
+impl BackoffSettings {
+	/// Checks that the settings can be used by a node.
+	pub fn validate(&self) -> Result<(), String> {
+		if self.authoring_bias == 0 {
+			return Err("the backoff authoring bias must be positive".into())
+		}
+		if self.max_interval == 0 {
+			return Err("the maximum backoff interval must be positive".into())
+		}
+		Ok(())
+	}
+}
+
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
-pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
+pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig, Extensions>;
 
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
@@ -31,9 +189,26 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
-	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
+/// Generate the session keys of an authority: its block authoring (Aura or BABE) key, unless blocks
+/// are mined, and its GRANDPA key.
+pub fn authority_keys_from_seed(s: &str) -> SessionKeys {
+	SessionKeys {
+		#[cfg(not(any(feature = "babe", feature = "pow")))]
+		aura: get_from_seed::<AuraId>(s),
+		#[cfg(feature = "babe")]
+		babe: get_from_seed::<BabeId>(s),
+		grandpa: get_from_seed::<GrandpaId>(s),
+	}
+}
+
+/// Generate the stash and controller accounts of an authority, which only matter with the `npos`
+/// feature, and its session keys.
+pub fn authority_from_seed(s: &str) -> (AccountId, AccountId, SessionKeys) {
+	(
+		get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", s)),
+		get_account_id_from_seed::<sr25519::Public>(s),
+		authority_keys_from_seed(s),
+	)
 }
 
 pub fn development_config() -> Result<ChainSpec, String> {
@@ -49,7 +224,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 			testnet_genesis(
 				wasm_binary,
 				// Initial PoA authorities
-				vec![authority_keys_from_seed("Alice")],
+				vec![authority_from_seed("Alice")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Pre-funded accounts
@@ -72,7 +247,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		// Properties
 		None,
 		// Extensions
-		None,
+		Default::default(),
 	))
 }
 
@@ -89,7 +264,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 			testnet_genesis(
 				wasm_binary,
 				// Initial PoA authorities
-				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
+				vec![authority_from_seed("Alice"), authority_from_seed("Bob")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Pre-funded accounts
@@ -120,14 +295,61 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		None,
 		None,
 		// Extensions
+		Default::default(),
+	))
+}
+
+/// Chain spec of the `local-net` subcommand, whose authorities are derived from `seeds`.
+///
+/// Alice is the sudo account. The well-known accounts, the authorities and their stash accounts
+/// are pre-funded.
+pub fn local_net_config(seeds: &[String]) -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	let seeds = seeds.to_vec();
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Local Network",
+		// ID
+		"local_net",
+		ChainType::Local,
+		move || {
+			let mut endowed_accounts = Vec::new();
+			let well_known = ["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"].map(String::from);
+			for seed in well_known.iter().chain(&seeds) {
+				for seed in [seed.clone(), format!("{}//stash", seed)] {
+					let account = get_account_id_from_seed::<sr25519::Public>(&seed);
+					if !endowed_accounts.contains(&account) {
+						endowed_accounts.push(account);
+					}
+				}
+			}
+			testnet_genesis(
+				wasm_binary,
+				seeds.iter().map(|seed| authority_from_seed(seed)).collect(),
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				endowed_accounts,
+				true,
+			)
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
 		None,
+		None,
+		// Properties
+		None,
+		// Extensions
+		Default::default(),
 	))
 }
 
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
+	initial_authorities: Vec<(AccountId, AccountId, SessionKeys)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
@@ -141,16 +363,109 @@ fn testnet_genesis(
 			// Configure endowed accounts with initial balance of 1 << 60.
 			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
 		},
+		#[cfg(not(any(feature = "babe", feature = "pow")))]
 		aura: AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+			#[cfg(not(feature = "npos"))]
+			authorities: initial_authorities.iter().map(|x| (x.2.aura.clone())).collect(),
+			// The session pallet sets the authorities elected at genesis.
+			#[cfg(feature = "npos")]
+			authorities: vec![],
 		},
+		#[cfg(feature = "babe")]
+		babe: BabeConfig {
+			authorities: initial_authorities.iter().map(|x| (x.2.babe.clone(), 1)).collect(),
+			epoch_config: Some(BABE_GENESIS_EPOCH_CONFIG),
+		},
+		#[cfg(feature = "pow")]
+		difficulty: DifficultyConfig { initial_difficulty: U256::from(INITIAL_DIFFICULTY) },
 		grandpa: GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+			#[cfg(not(feature = "npos"))]
+			authorities: initial_authorities.iter().map(|x| (x.2.grandpa.clone(), 1)).collect(),
+			#[cfg(feature = "npos")]
+			authorities: vec![],
+		},
+		// The initial authorities are the first validators, bonded from their stash accounts, and
+		// can't be slashed so that development chains keep running.
+		#[cfg(feature = "npos")]
+		staking: StakingConfig {
+			validator_count: initial_authorities.len() as u32 * 2,
+			minimum_validator_count: initial_authorities.len() as u32,
+			invulnerables: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			slash_reward_fraction: Perbill::from_percent(10),
+			stakers: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.1.clone(), VALIDATOR_BOND, StakerStatus::Validator))
+				.collect(),
+			..Default::default()
+		},
+		#[cfg(feature = "npos")]
+		session: SessionConfig {
+			keys: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.0.clone(), x.2.clone()))
+				.collect(),
 		},
 		sudo: SudoConfig {
 			// Assign network admin rights.
-			key: Some(root_key),
+			key: Some(root_key.clone()),
 		},
 		transaction_payment: Default::default(),
+		priority: Default::default(),
+		assets: AssetsConfig {
+			// An asset owned by the sudo account, to try out the assets pallet without creating
+			// one first.
+			assets: vec![(DEV_ASSET, root_key, false, 1)],
+			metadata: vec![(DEV_ASSET, b"Development Token".to_vec(), b"DEV".to_vec(), 12)],
+			accounts: endowed_accounts.iter().cloned().map(|k| (DEV_ASSET, k, 1 << 60)).collect(),
+		},
+		asset_rate: Default::default(),
+		feeless: FeelessConfig {
+			// Lets accounts holding the existential deposit call the template pallet once.
+			calls: vec![(
+				b"TemplateModule".to_vec(),
+				b"do_something".to_vec(),
+				FeelessCall { free_calls: 1, rate_limit: 1 },
+			)],
+			whitelist: vec![],
+		},
+		#[cfg(feature = "evm")]
+		evm: EVMConfig { accounts: evm_genesis_accounts() },
+		#[cfg(feature = "evm")]
+		ethereum: Default::default(),
 	}
 }
+
+/// Pre-funds the well known development account Gerald
+/// (`0x6be02d1d3665660d22ff9624b7be0551ee1ac91b`) and deploys a stub at every precompile address,
+/// because Solidity refuses to call addresses without code.
+#[cfg(feature = "evm")]
+fn evm_genesis_accounts() -> BTreeMap<H160, pallet_evm::GenesisAccount> {
+	// `PUSH1 0 PUSH1 0 REVERT`, never executed since the precompile takes precedence.
+	let revert_stub = vec![0x60, 0x00, 0x60, 0x00, 0xfd];
+
+	let mut accounts: BTreeMap<_, _> = NodeTemplatePrecompiles::used_addresses()
+		.into_iter()
+		.map(|address| {
+			let account = pallet_evm::GenesisAccount {
+				nonce: U256::one(),
+				balance: U256::zero(),
+				storage: Default::default(),
+				code: revert_stub.clone(),
+			};
+			(address, account)
+		})
+		.collect();
+
+	accounts.insert(
+		H160::from_str("6be02d1d3665660d22ff9624b7be0551ee1ac91b")
+			.expect("internal H160 is valid; qed"),
+		pallet_evm::GenesisAccount {
+			nonce: U256::zero(),
+			balance: U256::from(1u128 << 60),
+			storage: Default::default(),
+			code: vec![],
+		},
+	);
+
+	accounts
+}
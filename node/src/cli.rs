@@ -1,4 +1,4 @@
-use sc_cli::RunCmd;
+use sc_cli::{CliConfiguration, ImportParams, RunCmd, SharedParams};
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +7,9 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	#[clap(flatten)]
+	pub options: crate::service::NodeOptions,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -38,7 +41,7 @@ pub enum Subcommand {
 
 	/// Sub-commands concerned with benchmarking.
 	#[clap(subcommand)]
-	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+	Benchmark(BenchmarkSubcommand),
 
 	/// Try some command against runtime state.
 	#[cfg(feature = "try-runtime")]
@@ -50,4 +53,59 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Run a local network of validators and full nodes until Ctrl-C is pressed.
+	LocalNet(crate::local_net::LocalNetCmd),
+
+	/// Submit a mix of signed extrinsics to a node at a steady rate and report how they fare.
+	Load(crate::load::LoadCmd),
+
+	/// Compare the metadata of two runtimes and check that the new one bumps its versions enough.
+	MetadataDiff(crate::metadata_diff::MetadataDiffCmd),
+
+	/// Rehearse a runtime upgrade on a fork of the state of a database, without writing to it.
+	UpgradeRehearsal(crate::rehearsal::UpgradeRehearsalCmd),
+}
+
+/// The benchmarking sub-commands of substrate and those of this node.
+#[derive(Debug, clap::Subcommand)]
+pub enum BenchmarkSubcommand {
+	/// Benchmark the extrinsic weight of FRAME Pallets.
+	Pallet(frame_benchmarking_cli::PalletCmd),
+
+	/// Benchmark the storage speed of a chain snapshot.
+	Storage(frame_benchmarking_cli::StorageCmd),
+
+	/// Benchmark the execution overhead per-block and per-extrinsic.
+	Overhead(OverheadCmd),
+
+	/// Benchmark the execution time of historic blocks.
+	Block(frame_benchmarking_cli::BlockCmd),
+
+	/// Command to benchmark the hardware.
+	Machine(frame_benchmarking_cli::MachineCmd),
+
+	/// Fill blocks with common calls and check them against the weight and length limits.
+	Limits(crate::limits::LimitsCmd),
+}
+
+/// The `benchmark overhead` command of substrate, with the extrinsic to fill blocks with.
+#[derive(Debug, clap::Parser)]
+pub struct OverheadCmd {
+	#[clap(flatten)]
+	pub cmd: frame_benchmarking_cli::OverheadCmd,
+
+	/// Extrinsic the blocks are filled with, signed in turn by the development accounts.
+	#[clap(long, arg_enum, value_name = "EXTRINSIC", default_value = "remark")]
+	pub extrinsic: crate::command_helper::BenchmarkExtrinsic,
+}
+
+impl CliConfiguration for OverheadCmd {
+	fn shared_params(&self) -> &SharedParams {
+		self.cmd.shared_params()
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		self.cmd.import_params()
+	}
 }
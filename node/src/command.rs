@@ -1,10 +1,10 @@
 use crate::{
 	chain_spec,
-	cli::{Cli, Subcommand},
+	cli::{BenchmarkSubcommand, Cli, Subcommand},
 	command_helper::{inherent_benchmark_data, BenchmarkExtrinsicBuilder},
 	service,
 };
-use frame_benchmarking_cli::{BenchmarkCmd, SUBSTRATE_REFERENCE_HARDWARE};
+use frame_benchmarking_cli::SUBSTRATE_REFERENCE_HARDWARE;
 use node_template_runtime::Block;
 use sc_cli::{ChainSpec, RuntimeVersion, SubstrateCli};
 use sc_service::PartialComponents;
@@ -105,45 +105,51 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, backend, Some(aux_revert)), task_manager))
 			})
 		},
-		Some(Subcommand::Benchmark(cmd)) => {
-			let runner = cli.create_runner(cmd)?;
+		Some(Subcommand::Benchmark(BenchmarkSubcommand::Pallet(cmd))) => {
+			if !cfg!(feature = "runtime-benchmarks") {
+				return Err("Runtime benchmarking wasn't enabled when building the node. \
+				You can enable it with `--features runtime-benchmarks`."
+					.into())
+			}
 
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run::<Block, service::ExecutorDispatch>(config))
+		},
+		Some(Subcommand::Benchmark(BenchmarkSubcommand::Block(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| {
-				// This switch needs to be in the client, since the client decides
-				// which sub-commands it wants to support.
-				match cmd {
-					BenchmarkCmd::Pallet(cmd) => {
-						if !cfg!(feature = "runtime-benchmarks") {
-							return Err(
-								"Runtime benchmarking wasn't enabled when building the node. \
-							You can enable it with `--features runtime-benchmarks`."
-									.into(),
-							)
-						}
-
-						cmd.run::<Block, service::ExecutorDispatch>(config)
-					},
-					BenchmarkCmd::Block(cmd) => {
-						let PartialComponents { client, .. } = service::new_partial(&config)?;
-						cmd.run(client)
-					},
-					BenchmarkCmd::Storage(cmd) => {
-						let PartialComponents { client, backend, .. } =
-							service::new_partial(&config)?;
-						let db = backend.expose_db();
-						let storage = backend.expose_storage();
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client)
+			})
+		},
+		Some(Subcommand::Benchmark(BenchmarkSubcommand::Storage(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, .. } = service::new_partial(&config)?;
+				let db = backend.expose_db();
+				let storage = backend.expose_storage();
 
-						cmd.run(config, client, db, storage)
-					},
-					BenchmarkCmd::Overhead(cmd) => {
-						let PartialComponents { client, .. } = service::new_partial(&config)?;
-						let ext_builder = BenchmarkExtrinsicBuilder::new(client.clone());
+				cmd.run(config, client, db, storage)
+			})
+		},
+		Some(Subcommand::Benchmark(BenchmarkSubcommand::Overhead(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				let ext_builder = BenchmarkExtrinsicBuilder::new(client.clone(), cmd.extrinsic);
 
-						cmd.run(config, client, inherent_benchmark_data()?, Arc::new(ext_builder))
-					},
-					BenchmarkCmd::Machine(cmd) =>
-						cmd.run(&config, SUBSTRATE_REFERENCE_HARDWARE.clone()),
-				}
+				cmd.cmd.run(config, client, inherent_benchmark_data()?, Arc::new(ext_builder))
+			})
+		},
+		Some(Subcommand::Benchmark(BenchmarkSubcommand::Machine(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run(&config, SUBSTRATE_REFERENCE_HARDWARE.clone()))
+		},
+		Some(Subcommand::Benchmark(BenchmarkSubcommand::Limits(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client)
 			})
 		},
 		#[cfg(feature = "try-runtime")]
@@ -167,10 +173,22 @@ pub fn run() -> sc_cli::Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run::<Block>(&config))
 		},
+		Some(Subcommand::LocalNet(cmd)) => cmd.run(),
+		Some(Subcommand::Load(cmd)) => cmd.run(),
+		Some(Subcommand::MetadataDiff(cmd)) => cmd.run(),
+		Some(Subcommand::UpgradeRehearsal(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, task_manager, .. } =
+					service::new_partial(&config)?;
+				cmd.run(&config, client, backend, task_manager.spawn_handle())
+			})
+		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let options = cli.options.clone();
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				service::new_full(config, options).map_err(sc_cli::Error::Service)
 			})
 		},
 	}
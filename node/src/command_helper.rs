@@ -22,40 +22,70 @@
 use crate::service::FullClient;
 
 use node_template_runtime as runtime;
-use runtime::SystemCall;
+use runtime::{BalancesCall, SystemCall};
 use sc_cli::Result;
 use sc_client_api::BlockBackend;
-use sp_core::{Encode, Pair};
+use sp_core::{sr25519, Encode, Pair};
 use sp_inherents::{InherentData, InherentDataProvider};
-use sp_keyring::Sr25519Keyring;
-use sp_runtime::{OpaqueExtrinsic, SaturatedConversion};
+use sp_runtime::{generic::Era, OpaqueExtrinsic, SaturatedConversion};
 
 use std::{sync::Arc, time::Duration};
 
+/// The extrinsic the `benchmark overhead` command fills blocks with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ArgEnum)]
+pub enum BenchmarkExtrinsic {
+	/// `System::remark` with an empty remark.
+	Remark,
+	/// `Balances::transfer_keep_alive` of 1 unit to the next signer.
+	Transfer,
+	/// `TemplateModule::do_something`.
+	Template,
+}
+
 /// Generates extrinsics for the `benchmark overhead` command.
 ///
+/// The extrinsics are signed in turn by the accounts endowed by the development chain spec, so
+/// that a block doesn't hold a long run of nonces of a single account.
+///
 /// Note: Should only be used for benchmarking.
 pub struct BenchmarkExtrinsicBuilder {
 	client: Arc<FullClient>,
+	extrinsic: BenchmarkExtrinsic,
+	signers: Vec<sr25519::Pair>,
 }
 
 impl BenchmarkExtrinsicBuilder {
-	/// Creates a new [`Self`] from the given client.
-	pub fn new(client: Arc<FullClient>) -> Self {
-		Self { client }
+	/// Creates a new [`Self`] from the given client, building `extrinsic`s.
+	pub fn new(client: Arc<FullClient>, extrinsic: BenchmarkExtrinsic) -> Self {
+		let signers = ["//Alice", "//Bob", "//Alice//stash", "//Bob//stash"]
+			.iter()
+			.map(|seed| {
+				sr25519::Pair::from_string(seed, None).expect("static values are valid; qed")
+			})
+			.collect();
+		Self { client, extrinsic, signers }
 	}
 }
 
 impl frame_benchmarking_cli::ExtrinsicBuilder for BenchmarkExtrinsicBuilder {
+	/// Builds the `nonce`th extrinsic of a block, which is the extrinsic selected on the command
+	/// line rather than necessarily a remark.
 	fn remark(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
-		let acc = Sr25519Keyring::Bob.pair();
-		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
-			self.client.as_ref(),
-			acc,
-			SystemCall::remark { remark: vec![] }.into(),
-			nonce,
-		)
-		.into();
+		let signers = self.signers.len() as u32;
+		let signer = &self.signers[(nonce % signers) as usize];
+		let call: runtime::Call = match self.extrinsic {
+			BenchmarkExtrinsic::Remark => SystemCall::remark { remark: vec![] }.into(),
+			BenchmarkExtrinsic::Transfer => {
+				let dest = &self.signers[((nonce + 1) % signers) as usize];
+				let dest = sp_runtime::AccountId32::from(dest.public()).into();
+				BalancesCall::transfer_keep_alive { dest, value: 1 }.into()
+			},
+			BenchmarkExtrinsic::Template =>
+				runtime::pallet_template::Call::do_something { something: nonce }.into(),
+		};
+		let extrinsic: OpaqueExtrinsic =
+			create_benchmark_extrinsic(self.client.as_ref(), signer.clone(), call, nonce / signers)
+				.into();
 
 		Ok(extrinsic)
 	}
@@ -78,18 +108,50 @@ pub fn create_benchmark_extrinsic(
 		.checked_next_power_of_two()
 		.map(|c| c / 2)
 		.unwrap_or(2) as u64;
+	let context = SigningContext {
+		genesis_hash,
+		spec_version: runtime::VERSION.spec_version,
+		transaction_version: runtime::VERSION.transaction_version,
+		era: Era::mortal(period, best_block.saturated_into()),
+		era_hash: best_hash,
+	};
+	create_signed_extrinsic(&sender, call, nonce, &context)
+}
+
+/// What the signature of an extrinsic commits to besides its call and nonce.
+#[derive(Debug, Clone, Copy)]
+pub struct SigningContext {
+	/// Hash of the genesis block of the chain.
+	pub genesis_hash: runtime::Hash,
+	/// Spec version of the runtime of the chain.
+	pub spec_version: u32,
+	/// Transaction version of the runtime of the chain.
+	pub transaction_version: u32,
+	/// Era in which the extrinsic is valid.
+	pub era: Era,
+	/// Hash of the block the era starts at, the genesis hash for immortal eras.
+	pub era_hash: runtime::Hash,
+}
+
+/// Signs `call` by `sender` with `nonce`, paying the fee in the native currency without tip.
+pub fn create_signed_extrinsic(
+	sender: &sp_core::sr25519::Pair,
+	call: runtime::Call,
+	nonce: u32,
+	context: &SigningContext,
+) -> runtime::UncheckedExtrinsic {
 	let extra: runtime::SignedExtra = (
 		frame_system::CheckNonZeroSender::<runtime::Runtime>::new(),
 		frame_system::CheckSpecVersion::<runtime::Runtime>::new(),
 		frame_system::CheckTxVersion::<runtime::Runtime>::new(),
 		frame_system::CheckGenesis::<runtime::Runtime>::new(),
-		frame_system::CheckEra::<runtime::Runtime>::from(sp_runtime::generic::Era::mortal(
-			period,
-			best_block.saturated_into(),
-		)),
+		frame_system::CheckEra::<runtime::Runtime>::from(context.era),
 		frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
 		frame_system::CheckWeight::<runtime::Runtime>::new(),
-		pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
+		runtime::pallet_feeless::CheckFeeless::from(
+			pallet_asset_tx_payment::ChargeAssetTxPayment::<runtime::Runtime>::from(0, None),
+		),
+		runtime::pallet_priority::PrioritizeCalls::<runtime::Runtime>::new(),
 	);
 
 	let raw_payload = runtime::SignedPayload::from_raw(
@@ -97,10 +159,11 @@ pub fn create_benchmark_extrinsic(
 		extra.clone(),
 		(
 			(),
-			runtime::VERSION.spec_version,
-			runtime::VERSION.transaction_version,
-			genesis_hash,
-			best_hash,
+			context.spec_version,
+			context.transaction_version,
+			context.genesis_hash,
+			context.era_hash,
+			(),
 			(),
 			(),
 			(),
@@ -109,10 +172,10 @@ pub fn create_benchmark_extrinsic(
 	let signature = raw_payload.using_encoded(|e| sender.sign(e));
 
 	runtime::UncheckedExtrinsic::new_signed(
-		call.clone(),
+		call,
 		sp_runtime::AccountId32::from(sender.public()).into(),
-		runtime::Signature::Sr25519(signature.clone()),
-		extra.clone(),
+		runtime::Signature::Sr25519(signature),
+		extra,
 	)
 }
 
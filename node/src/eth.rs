@@ -0,0 +1,160 @@
+//! Ethereum compatibility layer of the node: the Frontier database that maps Ethereum to substrate
+//! blocks and the background tasks keeping it and the RPC caches up to date.
+//!
+//! Only compiled with the `evm` feature.
+
+use crate::service::{FullBackend, FullClient};
+use fc_mapping_sync::{MappingSyncWorker, SyncStrategy};
+use fc_rpc::{
+	EthTask, OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override, SchemaV2Override,
+	SchemaV3Override, StorageOverride,
+};
+use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
+use fp_storage::EthereumStorageSchema;
+use futures::{future, StreamExt};
+use node_template_runtime::opaque::Block;
+use sc_client_api::BlockchainEvents;
+use sc_service::{BasePath, Configuration, TaskManager};
+use std::{
+	collections::BTreeMap,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+/// Command line options of the Ethereum RPC.
+#[derive(Debug, Clone, clap::Args)]
+pub struct EthConfiguration {
+	/// Maximum number of logs returned by a single `eth_getLogs` query.
+	#[clap(long, default_value = "10000")]
+	pub max_past_logs: u32,
+
+	/// Maximum number of blocks kept in the fee history cache.
+	#[clap(long, default_value = "2048")]
+	pub fee_history_limit: u64,
+
+	/// Maximum number of filters stored by `eth_newFilter`.
+	#[clap(long, default_value = "500")]
+	pub max_stored_filters: usize,
+
+	/// Sign transactions submitted through `eth_sendTransaction` with a built-in development key.
+	#[clap(long)]
+	pub enable_dev_signer: bool,
+
+	/// Size in bytes of the LRU cache for Ethereum blocks.
+	#[clap(long, default_value = "50000000")]
+	pub eth_log_block_cache: usize,
+
+	/// Size in bytes of the LRU cache for transaction statuses.
+	#[clap(long, default_value = "50000000")]
+	pub eth_statuses_cache: usize,
+}
+
+/// Frontier components shared between the background tasks and the RPC.
+pub struct FrontierComponents {
+	/// Maps Ethereum block and transaction hashes to substrate blocks.
+	pub backend: Arc<fc_db::Backend<Block>>,
+	/// Filters created by `eth_newFilter` and friends.
+	pub filter_pool: FilterPool,
+	/// Recent fee history served by `eth_feeHistory`.
+	pub fee_history_cache: FeeHistoryCache,
+	/// Reads the Ethereum state directly from storage for known schemas.
+	pub overrides: Arc<OverrideHandle<Block>>,
+}
+
+impl FrontierComponents {
+	/// Opens the Frontier database next to the node's database.
+	pub fn new(config: &Configuration, client: Arc<FullClient>) -> Result<Self, String> {
+		let backend = fc_db::Backend::<Block>::new(&fc_db::DatabaseSettings {
+			source: fc_db::DatabaseSource::RocksDb {
+				path: frontier_database_dir(config),
+				cache_size: 0,
+			},
+		})?;
+
+		Ok(Self {
+			backend: Arc::new(backend),
+			filter_pool: Arc::new(Mutex::new(BTreeMap::new())),
+			fee_history_cache: Arc::new(Mutex::new(BTreeMap::new())),
+			overrides: overrides_handle(client),
+		})
+	}
+}
+
+fn frontier_database_dir(config: &Configuration) -> PathBuf {
+	let config_dir = config
+		.base_path
+		.as_ref()
+		.map(|base_path| base_path.config_dir(config.chain_spec.id()))
+		.unwrap_or_else(|| {
+			BasePath::from_project("", "", "node-template").config_dir(config.chain_spec.id())
+		});
+	config_dir.join("frontier").join("db")
+}
+
+fn overrides_handle(client: Arc<FullClient>) -> Arc<OverrideHandle<Block>> {
+	let mut overrides_map = BTreeMap::new();
+	overrides_map.insert(
+		EthereumStorageSchema::V1,
+		Box::new(SchemaV1Override::new(client.clone())) as Box<dyn StorageOverride<_> + 'static>,
+	);
+	overrides_map.insert(
+		EthereumStorageSchema::V2,
+		Box::new(SchemaV2Override::new(client.clone())) as Box<dyn StorageOverride<_> + 'static>,
+	);
+	overrides_map.insert(
+		EthereumStorageSchema::V3,
+		Box::new(SchemaV3Override::new(client.clone())) as Box<dyn StorageOverride<_> + 'static>,
+	);
+
+	Arc::new(OverrideHandle {
+		schemas: overrides_map,
+		fallback: Box::new(RuntimeApiStorageOverride::new(client)),
+	})
+}
+
+/// Spawns the tasks maintaining the Frontier database, the filter pool and the fee history.
+pub fn spawn_frontier_tasks(
+	task_manager: &TaskManager,
+	client: Arc<FullClient>,
+	backend: Arc<FullBackend>,
+	frontier: &FrontierComponents,
+	eth_config: &EthConfiguration,
+) {
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-mapping-sync-worker",
+		None,
+		MappingSyncWorker::new(
+			client.import_notification_stream(),
+			Duration::new(6, 0),
+			client.clone(),
+			backend,
+			frontier.backend.clone(),
+			SyncStrategy::Normal,
+		)
+		.for_each(|()| future::ready(())),
+	);
+
+	// Each filter is allowed to stay in the pool for 100 blocks.
+	const FILTER_RETAIN_THRESHOLD: u64 = 100;
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-filter-pool",
+		None,
+		EthTask::filter_pool_task(
+			client.clone(),
+			frontier.filter_pool.clone(),
+			FILTER_RETAIN_THRESHOLD,
+		),
+	);
+
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-fee-history",
+		None,
+		EthTask::fee_history_task(
+			client,
+			frontier.overrides.clone(),
+			frontier.fee_history_cache.clone(),
+			eth_config.fee_history_limit,
+		),
+	);
+}
@@ -0,0 +1,228 @@
+//! Optional indexing task, enabled with `--index-db`, that persists blocks with their decoded
+//! extrinsics, events and balance changes to an embedded SQLite database.
+//!
+//! Blocks are indexed as they become part of the best chain and removed again if they are
+//! retracted, so the index follows reorgs of unfinalized blocks. Blocks are marked as finalized
+//! once they are, and forks that can't be finalized anymore are pruned.
+
+pub mod db;
+
+use std::{error::Error as StdError, iter, path::Path, sync::Arc};
+
+use codec::{Decode, Encode};
+use frame_system::Phase;
+use futures::{stream, StreamExt};
+use node_template_runtime::{opaque::Block, Address, Call, Signature, SignedExtra};
+use sc_client_api::{BlockBackend, BlockImportNotification, BlockchainEvents, FinalityNotification};
+use sc_service::TaskManager;
+use sp_blockchain::HeaderBackend;
+use sp_core::H256;
+use sp_runtime::{
+	generic::{BlockId, UncheckedExtrinsic},
+	traits::Header as HeaderT,
+	MultiAddress, OpaqueExtrinsic,
+};
+
+use crate::{
+	metadata::{
+		read_events, value_as_bytes32, value_as_u128, DecodedEvent, Metadata, MetadataCache,
+	},
+	service::FullClient,
+};
+use db::{BalanceChange, BlockRecord, EventRecord, ExtrinsicRecord, IndexDb};
+
+type Error = Box<dyn StdError + Send + Sync>;
+
+/// Opens the index database at `path` and spawns the task keeping it up to date.
+pub fn spawn(
+	task_manager: &TaskManager,
+	client: Arc<FullClient>,
+	path: &Path,
+) -> Result<(), String> {
+	let db = IndexDb::open(path)
+		.map_err(|e| format!("Failed to open the index database {}: {}", path.display(), e))?;
+	let indexer = Indexer { client, db, metadata: Default::default() };
+	task_manager.spawn_handle().spawn_blocking("indexer", Some("indexer"), indexer.run());
+	Ok(())
+}
+
+enum Notification {
+	Import(BlockImportNotification<Block>),
+	Finality(FinalityNotification<Block>),
+}
+
+struct Indexer {
+	client: Arc<FullClient>,
+	db: IndexDb,
+	metadata: MetadataCache,
+}
+
+impl Indexer {
+	async fn run(mut self) {
+		// Subscribe before catching up, so no block is missed in between.
+		let mut notifications = stream::select(
+			self.client.import_notification_stream().map(Notification::Import),
+			self.client.finality_notification_stream().map(Notification::Finality),
+		);
+
+		if let Err(e) = self.catch_up() {
+			log::error!(target: "indexer", "Failed to index the finalized chain: {}", e);
+		}
+
+		while let Some(notification) = notifications.next().await {
+			let result = match notification {
+				Notification::Import(n) => self.on_import(n),
+				Notification::Finality(n) => self.on_finality(n),
+			};
+			if let Err(e) = result {
+				log::error!(target: "indexer", "Failed to index a block: {}", e);
+			}
+		}
+	}
+
+	/// Indexes the finalized blocks imported while the indexer wasn't running.
+	fn catch_up(&mut self) -> Result<(), Error> {
+		let finalized = self.client.info().finalized_number;
+		let from = self.db.last_finalized()?.map_or(0, |number| number + 1);
+		for number in from..=finalized {
+			let hash = self.client.hash(number)?.ok_or("Missing finalized block")?;
+			// Blocks indexed while they were best are stored as unfinalized, and would be pruned.
+			self.index(hash, true)?;
+			self.db.finalize(&hash)?;
+		}
+		self.db.prune_forks(finalized)?;
+		Ok(())
+	}
+
+	fn on_import(&mut self, notification: BlockImportNotification<Block>) -> Result<(), Error> {
+		if !notification.is_new_best {
+			return Ok(())
+		}
+		if let Some(route) = &notification.tree_route {
+			for block in route.retracted() {
+				self.db.remove(&block.hash)?;
+			}
+			for block in route.enacted() {
+				self.index(block.hash, false)?;
+			}
+		}
+		self.index(notification.hash, false)
+	}
+
+	fn on_finality(&mut self, notification: FinalityNotification<Block>) -> Result<(), Error> {
+		// `tree_route` holds the blocks finalized implicitly by finalizing `hash`.
+		for hash in notification.tree_route.iter().chain(iter::once(&notification.hash)) {
+			self.index(*hash, true)?;
+			self.db.finalize(hash)?;
+		}
+		self.db.prune_forks(*notification.header.number())?;
+		Ok(())
+	}
+
+	/// Indexes block `hash`, unless it is indexed already.
+	fn index(&mut self, hash: H256, finalized: bool) -> Result<(), Error> {
+		if self.db.contains(&hash)? {
+			return Ok(())
+		}
+
+		let at = BlockId::Hash(hash);
+		let header = self.client.header(at)?.ok_or("Unknown block")?;
+		let body = self.client.block_body(&at)?.unwrap_or_default();
+		let metadata = self.metadata.at(&*self.client, &at)?;
+		let events = read_events(&*self.client, &at, &metadata)?;
+
+		let extrinsics = body
+			.iter()
+			.enumerate()
+			.map(|(index, extrinsic)| {
+				extrinsic_record(&metadata, extrinsic, outcome(&events, index as u32))
+			})
+			.collect::<Result<_, _>>()?;
+		let events = events.into_iter().map(event_record).collect::<Result<_, _>>()?;
+
+		self.db.insert(&BlockRecord {
+			hash,
+			number: *header.number(),
+			parent_hash: *header.parent_hash(),
+			finalized,
+			extrinsics,
+			events,
+		})?;
+		Ok(())
+	}
+}
+
+/// Whether the extrinsic at `index` was dispatched successfully, according to the system events.
+fn outcome(events: &[(Phase, DecodedEvent)], index: u32) -> Option<bool> {
+	events.iter().find_map(|(phase, event)| match phase {
+		Phase::ApplyExtrinsic(i) if *i == index && event.pallet == "System" =>
+			match event.event.as_str() {
+				"ExtrinsicSuccess" => Some(true),
+				"ExtrinsicFailed" => Some(false),
+				_ => None,
+			},
+		_ => None,
+	})
+}
+
+fn extrinsic_record(
+	metadata: &Metadata,
+	extrinsic: &OpaqueExtrinsic,
+	success: Option<bool>,
+) -> Result<ExtrinsicRecord, Error> {
+	let extrinsic = UncheckedExtrinsic::<Address, Call, Signature, SignedExtra>::decode(
+		&mut &extrinsic.encode()[..],
+	)?;
+	let signer = extrinsic.signature.and_then(|(address, _, _)| match address {
+		MultiAddress::Id(account) => Some(account.into()),
+		_ => None,
+	});
+	let call = metadata.decode_call(&mut &extrinsic.function.encode()[..])?;
+
+	Ok(ExtrinsicRecord {
+		signer,
+		pallet: call.pallet,
+		call: call.call,
+		args: serde_json::to_string(&call.args)?,
+		success,
+	})
+}
+
+fn event_record((phase, event): (Phase, DecodedEvent)) -> Result<EventRecord, Error> {
+	let mut accounts = event.accounts();
+	accounts.sort();
+	accounts.dedup();
+
+	Ok(EventRecord {
+		extrinsic_index: match phase {
+			Phase::ApplyExtrinsic(index) => Some(index),
+			_ => None,
+		},
+		fields: serde_json::to_string(&event.fields)?,
+		balance_changes: balance_changes(&event),
+		accounts,
+		pallet: event.pallet,
+		event: event.event,
+	})
+}
+
+/// Changes of free balances announced by a `pallet_balances` event.
+fn balance_changes(event: &DecodedEvent) -> Vec<([u8; 32], BalanceChange)> {
+	if event.pallet != "Balances" {
+		return Vec::new()
+	}
+
+	let account = |name: &str| event.field(name).and_then(value_as_bytes32);
+	let amount = || event.field("amount").and_then(value_as_u128);
+	let changes = match event.event.as_str() {
+		"Transfer" => vec![
+			(account("from"), amount().map(BalanceChange::Debit)),
+			(account("to"), amount().map(BalanceChange::Credit)),
+		],
+		"Deposit" => vec![(account("who"), amount().map(BalanceChange::Credit))],
+		"Withdraw" | "Slashed" => vec![(account("who"), amount().map(BalanceChange::Debit))],
+		"DustLost" => vec![(account("account"), amount().map(BalanceChange::Debit))],
+		_ => Vec::new(),
+	};
+	changes.into_iter().filter_map(|(account, change)| Some((account?, change?))).collect()
+}
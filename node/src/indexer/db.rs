@@ -0,0 +1,297 @@
+//! SQLite storage of the indexer.
+
+use std::{fmt, path::Path};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+
+/// Largest page served by [`IndexDb::account_history`].
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS blocks (
+		hash BLOB PRIMARY KEY,
+		number INTEGER NOT NULL,
+		parent_hash BLOB NOT NULL,
+		finalized INTEGER NOT NULL DEFAULT 0
+	);
+	CREATE INDEX IF NOT EXISTS blocks_by_number ON blocks (number);
+
+	CREATE TABLE IF NOT EXISTS extrinsics (
+		block_hash BLOB NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+		idx INTEGER NOT NULL,
+		signer BLOB,
+		pallet TEXT NOT NULL,
+		call TEXT NOT NULL,
+		args TEXT NOT NULL,
+		success INTEGER,
+		PRIMARY KEY (block_hash, idx)
+	);
+	CREATE INDEX IF NOT EXISTS extrinsics_by_signer ON extrinsics (signer);
+
+	CREATE TABLE IF NOT EXISTS events (
+		block_hash BLOB NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+		idx INTEGER NOT NULL,
+		extrinsic_idx INTEGER,
+		pallet TEXT NOT NULL,
+		event TEXT NOT NULL,
+		fields TEXT NOT NULL,
+		PRIMARY KEY (block_hash, idx)
+	);
+
+	CREATE TABLE IF NOT EXISTS event_accounts (
+		block_hash BLOB NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+		event_idx INTEGER NOT NULL,
+		account BLOB NOT NULL,
+		PRIMARY KEY (account, block_hash, event_idx)
+	);
+
+	CREATE TABLE IF NOT EXISTS balance_changes (
+		block_hash BLOB NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+		event_idx INTEGER NOT NULL,
+		account BLOB NOT NULL,
+		change TEXT NOT NULL,
+		PRIMARY KEY (account, block_hash, event_idx)
+	);
+";
+
+/// An indexed block.
+pub struct BlockRecord {
+	/// Hash of the block.
+	pub hash: H256,
+	/// Number of the block.
+	pub number: u32,
+	/// Hash of the parent block.
+	pub parent_hash: H256,
+	/// Whether the block is finalized.
+	pub finalized: bool,
+	/// Extrinsics of the block.
+	pub extrinsics: Vec<ExtrinsicRecord>,
+	/// Events deposited in the block.
+	pub events: Vec<EventRecord>,
+}
+
+/// An indexed extrinsic.
+pub struct ExtrinsicRecord {
+	/// Account that signed the extrinsic, if it is signed.
+	pub signer: Option<[u8; 32]>,
+	/// Pallet of the call.
+	pub pallet: String,
+	/// Name of the call.
+	pub call: String,
+	/// Arguments of the call as JSON.
+	pub args: String,
+	/// Whether the call was dispatched successfully, if known.
+	pub success: Option<bool>,
+}
+
+/// An indexed event.
+pub struct EventRecord {
+	/// Index of the extrinsic that deposited the event, if any.
+	pub extrinsic_index: Option<u32>,
+	/// Pallet that deposited the event.
+	pub pallet: String,
+	/// Name of the event.
+	pub event: String,
+	/// Fields of the event as JSON.
+	pub fields: String,
+	/// Accounts held by the fields of the event.
+	pub accounts: Vec<[u8; 32]>,
+	/// Changes of the free balance of accounts caused by the event.
+	pub balance_changes: Vec<([u8; 32], BalanceChange)>,
+}
+
+/// A change of the free balance of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceChange {
+	/// The balance increased by the given amount.
+	Credit(u128),
+	/// The balance decreased by the given amount.
+	Debit(u128),
+}
+
+impl fmt::Display for BalanceChange {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			BalanceChange::Credit(amount) => write!(f, "+{}", amount),
+			BalanceChange::Debit(amount) => write!(f, "-{}", amount),
+		}
+	}
+}
+
+/// An event in the history of an account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryItem {
+	/// Number of the block that deposited the event.
+	pub block_number: u32,
+	/// Hash of the block that deposited the event.
+	pub block_hash: H256,
+	/// Whether the block is finalized. Unfinalized blocks may still be retracted.
+	pub finalized: bool,
+	/// Index of the extrinsic that deposited the event, if any.
+	pub extrinsic_index: Option<u32>,
+	/// Whether the account signed that extrinsic.
+	pub signed: bool,
+	/// Index of the event in its block.
+	pub event_index: u32,
+	/// Pallet that deposited the event.
+	pub pallet: String,
+	/// Name of the event.
+	pub event: String,
+	/// Fields of the event.
+	pub fields: serde_json::Value,
+	/// Change of the account's free balance caused by the event, e.g. `-1500`.
+	pub balance_change: Option<String>,
+}
+
+/// Connection to the index database.
+pub struct IndexDb(Connection);
+
+impl IndexDb {
+	/// Opens or creates the index database at `path`.
+	pub fn open(path: &Path) -> rusqlite::Result<Self> {
+		let connection = Connection::open(path)?;
+		// Let the RPC read while the indexer writes.
+		connection.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+		connection.pragma_update(None, "foreign_keys", true)?;
+		connection.execute_batch(SCHEMA)?;
+		Ok(Self(connection))
+	}
+
+	/// Whether block `hash` is indexed.
+	pub fn contains(&self, hash: &H256) -> rusqlite::Result<bool> {
+		self.0
+			.query_row("SELECT 1 FROM blocks WHERE hash = ?", params![hash.as_bytes()], |_| Ok(()))
+			.optional()
+			.map(|row| row.is_some())
+	}
+
+	/// Number of the latest finalized block that is indexed.
+	pub fn last_finalized(&self) -> rusqlite::Result<Option<u32>> {
+		self.0
+			.query_row("SELECT MAX(number) FROM blocks WHERE finalized = 1", [], |row| row.get(0))
+	}
+
+	/// Stores `block` with its extrinsics and events.
+	pub fn insert(&mut self, block: &BlockRecord) -> rusqlite::Result<()> {
+		let tx = self.0.transaction()?;
+		let hash = block.hash.as_bytes();
+		tx.execute(
+			"INSERT INTO blocks (hash, number, parent_hash, finalized) VALUES (?, ?, ?, ?)",
+			params![hash, block.number, block.parent_hash.as_bytes(), block.finalized],
+		)?;
+
+		for (index, extrinsic) in block.extrinsics.iter().enumerate() {
+			tx.execute(
+				"INSERT INTO extrinsics (block_hash, idx, signer, pallet, call, args, success)
+				VALUES (?, ?, ?, ?, ?, ?, ?)",
+				params![
+					hash,
+					index as u32,
+					extrinsic.signer.as_ref().map(|signer| &signer[..]),
+					extrinsic.pallet,
+					extrinsic.call,
+					extrinsic.args,
+					extrinsic.success,
+				],
+			)?;
+		}
+
+		for (index, event) in block.events.iter().enumerate() {
+			let index = index as u32;
+			tx.execute(
+				"INSERT INTO events (block_hash, idx, extrinsic_idx, pallet, event, fields)
+				VALUES (?, ?, ?, ?, ?, ?)",
+				params![
+					hash,
+					index,
+					event.extrinsic_index,
+					event.pallet,
+					event.event,
+					event.fields,
+				],
+			)?;
+			for account in &event.accounts {
+				tx.execute(
+					"INSERT OR IGNORE INTO event_accounts (block_hash, event_idx, account)
+					VALUES (?, ?, ?)",
+					params![hash, index, &account[..]],
+				)?;
+			}
+			for (account, change) in &event.balance_changes {
+				tx.execute(
+					"INSERT OR REPLACE INTO balance_changes (block_hash, event_idx, account, change)
+					VALUES (?, ?, ?, ?)",
+					params![hash, index, &account[..], change.to_string()],
+				)?;
+			}
+		}
+
+		tx.commit()
+	}
+
+	/// Removes block `hash` and everything deposited in it.
+	pub fn remove(&self, hash: &H256) -> rusqlite::Result<()> {
+		self.0.execute("DELETE FROM blocks WHERE hash = ?", params![hash.as_bytes()]).map(|_| ())
+	}
+
+	/// Marks block `hash` as finalized.
+	pub fn finalize(&self, hash: &H256) -> rusqlite::Result<()> {
+		self.0
+			.execute("UPDATE blocks SET finalized = 1 WHERE hash = ?", params![hash.as_bytes()])
+			.map(|_| ())
+	}
+
+	/// Removes the unfinalized blocks up to `number`, which can't become finalized anymore.
+	pub fn prune_forks(&self, number: u32) -> rusqlite::Result<()> {
+		self.0
+			.execute("DELETE FROM blocks WHERE finalized = 0 AND number <= ?", params![number])
+			.map(|_| ())
+	}
+
+	/// Returns up to `limit` events mentioning `account`, newest first, skipping the first
+	/// `offset` ones.
+	pub fn account_history(
+		&self,
+		account: &[u8; 32],
+		offset: u32,
+		limit: u32,
+	) -> rusqlite::Result<Vec<HistoryItem>> {
+		let mut statement = self.0.prepare_cached(
+			"SELECT b.number, b.hash, b.finalized, e.extrinsic_idx, x.signer = a.account, e.idx,
+				e.pallet, e.event, e.fields, c.change
+			FROM event_accounts a
+			JOIN blocks b ON b.hash = a.block_hash
+			JOIN events e ON e.block_hash = a.block_hash AND e.idx = a.event_idx
+			LEFT JOIN extrinsics x ON x.block_hash = e.block_hash AND x.idx = e.extrinsic_idx
+			LEFT JOIN balance_changes c ON c.block_hash = a.block_hash
+				AND c.event_idx = a.event_idx
+				AND c.account = a.account
+			WHERE a.account = ?
+			ORDER BY b.number DESC, e.idx DESC
+			LIMIT ? OFFSET ?",
+		)?;
+		let rows = statement.query_map(
+			params![&account[..], limit.min(MAX_PAGE_SIZE), offset],
+			|row| {
+				let hash: Vec<u8> = row.get(1)?;
+				let fields: String = row.get(8)?;
+				Ok(HistoryItem {
+					block_number: row.get(0)?,
+					block_hash: H256::from_slice(&hash),
+					finalized: row.get(2)?,
+					extrinsic_index: row.get(3)?,
+					signed: row.get::<_, Option<bool>>(4)?.unwrap_or(false),
+					event_index: row.get(5)?,
+					pallet: row.get(6)?,
+					event: row.get(7)?,
+					fields: serde_json::from_str(&fields).unwrap_or(serde_json::Value::Null),
+					balance_change: row.get(9)?,
+				})
+			},
+		)?;
+		rows.collect()
+	}
+}
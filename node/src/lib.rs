@@ -1,3 +1,13 @@
+#[cfg(not(feature = "pow"))]
+pub mod backoff;
 pub mod chain_spec;
+pub mod command_helper;
+#[cfg(feature = "evm")]
+pub mod eth;
+pub mod indexer;
+pub mod metadata;
+pub mod metadata_diff;
+#[cfg(feature = "pow")]
+pub mod pow;
 pub mod rpc;
 pub mod service;
@@ -0,0 +1,216 @@
+//! The `benchmark limits` command: fills blocks with as many extrinsics of common calls as they
+//! take, and checks their weight, length and execution time against the limits of the runtime.
+//!
+//! Like `benchmark overhead`, the blocks are built on top of the best block with the timestamp of
+//! the genesis block, so the command runs on a fresh chain, e.g. with `--dev`. Weights are
+//! calibrated for the reference hardware of `benchmark machine`, execution times measured on
+//! slower machines may exceed them.
+
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use codec::{Decode, Encode};
+use frame_support::weights::{DispatchClass, GetDispatchInfo, Weight};
+use node_template_runtime::{
+	self as runtime, opaque::Block, pallet_template, BalancesCall, BlockLength, BlockWeights,
+	SystemCall, WEIGHT_PER_SECOND,
+};
+use sc_block_builder::BlockBuilderProvider;
+use sc_cli::{CliConfiguration, ImportParams, SharedParams};
+use sp_api::{Core, ProvideRuntimeApi};
+use sp_blockchain::{ApplyExtrinsicFailed, Error as BlockchainError};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{generic::BlockId, MultiAddress};
+
+use crate::{
+	command_helper::{create_benchmark_extrinsic, inherent_benchmark_data},
+	service::FullClient,
+};
+
+/// The `benchmark limits` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct LimitsCmd {
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+
+	/// Number of times each block is executed, the median execution time is reported.
+	#[clap(long, default_value = "10")]
+	pub repeat: u32,
+}
+
+/// The calls blocks are filled with, by name. They are signed by Alice, who must be endowed.
+fn calls() -> Vec<(&'static str, runtime::Call)> {
+	let bob = MultiAddress::Id(Sr25519Keyring::Bob.to_account_id());
+	let do_something = pallet_template::Call::do_something { something: 42 };
+	let transfer = BalancesCall::transfer_keep_alive { dest: bob, value: 1 };
+	let remark = SystemCall::remark { remark: vec![] };
+	vec![
+		("TemplateModule::do_something", do_something.into()),
+		("Balances::transfer_keep_alive", transfer.into()),
+		("System::remark", remark.into()),
+	]
+}
+
+/// What a full block consumes.
+struct Report {
+	name: &'static str,
+	/// Number of extrinsics of the call in the block.
+	extrinsics: usize,
+	/// Weight of the normal extrinsics.
+	normal_weight: Weight,
+	/// Weight of the whole block, including its inherents and the base weight of a block.
+	total_weight: Weight,
+	/// Length of all extrinsics.
+	length: u32,
+	/// Median execution time.
+	time: Duration,
+}
+
+impl LimitsCmd {
+	/// Builds a full block of each call, prints a report and fails if a block exceeds a limit.
+	pub fn run(&self, client: Arc<FullClient>) -> sc_cli::Result<()> {
+		let weights = BlockWeights::get();
+		let max_normal = weights.get(DispatchClass::Normal).max_total.unwrap_or(weights.max_block);
+		let max_length = *BlockLength::get().max.get(DispatchClass::Normal);
+
+		println!(
+			"Limits: {} of normal weight, {} of weight in total, {} bytes of extrinsics",
+			seconds(max_normal),
+			seconds(weights.max_block),
+			max_length
+		);
+		println!(
+			"{:<30} {:>10} {:>18} {:>18} {:>26}",
+			"Call", "Extrinsics", "Normal weight", "Length", "Execution (of weight)"
+		);
+
+		let mut exceeded = Vec::new();
+		for (name, call) in calls() {
+			let block = build_block(&client, &call)?;
+			let report = self.measure(&client, name, block)?;
+			println!(
+				"{:<30} {:>10} {:>9} ({:>5.1}%) {:>9} ({:>5.1}%) {:>8.3} s of {} ({:>5.1}%)",
+				report.name,
+				report.extrinsics,
+				seconds(report.normal_weight),
+				percent(report.normal_weight as f64, max_normal as f64),
+				report.length,
+				percent(report.length.into(), max_length.into()),
+				report.time.as_secs_f64(),
+				seconds(report.total_weight),
+				percent(report.time.as_nanos() as f64 * 1_000.0, report.total_weight as f64),
+			);
+
+			if report.normal_weight > max_normal || report.total_weight > weights.max_block {
+				exceeded.push(format!("{} exceeds the block weight", name));
+			}
+			if report.length > max_length {
+				exceeded.push(format!("{} exceeds the block length", name));
+			}
+			if report.time > to_duration(report.total_weight) {
+				exceeded.push(format!("{} takes longer to execute than its weight", name));
+			}
+		}
+
+		if exceeded.is_empty() {
+			Ok(())
+		} else {
+			Err(exceeded.join(", ").into())
+		}
+	}
+
+	/// Weighs `block` and times its execution on top of its parent.
+	fn measure(
+		&self,
+		client: &FullClient,
+		name: &'static str,
+		block: Block,
+	) -> sc_cli::Result<Report> {
+		let weights = BlockWeights::get();
+		let mut report = Report {
+			name,
+			extrinsics: 0,
+			normal_weight: 0,
+			total_weight: weights.base_block,
+			length: 0,
+			time: Duration::ZERO,
+		};
+		for extrinsic in &block.extrinsics {
+			let encoded = extrinsic.encode();
+			let info = runtime::UncheckedExtrinsic::decode(&mut &encoded[..])
+				.map_err(|e| format!("decoding an extrinsic of the block: {}", e))?
+				.get_dispatch_info();
+			let weight = info.weight + weights.get(info.class).base_extrinsic;
+			if info.class == DispatchClass::Normal {
+				report.extrinsics += 1;
+				report.normal_weight += weight;
+			}
+			report.total_weight += weight;
+			report.length += encoded.len() as u32;
+		}
+
+		let parent = BlockId::Hash(block.header.parent_hash);
+		let mut times = Vec::new();
+		for _ in 0..self.repeat.max(1) {
+			let block = block.clone();
+			let runtime_api = client.runtime_api();
+			let started = Instant::now();
+			runtime_api
+				.execute_block(&parent, block)
+				.map_err(|e| format!("executing the block of {}: {}", name, e))?;
+			times.push(started.elapsed());
+		}
+		times.sort();
+		report.time = times[times.len() / 2];
+		Ok(report)
+	}
+}
+
+/// Builds a block on top of the best block, with as many extrinsics of `call` as it takes.
+fn build_block(client: &FullClient, call: &runtime::Call) -> sc_cli::Result<Block> {
+	let mut builder = client.new_block(Default::default())?;
+	for inherent in builder.create_inherents(inherent_benchmark_data()?)? {
+		builder.push(inherent)?;
+	}
+
+	for nonce in 0.. {
+		let signer = Sr25519Keyring::Alice.pair();
+		let extrinsic = create_benchmark_extrinsic(client, signer, call.clone(), nonce);
+		match builder.push(extrinsic.into()) {
+			Ok(()) => (),
+			Err(BlockchainError::ApplyExtrinsicFailed(ApplyExtrinsicFailed::Validity(e)))
+				if e.exhausted_resources() =>
+				break,
+			Err(e) => return Err(format!("applying {:?}: {}", call, e).into()),
+		}
+	}
+	Ok(builder.build()?.block)
+}
+
+fn seconds(weight: Weight) -> String {
+	format!("{:.3} s", weight as f64 / WEIGHT_PER_SECOND as f64)
+}
+
+/// Time it takes the reference hardware to execute `weight`.
+fn to_duration(weight: Weight) -> Duration {
+	Duration::from_nanos(weight / (WEIGHT_PER_SECOND / 1_000_000_000))
+}
+
+fn percent(part: f64, whole: f64) -> f64 {
+	part / whole * 100.0
+}
+
+impl CliConfiguration for LimitsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -0,0 +1,497 @@
+//! The `load` subcommand: submits a mix of signed extrinsics to a node over its websocket RPC at a
+//! steady rate, and reports how long they took to be included and how many the pool rejected.
+//!
+//! The extrinsics are signed by accounts derived from a seed, which are funded by a development
+//! account first. Their nonces are tracked locally, and re-synced from the node when the pool
+//! rejects an extrinsic, so the accounts should not be used by anyone else during the run.
+
+use std::{
+	collections::BTreeMap,
+	fmt,
+	str::FromStr,
+	sync::{
+		atomic::{AtomicU32, Ordering},
+		Arc,
+	},
+	time::{Duration, Instant},
+};
+
+use codec::Encode;
+use futures::{future::join_all, StreamExt};
+use jsonrpsee::{
+	core::client::{ClientT, Subscription, SubscriptionClientT},
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_template_runtime::{self as runtime, pallet_template, AccountId, Balance, BalancesCall};
+use serde_json::Value;
+use sp_core::{sr25519, Bytes, Pair};
+use sp_runtime::{generic::Era, MultiAddress};
+
+use crate::command_helper::{create_signed_extrinsic, SigningContext};
+
+/// How long an extrinsic may take to be included before it counts as lost.
+const INCLUSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum number of extrinsics waiting for their inclusion at once.
+const MAX_IN_FLIGHT: usize = 16 * 1024;
+
+/// The `load` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct LoadCmd {
+	/// Websocket RPC endpoint of the node to load.
+	#[clap(long, default_value = "ws://127.0.0.1:9944")]
+	pub rpc: String,
+
+	/// Number of extrinsics submitted per second.
+	#[clap(long, default_value = "10")]
+	pub tps: u32,
+
+	/// Number of accounts signing the extrinsics, derived as `<seed>//0`, `<seed>//1`...
+	#[clap(long, default_value = "10")]
+	pub accounts: u32,
+
+	/// Share of each call in the extrinsics, out of the sum of the shares. The calls are `remark`
+	/// (`System::remark`), `transfer` (`Balances::transfer_keep_alive` to the next account) and
+	/// `template` (`TemplateModule::do_something`).
+	#[clap(long, default_value = "remark:50,transfer:30,template:20")]
+	pub mix: Mix,
+
+	/// Number of seconds extrinsics are submitted for.
+	#[clap(long, default_value = "60")]
+	pub duration: u64,
+
+	/// Seed the accounts are derived from.
+	#[clap(long, default_value = "//Load")]
+	pub seed: String,
+
+	/// Seed of the account funding the others before the load starts.
+	#[clap(long, default_value = "//Alice")]
+	pub funder: String,
+
+	/// Balance transferred to each account before the load starts, `0` to skip the funding.
+	#[clap(long, default_value = "1000000000000000")]
+	pub funding: Balance,
+}
+
+/// A kind of call submitted by the `load` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+	/// An empty `System::remark`.
+	Remark,
+	/// A `Balances::transfer_keep_alive` of 1 unit to the next account.
+	Transfer,
+	/// A `TemplateModule::do_something` storing the index of the extrinsic.
+	Template,
+}
+
+impl FromStr for CallKind {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"remark" => Ok(CallKind::Remark),
+			"transfer" => Ok(CallKind::Transfer),
+			"template" => Ok(CallKind::Template),
+			s => Err(format!("unknown call `{}`, expected remark, transfer or template", s)),
+		}
+	}
+}
+
+/// Shares of the kinds of calls, e.g. `remark:50,transfer:30,template:20`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mix(Vec<(CallKind, u32)>);
+
+impl FromStr for Mix {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let shares = s
+			.split(',')
+			.map(|share| {
+				let (kind, weight) = share
+					.split_once(':')
+					.ok_or_else(|| format!("`{}` is not of the form `<call>:<share>`", share))?;
+				let weight =
+					weight.parse().map_err(|e| format!("invalid share `{}`: {}", weight, e))?;
+				Ok((kind.parse()?, weight))
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+		if shares.iter().all(|(_, weight)| *weight == 0) {
+			return Err("the mix needs a call with a nonzero share".into())
+		}
+		Ok(Mix(shares))
+	}
+}
+
+impl Mix {
+	/// Spreads the calls evenly, picking them by smooth weighted round-robin.
+	fn picker(&self) -> impl FnMut() -> CallKind + '_ {
+		let total: i64 = self.0.iter().map(|(_, weight)| i64::from(*weight)).sum();
+		let mut current = vec![0; self.0.len()];
+		move || {
+			for (current, (_, weight)) in current.iter_mut().zip(&self.0) {
+				*current += i64::from(*weight);
+			}
+			let (index, _) = current
+				.iter()
+				.enumerate()
+				.max_by_key(|(index, current)| (**current, std::cmp::Reverse(*index)))
+				.expect("a mix has calls");
+			current[index] -= total;
+			self.0[index].0
+		}
+	}
+}
+
+/// What happened to a submitted extrinsic.
+enum Outcome {
+	/// Included in a block after this long.
+	Included(Duration),
+	/// Rejected by the pool on submission.
+	Rejected(String),
+	/// Accepted by the pool, and then dropped or found invalid.
+	Dropped(String),
+	/// Not included within `INCLUSION_TIMEOUT`.
+	TimedOut,
+}
+
+impl LoadCmd {
+	/// Runs the load and prints its report.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		if self.tps == 0 || self.accounts == 0 {
+			return Err("`--tps` and `--accounts` must be positive".into())
+		}
+		let runtime = tokio::runtime::Runtime::new()?;
+		runtime.block_on(self.run_load())
+	}
+
+	async fn run_load(&self) -> sc_cli::Result<()> {
+		let client = WsClientBuilder::default()
+			.max_concurrent_requests(MAX_IN_FLIGHT)
+			.build(&self.rpc)
+			.await
+			.map_err(|e| format!("connecting to {}: {}", self.rpc, e))?;
+		let client = Arc::new(client);
+		let context = signing_context(&client).await?;
+
+		let accounts = (0..self.accounts)
+			.map(|i| derive(&format!("{}//{}", self.seed, i)))
+			.collect::<sc_cli::Result<Vec<_>>>()?;
+		if self.funding > 0 {
+			self.fund(&client, &context, &accounts).await?;
+		}
+		// Shared with the submissions, which re-sync the nonce of their sender when rejected.
+		let mut nonces = Vec::new();
+		for account in &accounts {
+			nonces.push(AtomicU32::new(next_nonce(&client, &account_id(account)).await?));
+		}
+		let nonces = Arc::new(nonces);
+
+		eprintln!(
+			"Submitting {} extrinsics per second for {} s from {} accounts",
+			self.tps, self.duration, self.accounts
+		);
+		let mut next_call = self.mix.picker();
+		let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / f64::from(self.tps)));
+		let started = Instant::now();
+		let mut submissions = Vec::new();
+		let mut index = 0;
+		while started.elapsed() < Duration::from_secs(self.duration) {
+			ticker.tick().await;
+			let sender = index % accounts.len();
+			let call = match next_call() {
+				CallKind::Remark => runtime::SystemCall::remark { remark: vec![] }.into(),
+				CallKind::Transfer => {
+					let dest = account_id(&accounts[(sender + 1) % accounts.len()]);
+					BalancesCall::transfer_keep_alive { dest: MultiAddress::Id(dest), value: 1 }
+						.into()
+				},
+				CallKind::Template =>
+					pallet_template::Call::do_something { something: index as u32 }.into(),
+			};
+			let nonce = nonces[sender].fetch_add(1, Ordering::SeqCst);
+			let extrinsic = create_signed_extrinsic(&accounts[sender], call, nonce, &context);
+			submissions.push(tokio::spawn(submit_from(
+				client.clone(),
+				extrinsic,
+				account_id(&accounts[sender]),
+				nonces.clone(),
+				sender,
+			)));
+			index += 1;
+		}
+		let elapsed = started.elapsed();
+
+		eprintln!("Waiting for the last extrinsics to be included");
+		let outcomes = join_all(submissions)
+			.await
+			.into_iter()
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("watching an extrinsic: {}", e))?;
+		report(&outcomes, elapsed);
+		Ok(())
+	}
+
+	/// Transfers `funding` from the funder to every account and waits for the transfers to be
+	/// included.
+	async fn fund(
+		&self,
+		client: &Arc<WsClient>,
+		context: &SigningContext,
+		accounts: &[sr25519::Pair],
+	) -> sc_cli::Result<()> {
+		eprintln!("Funding {} accounts from {}", accounts.len(), self.funder);
+		let funder = derive(&self.funder)?;
+		let mut nonce = next_nonce(client, &account_id(&funder)).await?;
+		let mut transfers = Vec::new();
+		for account in accounts {
+			let dest = MultiAddress::Id(account_id(account));
+			let call = BalancesCall::transfer_keep_alive { dest, value: self.funding };
+			let extrinsic = create_signed_extrinsic(&funder, call.into(), nonce, context);
+			nonce += 1;
+			transfers.push(submit(client.clone(), extrinsic));
+		}
+		for outcome in join_all(transfers).await {
+			match outcome {
+				Outcome::Included(_) => (),
+				Outcome::Rejected(e) | Outcome::Dropped(e) =>
+					return Err(format!("funding an account failed: {}", e).into()),
+				Outcome::TimedOut => return Err("funding an account timed out".into()),
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Fetches what the extrinsics of the chain are signed for. They are immortal, so that the load
+/// doesn't depend on how far the chain is.
+async fn signing_context(client: &WsClient) -> sc_cli::Result<SigningContext> {
+	let genesis_hash: runtime::Hash = client
+		.request("chain_getBlockHash", rpc_params![0])
+		.await
+		.map_err(|e| format!("querying the genesis hash: {}", e))?;
+	let version: Value = client
+		.request("state_getRuntimeVersion", None)
+		.await
+		.map_err(|e| format!("querying the runtime version: {}", e))?;
+	let version_field = |name: &str| -> sc_cli::Result<u32> {
+		version[name]
+			.as_u64()
+			.and_then(|v| u32::try_from(v).ok())
+			.ok_or_else(|| format!("the runtime version has no {}", name).into())
+	};
+
+	Ok(SigningContext {
+		genesis_hash,
+		spec_version: version_field("specVersion")?,
+		transaction_version: version_field("transactionVersion")?,
+		era: Era::Immortal,
+		era_hash: genesis_hash,
+	})
+}
+
+async fn next_nonce(client: &WsClient, account: &AccountId) -> sc_cli::Result<u32> {
+	client
+		.request("system_accountNextIndex", rpc_params![account])
+		.await
+		.map_err(|e| format!("querying the nonce of {}: {}", account, e).into())
+}
+
+fn derive(suri: &str) -> sc_cli::Result<sr25519::Pair> {
+	sr25519::Pair::from_string(suri, None)
+		.map_err(|e| format!("invalid seed `{}`: {:?}", suri, e).into())
+}
+
+fn account_id(pair: &sr25519::Pair) -> AccountId {
+	pair.public().into()
+}
+
+/// Submits `extrinsic` and watches it until it is included or lost.
+async fn submit(client: Arc<WsClient>, extrinsic: runtime::UncheckedExtrinsic) -> Outcome {
+	let started = Instant::now();
+	let subscription: Result<Subscription<Value>, _> = client
+		.subscribe(
+			"author_submitAndWatchExtrinsic",
+			rpc_params![Bytes(extrinsic.encode())],
+			"author_unwatchExtrinsic",
+		)
+		.await;
+	let mut subscription = match subscription {
+		Ok(subscription) => subscription,
+		Err(e) => return Outcome::Rejected(e.to_string()),
+	};
+
+	let included = async {
+		while let Some(status) = subscription.next().await {
+			match status {
+				Ok(Value::Object(status)) if status.contains_key("inBlock") =>
+					return Outcome::Included(started.elapsed()),
+				Ok(Value::Object(status)) if status.contains_key("usurped") =>
+					return Outcome::Dropped("usurped".into()),
+				Ok(Value::String(status)) if status == "dropped" || status == "invalid" =>
+					return Outcome::Dropped(status),
+				Ok(_) => (),
+				Err(e) => return Outcome::Dropped(e.to_string()),
+			}
+		}
+		Outcome::Dropped("the node stopped watching the extrinsic".into())
+	};
+	tokio::time::timeout(INCLUSION_TIMEOUT, included).await.unwrap_or(Outcome::TimedOut)
+}
+
+/// Submits `extrinsic` of the `sender`th account like [`submit`]. If the pool rejects it, the
+/// nonce was not used, and the account's next extrinsics would wait for it forever: the nonce of
+/// the account is re-synced from the node instead.
+async fn submit_from(
+	client: Arc<WsClient>,
+	extrinsic: runtime::UncheckedExtrinsic,
+	account: AccountId,
+	nonces: Arc<Vec<AtomicU32>>,
+	sender: usize,
+) -> Outcome {
+	let outcome = submit(client.clone(), extrinsic).await;
+	if let Outcome::Rejected(_) = outcome {
+		match next_nonce(&client, &account).await {
+			Ok(nonce) => nonces[sender].store(nonce, Ordering::SeqCst),
+			Err(e) => eprintln!("Keeping the nonce of {} after a rejection: {}", account, e),
+		}
+	}
+	outcome
+}
+
+/// Prints the counts of outcomes, the inclusion latency percentiles and the rejection reasons.
+fn report(outcomes: &[Outcome], elapsed: Duration) {
+	let mut latencies = Vec::new();
+	let mut rejections = BTreeMap::<&str, usize>::new();
+	let mut drops = BTreeMap::<&str, usize>::new();
+	let mut timeouts = 0;
+	for outcome in outcomes {
+		match outcome {
+			Outcome::Included(latency) => latencies.push(*latency),
+			Outcome::Rejected(reason) => *rejections.entry(reason).or_default() += 1,
+			Outcome::Dropped(reason) => *drops.entry(reason).or_default() += 1,
+			Outcome::TimedOut => timeouts += 1,
+		}
+	}
+	latencies.sort();
+
+	println!(
+		"Submitted {} extrinsics in {:.1} s ({:.1} per second)",
+		outcomes.len(),
+		elapsed.as_secs_f64(),
+		outcomes.len() as f64 / elapsed.as_secs_f64()
+	);
+	println!(
+		"Included: {}, rejected by the pool: {}, dropped: {}, not included within {} s: {}",
+		latencies.len(),
+		rejections.values().sum::<usize>(),
+		drops.values().sum::<usize>(),
+		INCLUSION_TIMEOUT.as_secs(),
+		timeouts
+	);
+	if !latencies.is_empty() {
+		println!(
+			"Inclusion latency: p50 {}, p90 {}, p99 {}, max {}",
+			Latency(percentile(&latencies, 0.5)),
+			Latency(percentile(&latencies, 0.9)),
+			Latency(percentile(&latencies, 0.99)),
+			Latency(latencies[latencies.len() - 1]),
+		);
+	}
+	for (reason, count) in rejections {
+		println!("Rejected {} times: {}", count, reason);
+	}
+	for (reason, count) in drops {
+		println!("Dropped {} times: {}", count, reason);
+	}
+}
+
+/// The latency below which a `p` fraction of the sorted `latencies` are.
+fn percentile(latencies: &[Duration], p: f64) -> Duration {
+	let index = ((latencies.len() - 1) as f64 * p).round() as usize;
+	latencies[index]
+}
+
+struct Latency(Duration);
+
+impl fmt::Display for Latency {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:.2} s", self.0.as_secs_f64())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_mixes() {
+		assert_eq!(
+			"remark:50,transfer:30,template:20".parse::<Mix>(),
+			Ok(Mix(vec![
+				(CallKind::Remark, 50),
+				(CallKind::Transfer, 30),
+				(CallKind::Template, 20)
+			]))
+		);
+		assert_eq!("transfer:1".parse::<Mix>(), Ok(Mix(vec![(CallKind::Transfer, 1)])));
+		assert_eq!("remark:0,template:3".parse::<Mix>().map(|mix| mix.0.len()), Ok(2));
+	}
+
+	#[test]
+	fn rejects_invalid_mixes() {
+		assert_eq!(
+			"remark".parse::<Mix>(),
+			Err("`remark` is not of the form `<call>:<share>`".into())
+		);
+		assert!("remark:half".parse::<Mix>().unwrap_err().starts_with("invalid share `half`"));
+		assert!("remark:-1".parse::<Mix>().unwrap_err().starts_with("invalid share `-1`"));
+		assert_eq!(
+			"remark:1,stake:1".parse::<Mix>(),
+			Err("unknown call `stake`, expected remark, transfer or template".into())
+		);
+		assert_eq!(
+			"remark:0,transfer:0".parse::<Mix>(),
+			Err("the mix needs a call with a nonzero share".into())
+		);
+	}
+
+	#[test]
+	fn picks_calls_by_their_shares() {
+		let mix: Mix = "remark:50,transfer:30,template:20".parse().unwrap();
+		let mut next_call = mix.picker();
+		let picks: Vec<_> = (0..100).map(|_| next_call()).collect();
+
+		let count = |picks: &[CallKind], kind| picks.iter().filter(|pick| **pick == kind).count();
+		assert_eq!(count(&picks, CallKind::Remark), 50);
+		assert_eq!(count(&picks, CallKind::Transfer), 30);
+		assert_eq!(count(&picks, CallKind::Template), 20);
+		// The calls are spread evenly: every 10 picks follow the shares.
+		for window in picks.chunks(10) {
+			assert_eq!(count(window, CallKind::Remark), 5);
+			assert_eq!(count(window, CallKind::Transfer), 3);
+			assert_eq!(count(window, CallKind::Template), 2);
+		}
+	}
+
+	#[test]
+	fn never_picks_calls_without_share() {
+		let mix: Mix = "remark:0,template:1".parse().unwrap();
+		let mut next_call = mix.picker();
+		assert!((0..10).all(|_| next_call() == CallKind::Template));
+	}
+
+	#[test]
+	fn computes_percentiles_of_sorted_latencies() {
+		let latencies: Vec<_> = (1..=100).map(Duration::from_millis).collect();
+		assert_eq!(percentile(&latencies, 0.0), Duration::from_millis(1));
+		assert_eq!(percentile(&latencies, 0.5), Duration::from_millis(51));
+		assert_eq!(percentile(&latencies, 0.9), Duration::from_millis(90));
+		assert_eq!(percentile(&latencies, 0.99), Duration::from_millis(99));
+		assert_eq!(percentile(&latencies, 1.0), Duration::from_millis(100));
+
+		let single = [Duration::from_secs(3)];
+		assert_eq!(percentile(&single, 0.5), Duration::from_secs(3));
+	}
+}
@@ -0,0 +1,277 @@
+//! The `local-net` subcommand: runs a local network of validators and full nodes, each in a child
+//! process with its own base path, keystore, node key and ports.
+//!
+//! Everything is derived from the node's index and name, so a network started twice in the same
+//! base path gets the same chain spec, peer ids and ports, and resumes from its previous state.
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	process::{ExitStatus, Stdio},
+	time::Duration,
+};
+
+use futures::future::select_all;
+use node_template_runtime::opaque::SessionKeys;
+use sc_network::config::{identity::ed25519, NodeKeyConfig, Secret};
+use sc_service::ChainSpec;
+use sp_core::{blake2_256, hexdisplay::HexDisplay};
+use sp_keystore::SyncCryptoStore;
+use sp_runtime::traits::OpaqueKeys;
+use tokio::{
+	io::{AsyncBufReadExt, AsyncRead, BufReader},
+	process::{Child, Command},
+};
+
+use crate::chain_spec;
+
+/// Names of the first validators, whose keys are those of the well-known development accounts.
+const WELL_KNOWN: [&str; 6] = ["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"];
+
+/// How long the nodes get to shut down before they are killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The `local-net` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct LocalNetCmd {
+	/// Number of validators, named after the well-known development accounts and then
+	/// `Validator7`, `Validator8`...
+	#[clap(long, default_value = "2")]
+	pub validators: u16,
+
+	/// Number of full nodes, named `Full1`, `Full2`...
+	#[clap(long, default_value = "0")]
+	pub full_nodes: u16,
+
+	/// Directory holding the chain spec and a base path per node. Defaults to
+	/// `node-template-local-net` in the temporary directory.
+	#[clap(long)]
+	pub base_path: Option<PathBuf>,
+
+	/// libp2p port of the first node. The nth node listens on this port plus n.
+	#[clap(long, default_value = "30333")]
+	pub port: u16,
+
+	/// HTTP RPC port of the first node. The nth node listens on this port plus n.
+	#[clap(long, default_value = "9933")]
+	pub rpc_port: u16,
+
+	/// Websocket RPC port of the first node. The nth node listens on this port plus n.
+	#[clap(long, default_value = "9944")]
+	pub ws_port: u16,
+
+	/// Prometheus port of the first node. The nth node listens on this port plus n.
+	#[clap(long, default_value = "9615")]
+	pub prometheus_port: u16,
+
+	/// Arguments passed to every node, e.g. `-- --log runtime=debug`.
+	#[clap(last = true)]
+	pub node_args: Vec<String>,
+}
+
+/// A node of the network.
+struct NodeConfig {
+	name: String,
+	/// Seed of the node's session keys, if it is a validator.
+	seed: Option<String>,
+	index: u16,
+}
+
+impl NodeConfig {
+	fn base_path(&self, base_path: &Path) -> PathBuf {
+		base_path.join(self.name.to_lowercase())
+	}
+
+	/// Secret of the node's libp2p key.
+	fn node_key(&self) -> [u8; 32] {
+		blake2_256(format!("local-net//{}", self.name).as_bytes())
+	}
+}
+
+impl LocalNetCmd {
+	/// Runs the network until Ctrl-C is pressed or a node exits.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		if self.validators == 0 {
+			return Err("a local network needs at least one validator".into())
+		}
+		let nodes = self.nodes();
+		let base_path = self.base_path();
+
+		fs::create_dir_all(&base_path)?;
+		let seeds: Vec<_> = nodes.iter().filter_map(|node| node.seed.clone()).collect();
+		let spec = chain_spec::local_net_config(&seeds)?;
+		let spec_path = base_path.join("chain-spec.json");
+		fs::write(&spec_path, spec.as_json(true)?)?;
+
+		for node in &nodes {
+			if let Some(seed) = &node.seed {
+				let keystore = node.base_path(&base_path).join("chains").join(spec.id());
+				insert_session_keys(&keystore.join("keystore"), seed)?;
+			}
+		}
+
+		let runtime = tokio::runtime::Runtime::new()?;
+		runtime.block_on(self.run_nodes(&nodes, &base_path, &spec_path))
+	}
+
+	fn base_path(&self) -> PathBuf {
+		self.base_path
+			.clone()
+			.unwrap_or_else(|| std::env::temp_dir().join("node-template-local-net"))
+	}
+
+	fn nodes(&self) -> Vec<NodeConfig> {
+		let validators = (0..self.validators).map(|index| {
+			let name = match WELL_KNOWN.get(usize::from(index)) {
+				Some(name) => name.to_string(),
+				None => format!("Validator{}", index + 1),
+			};
+			NodeConfig { seed: Some(name.clone()), name, index }
+		});
+		let full_nodes = (0..self.full_nodes).map(|n| NodeConfig {
+			name: format!("Full{}", n + 1),
+			seed: None,
+			index: self.validators + n,
+		});
+		validators.chain(full_nodes).collect()
+	}
+
+	/// Port of `node` counting from `first`.
+	fn port(&self, first: u16, node: &NodeConfig) -> sc_cli::Result<u16> {
+		first.checked_add(node.index).ok_or_else(|| {
+			format!("the ports of {} don't fit below 65536, choose lower ports", node.name).into()
+		})
+	}
+
+	async fn run_nodes(
+		&self,
+		nodes: &[NodeConfig],
+		base_path: &Path,
+		spec_path: &Path,
+	) -> sc_cli::Result<()> {
+		let bootnode = &nodes[0];
+		let bootnode = format!(
+			"/ip4/127.0.0.1/tcp/{}/p2p/{}",
+			self.port(self.port, bootnode)?,
+			peer_id(bootnode)?
+		);
+
+		let mut children = Vec::new();
+		for node in nodes {
+			let mut command = Command::new(std::env::current_exe()?);
+			command
+				.arg("--chain")
+				.arg(spec_path)
+				.arg("--base-path")
+				.arg(node.base_path(base_path))
+				.args(["--name", &node.name])
+				.args(["--node-key", &HexDisplay::from(&node.node_key()).to_string()])
+				.args(["--port", &self.port(self.port, node)?.to_string()])
+				.args(["--rpc-port", &self.port(self.rpc_port, node)?.to_string()])
+				.args(["--ws-port", &self.port(self.ws_port, node)?.to_string()])
+				.args(["--prometheus-port", &self.port(self.prometheus_port, node)?.to_string()])
+				// Nodes of other local networks must not be discovered.
+				.args(["--no-mdns", "--no-telemetry"]);
+			if node.seed.is_some() {
+				command.arg("--validator");
+			}
+			if node.index > 0 {
+				command.args(["--bootnodes", &bootnode]);
+			}
+			let mut child = command
+				.args(&self.node_args)
+				.stdout(Stdio::piped())
+				.stderr(Stdio::piped())
+				.kill_on_drop(true)
+				.spawn()?;
+
+			let prefix = format!("{:>width$} |", node.name, width = name_width(nodes));
+			tokio::spawn(forward_lines(prefix.clone(), child.stdout.take()));
+			tokio::spawn(forward_lines(prefix, child.stderr.take()));
+			eprintln!(
+				"Started {} with the websocket RPC on port {}",
+				node.name,
+				self.port(self.ws_port, node)?
+			);
+			children.push(child);
+		}
+
+		let exits = select_all(children.iter_mut().map(|child| Box::pin(child.wait())));
+		tokio::select! {
+			result = tokio::signal::ctrl_c() => {
+				result?;
+				eprintln!("Stopping the network");
+			},
+			(status, index, _) = exits => {
+				let name = &nodes[index].name;
+				eprintln!("{} exited with {}, stopping the network", name, describe(status));
+			},
+		}
+
+		for (node, child) in nodes.iter().zip(&mut children) {
+			stop(&node.name, child).await;
+		}
+		Ok(())
+	}
+}
+
+/// Inserts the session keys derived from `seed` into the keystore at `path`.
+fn insert_session_keys(path: &Path, seed: &str) -> sc_cli::Result<()> {
+	let keystore = sc_keystore::LocalKeystore::open(path, None)?;
+	let keys = chain_spec::authority_keys_from_seed(seed);
+	let suri = format!("//{}", seed);
+	for key_type in SessionKeys::key_ids() {
+		SyncCryptoStore::insert_unknown(&keystore, *key_type, &suri, keys.get_raw(*key_type))
+			.map_err(|()| format!("inserting the {:?} key of {} failed", key_type, seed))?;
+	}
+	Ok(())
+}
+
+/// Peer id of `node`, derived from its node key.
+fn peer_id(node: &NodeConfig) -> sc_cli::Result<String> {
+	let mut secret = node.node_key();
+	let secret = ed25519::SecretKey::from_bytes(&mut secret)
+		.map_err(|e| format!("invalid node key of {}: {}", node.name, e))?;
+	let keypair = NodeKeyConfig::Ed25519(Secret::Input(secret)).into_keypair()?;
+	Ok(keypair.public().to_peer_id().to_base58())
+}
+
+/// Width of the widest node name, to align the log prefixes.
+fn name_width(nodes: &[NodeConfig]) -> usize {
+	nodes.iter().map(|node| node.name.len()).max().unwrap_or_default()
+}
+
+/// Prints the lines read from `output`, prefixed by `prefix`.
+async fn forward_lines(prefix: String, output: Option<impl AsyncRead + Unpin>) {
+	let mut lines = match output {
+		Some(output) => BufReader::new(output).lines(),
+		None => return,
+	};
+	while let Ok(Some(line)) = lines.next_line().await {
+		eprintln!("{} {}", prefix, line);
+	}
+}
+
+/// Asks `child` to shut down like Ctrl-C would, and kills it if it doesn't in time.
+async fn stop(name: &str, child: &mut Child) {
+	#[cfg(unix)]
+	if let Some(pid) = child.id() {
+		use nix::{sys::signal, unistd::Pid};
+		// The node may already be gone, or be shutting down after getting the terminal's Ctrl-C.
+		let _ = signal::kill(Pid::from_raw(pid as i32), signal::Signal::SIGINT);
+	}
+	match tokio::time::timeout(SHUTDOWN_TIMEOUT, child.wait()).await {
+		Ok(_) => (),
+		Err(_) => {
+			eprintln!("{} didn't shut down in time, killing it", name);
+			let _ = child.kill().await;
+		},
+	}
+}
+
+fn describe(status: std::io::Result<ExitStatus>) -> String {
+	match status {
+		Ok(status) => status.to_string(),
+		Err(e) => format!("an unknown status: {}", e),
+	}
+}
@@ -1,12 +1,25 @@
 //! Substrate Node Template CLI library.
 #![warn(missing_docs)]
 
+#[cfg(not(feature = "pow"))]
+mod backoff;
 mod chain_spec;
 #[macro_use]
 mod service;
 mod cli;
 mod command;
 mod command_helper;
+#[cfg(feature = "evm")]
+mod eth;
+mod indexer;
+mod limits;
+mod load;
+mod local_net;
+mod metadata;
+mod metadata_diff;
+#[cfg(feature = "pow")]
+mod pow;
+mod rehearsal;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
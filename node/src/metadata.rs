@@ -0,0 +1,279 @@
+//! Decoding of runtime errors, calls and events with the help of the runtime metadata, so RPC
+//! methods and the indexer can report them by name instead of by index.
+
+use std::sync::Arc;
+
+use codec::{Compact, Decode};
+use frame_metadata::{
+	v14::{PalletMetadata, RuntimeMetadataV14},
+	RuntimeMetadata, RuntimeMetadataPrefixed,
+};
+use frame_system::Phase;
+use sc_client_api::{Backend, StorageProvider};
+use scale_info::{form::PortableForm, TypeDef};
+use scale_value::{Composite, Primitive, Value, ValueDef};
+use serde::Serialize;
+use sp_api::{Core, Metadata as MetadataApi, ProvideRuntimeApi};
+use sp_core::{hashing::twox_128, storage::StorageKey, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, ModuleError};
+
+/// The V14 metadata of a runtime.
+pub struct Metadata(RuntimeMetadataV14);
+
+/// A pallet error resolved by name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NamedError {
+	/// Name of the pallet that returned the error.
+	pub pallet: String,
+	/// Name of the error variant.
+	pub error: String,
+	/// Documentation of the error variant.
+	pub docs: String,
+}
+
+/// A runtime event decoded by name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedEvent {
+	/// Name of the pallet that deposited the event.
+	pub pallet: String,
+	/// Name of the event variant.
+	pub event: String,
+	/// Fields of the event.
+	pub fields: Composite<()>,
+}
+
+/// A runtime call decoded by name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedCall {
+	/// Name of the pallet the call belongs to.
+	pub pallet: String,
+	/// Name of the call.
+	pub call: String,
+	/// Arguments of the call.
+	pub args: Composite<()>,
+}
+
+impl DecodedEvent {
+	/// Whether any field of the event holds the 32 byte account id `account`.
+	pub fn mentions(&self, account: &[u8; 32]) -> bool {
+		self.accounts().contains(account)
+	}
+
+	/// The 32 byte account ids held by the fields of the event, in order of appearance.
+	pub fn accounts(&self) -> Vec<[u8; 32]> {
+		let mut accounts = Vec::new();
+		collect_accounts(&self.fields, &mut accounts);
+		accounts
+	}
+
+	/// Returns the value of the field called `name`.
+	pub fn field(&self, name: &str) -> Option<&Value<()>> {
+		match &self.fields {
+			Composite::Named(fields) =>
+				fields.iter().find(|(field, _)| field == name).map(|(_, value)| value),
+			Composite::Unnamed(_) => None,
+		}
+	}
+}
+
+fn collect_accounts(composite: &Composite<()>, accounts: &mut Vec<[u8; 32]>) {
+	if let Some(account) = as_bytes32(composite) {
+		return accounts.push(account)
+	}
+	for value in composite.values() {
+		match &value.value {
+			ValueDef::Composite(composite) => collect_accounts(composite, accounts),
+			ValueDef::Variant(variant) => collect_accounts(&variant.values, accounts),
+			_ => {},
+		}
+	}
+}
+
+/// Returns the 32 byte array held by `value`, such as an `AccountId32`.
+pub fn value_as_bytes32(value: &Value<()>) -> Option<[u8; 32]> {
+	match &value.value {
+		ValueDef::Composite(composite) => as_bytes32(composite),
+		_ => None,
+	}
+}
+
+/// Returns the unsigned integer held by `value`.
+pub fn value_as_u128(value: &Value<()>) -> Option<u128> {
+	match value.value {
+		ValueDef::Primitive(Primitive::U128(v)) => Some(v),
+		_ => None,
+	}
+}
+
+/// Byte arrays are decoded as composites of `u8` primitives, and newtypes around them as
+/// composites with a single field.
+fn as_bytes32(composite: &Composite<()>) -> Option<[u8; 32]> {
+	let values: Vec<_> = composite.values().collect();
+	if let [value] = values[..] {
+		return value_as_bytes32(value)
+	}
+
+	let mut bytes = [0u8; 32];
+	if values.len() != bytes.len() {
+		return None
+	}
+	for (byte, value) in bytes.iter_mut().zip(values) {
+		*byte = value_as_u128(value)?.try_into().ok()?;
+	}
+	Some(bytes)
+}
+
+impl Metadata {
+	/// Fetches the metadata of the runtime at block `at`.
+	pub fn at<Block, C>(client: &C, at: &BlockId<Block>) -> Result<Self, String>
+	where
+		Block: BlockT,
+		C: ProvideRuntimeApi<Block>,
+		C::Api: MetadataApi<Block>,
+	{
+		let opaque = client
+			.runtime_api()
+			.metadata(at)
+			.map_err(|e| format!("Failed to fetch the runtime metadata: {}", e))?;
+		Self::decode(&opaque)
+	}
+
+	/// Decodes SCALE encoded, prefixed metadata as returned by `Metadata_metadata`.
+	pub fn decode(mut bytes: &[u8]) -> Result<Self, String> {
+		let prefixed = RuntimeMetadataPrefixed::decode(&mut bytes)
+			.map_err(|e| format!("Invalid runtime metadata: {}", e))?;
+		match prefixed.1 {
+			RuntimeMetadata::V14(metadata) => Ok(Self(metadata)),
+			_ => Err("Unsupported runtime metadata version, expected V14".into()),
+		}
+	}
+
+	/// The decoded V14 metadata.
+	pub fn v14(&self) -> &RuntimeMetadataV14 {
+		&self.0
+	}
+
+	/// Looks up the pallet and variant names of a module error.
+	pub fn module_error(&self, error: &ModuleError) -> Option<NamedError> {
+		let pallet = self.0.pallets.iter().find(|pallet| pallet.index == error.index)?;
+		let ty = self.0.types.resolve(pallet.error.as_ref()?.ty.id())?;
+		let variant = match ty.type_def() {
+			TypeDef::Variant(def) => def.variants().iter().find(|v| v.index() == error.error[0])?,
+			_ => return None,
+		};
+
+		Some(NamedError {
+			pallet: pallet.name.clone(),
+			error: variant.name().clone(),
+			docs: variant.docs().join("\n"),
+		})
+	}
+
+	/// Decodes the value of the `System::Events` storage item into the phase and the event of
+	/// each record.
+	pub fn decode_event_records(
+		&self,
+		mut bytes: &[u8],
+	) -> Result<Vec<(Phase, DecodedEvent)>, String> {
+		let invalid = |e: codec::Error| format!("Invalid event records: {}", e);
+		let Compact(len) = Compact::<u32>::decode(&mut bytes).map_err(invalid)?;
+		(0..len)
+			.map(|_| {
+				let phase = Phase::decode(&mut bytes).map_err(invalid)?;
+				let event = self.decode_event(&mut bytes)?;
+				let _topics = Vec::<H256>::decode(&mut bytes).map_err(invalid)?;
+				Ok((phase, event))
+			})
+			.collect()
+	}
+
+	/// Decodes a SCALE encoded runtime event, advancing `bytes` past it.
+	pub fn decode_event(&self, bytes: &mut &[u8]) -> Result<DecodedEvent, String> {
+		let (pallet, event, fields) = self.decode_pallet_variant(bytes, "event", |pallet| {
+			pallet.event.as_ref().map(|event| event.ty.id())
+		})?;
+		Ok(DecodedEvent { pallet, event, fields })
+	}
+
+	/// Decodes a SCALE encoded runtime call, advancing `bytes` past it.
+	pub fn decode_call(&self, bytes: &mut &[u8]) -> Result<DecodedCall, String> {
+		let (pallet, call, args) = self.decode_pallet_variant(bytes, "call", |pallet| {
+			pallet.calls.as_ref().map(|calls| calls.ty.id())
+		})?;
+		Ok(DecodedCall { pallet, call, args })
+	}
+
+	/// Decodes an enum prefixed with the index of its pallet, such as an outer event or call, into
+	/// the pallet name, the variant name and the fields.
+	fn decode_pallet_variant(
+		&self,
+		bytes: &mut &[u8],
+		what: &str,
+		ty: impl Fn(&PalletMetadata<PortableForm>) -> Option<u32>,
+	) -> Result<(String, String, Composite<()>), String> {
+		let index = u8::decode(bytes).map_err(|e| format!("Invalid {}: {}", what, e))?;
+		let pallet = self
+			.0
+			.pallets
+			.iter()
+			.find(|pallet| pallet.index == index)
+			.ok_or_else(|| format!("Unknown pallet index {}", index))?;
+		let ty = ty(pallet).ok_or_else(|| format!("Pallet {} has no {} type", pallet.name, what))?;
+
+		let value = scale_value::scale::decode_as_type(bytes, ty, &self.0.types)
+			.map_err(|e| format!("Invalid {} {}: {:?}", pallet.name, what, e))?;
+		match value.remove_context() {
+			Value { value: ValueDef::Variant(variant), .. } =>
+				Ok((pallet.name.clone(), variant.name, variant.values)),
+			_ => Err(format!("The {} type of pallet {} is not an enum", what, pallet.name)),
+		}
+	}
+}
+
+/// Keeps the metadata of the most recently used runtime version.
+#[derive(Default)]
+pub struct MetadataCache(Option<(u32, Arc<Metadata>)>);
+
+impl MetadataCache {
+	/// Returns the metadata of the runtime at block `at`, fetching it if the runtime version
+	/// changed since the last call.
+	pub fn at<Block, C>(&mut self, client: &C, at: &BlockId<Block>) -> Result<Arc<Metadata>, String>
+	where
+		Block: BlockT,
+		C: ProvideRuntimeApi<Block>,
+		C::Api: MetadataApi<Block>,
+	{
+		let spec_version = client
+			.runtime_api()
+			.version(at)
+			.map_err(|e| format!("Failed to fetch the runtime version: {}", e))?
+			.spec_version;
+		match &self.0 {
+			Some((version, metadata)) if *version == spec_version => Ok(metadata.clone()),
+			_ => {
+				let metadata = Arc::new(Metadata::at(client, at)?);
+				self.0 = Some((spec_version, metadata.clone()));
+				Ok(metadata)
+			},
+		}
+	}
+}
+
+/// Reads and decodes the events deposited in block `at`.
+pub fn read_events<Block, BE, C>(
+	client: &C,
+	at: &BlockId<Block>,
+	metadata: &Metadata,
+) -> Result<Vec<(Phase, DecodedEvent)>, String>
+where
+	Block: BlockT,
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE>,
+{
+	let key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+	match client.storage(at, &key) {
+		Ok(Some(data)) => metadata.decode_event_records(&data.0),
+		Ok(None) => Ok(Vec::new()),
+		Err(e) => Err(format!("Failed to read the events: {}", e)),
+	}
+}
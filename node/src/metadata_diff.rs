@@ -0,0 +1,451 @@
+//! The `metadata-diff` subcommand: compares the metadata of two runtimes, classifies the changes by
+//! what they break, and checks that the new runtime bumps its versions accordingly.
+//!
+//! Types are compared by their encoding, so renaming a type, a field or an enum variant is not a
+//! change. Only the metadata is compared: a runtime whose code changed without its metadata still
+//! needs a new `spec_version` to be upgraded to, which this command can't tell.
+
+use std::{
+	collections::HashSet,
+	fmt,
+	path::{Path, PathBuf},
+};
+
+use codec::Decode;
+use frame_metadata::v14::{
+	PalletMetadata, RuntimeMetadataV14, StorageEntryMetadata, StorageEntryType,
+};
+use jsonrpsee::{core::client::ClientT, ws_client::WsClientBuilder};
+use sc_executor::{WasmExecutionMethod, WasmExecutor};
+use sc_executor_common::runtime_blob::RuntimeBlob;
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, Variant};
+use sp_api::RuntimeVersion;
+use sp_core::{Bytes, OpaqueMetadata};
+
+use crate::metadata::Metadata;
+
+/// The `metadata-diff` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct MetadataDiffCmd {
+	/// The runtime upgraded from: a Wasm blob, or the websocket RPC endpoint of a node running it
+	/// such as `ws://127.0.0.1:9944`.
+	#[clap(value_name = "OLD")]
+	pub old: String,
+
+	/// The runtime upgraded to, a Wasm blob.
+	#[clap(value_name = "NEW", parse(from_os_str))]
+	pub new: PathBuf,
+}
+
+/// What a change of the metadata breaks, from the least to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+	/// Nothing, e.g. an added call.
+	NonBreaking,
+	/// The decoding of storage or events, which requires a new `spec_version`.
+	Breaking,
+	/// The encoding of extrinsics, which also requires a new `transaction_version`.
+	BreaksTransactions,
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Severity::NonBreaking => "non-breaking",
+			Severity::Breaking => "breaking",
+			Severity::BreaksTransactions => "breaks transactions",
+		})
+	}
+}
+
+/// A change between the metadata of two runtimes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+	/// What the change breaks.
+	pub severity: Severity,
+	/// What changed, e.g. `call TemplateModule::do_something removed`.
+	pub description: String,
+}
+
+/// The metadata and version of a runtime.
+struct Runtime {
+	version: RuntimeVersion,
+	metadata: Metadata,
+}
+
+impl MetadataDiffCmd {
+	/// Compares the runtimes, prints the changes and fails if the versions aren't bumped enough.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let old = if self.old.starts_with("ws://") || self.old.starts_with("wss://") {
+			tokio::runtime::Runtime::new()?.block_on(fetch_runtime(&self.old))?
+		} else {
+			load_runtime(Path::new(&self.old))?
+		};
+		let new = load_runtime(&self.new)?;
+
+		let changes = diff(&old.metadata, &new.metadata);
+		if changes.is_empty() {
+			println!("No changes to the metadata");
+		}
+		for change in &changes {
+			println!("[{}] {}", change.severity, change.description);
+		}
+		println!(
+			"spec_version: {} -> {}, transaction_version: {} -> {}",
+			old.version.spec_version,
+			new.version.spec_version,
+			old.version.transaction_version,
+			new.version.transaction_version
+		);
+		Ok(check_versions(&changes, &old.version, &new.version)?)
+	}
+}
+
+/// Executes the Wasm blob at `path` to get its metadata and version.
+fn load_runtime(path: &Path) -> Result<Runtime, String> {
+	let code = std::fs::read(path).map_err(|e| format!("reading {}: {}", path.display(), e))?;
+	let executor = WasmExecutor::<sp_io::SubstrateHostFunctions>::new(
+		WasmExecutionMethod::Interpreted,
+		None,
+		1,
+		None,
+		1,
+	);
+	let call = |method: &str| -> Result<Vec<u8>, String> {
+		let blob = RuntimeBlob::uncompress_if_needed(&code)
+			.map_err(|e| format!("invalid runtime {}: {}", path.display(), e))?;
+		let mut ext = sp_io::TestExternalities::default();
+		let mut ext = ext.ext();
+		executor
+			.uncached_call(blob, &mut ext, true, method, &[])
+			.map_err(|e| format!("calling {} of {}: {}", method, path.display(), e))
+	};
+
+	let version = RuntimeVersion::decode(&mut &call("Core_version")?[..])
+		.map_err(|e| format!("invalid runtime version of {}: {}", path.display(), e))?;
+	let metadata = OpaqueMetadata::decode(&mut &call("Metadata_metadata")?[..])
+		.map_err(|e| format!("invalid metadata of {}: {}", path.display(), e))?;
+	Ok(Runtime { version, metadata: Metadata::decode(&metadata)? })
+}
+
+/// Fetches the metadata and version of the runtime of the node at `url`.
+async fn fetch_runtime(url: &str) -> Result<Runtime, String> {
+	let client = WsClientBuilder::default()
+		.build(url)
+		.await
+		.map_err(|e| format!("connecting to {}: {}", url, e))?;
+	let version: RuntimeVersion = client
+		.request("state_getRuntimeVersion", None)
+		.await
+		.map_err(|e| format!("querying the runtime version: {}", e))?;
+	let metadata: Bytes = client
+		.request("state_getMetadata", None)
+		.await
+		.map_err(|e| format!("querying the metadata: {}", e))?;
+	Ok(Runtime { version, metadata: Metadata::decode(&metadata)? })
+}
+
+/// Checks that `new` bumps the versions of `old` as much as `changes` require.
+pub fn check_versions(
+	changes: &[Change],
+	old: &RuntimeVersion,
+	new: &RuntimeVersion,
+) -> Result<(), String> {
+	if new.spec_name != old.spec_name {
+		return Err(format!("the spec name changed from {} to {}", old.spec_name, new.spec_name))
+	}
+	if new.spec_version < old.spec_version || new.transaction_version < old.transaction_version {
+		return Err("the new runtime has lower versions than the old one".into())
+	}
+
+	let severity = changes.iter().map(|change| change.severity).max();
+	if severity.is_some() && new.spec_version == old.spec_version {
+		return Err("the metadata changed, spec_version must be bumped".into())
+	}
+	if severity == Some(Severity::BreaksTransactions) &&
+		new.transaction_version == old.transaction_version
+	{
+		return Err(
+			"the encoding of transactions changed, transaction_version must be bumped".into()
+		)
+	}
+	Ok(())
+}
+
+/// Lists the changes from the metadata `old` to `new`, pallets by name and their calls, events
+/// and storage entries.
+pub fn diff(old: &Metadata, new: &Metadata) -> Vec<Change> {
+	let (old, new) = (old.v14(), new.v14());
+	let mut differ = Differ { old, new, changes: Vec::new() };
+	differ.extrinsic();
+	for old_pallet in &old.pallets {
+		match new.pallets.iter().find(|pallet| pallet.name == old_pallet.name) {
+			Some(new_pallet) => differ.pallet(old_pallet, new_pallet),
+			None => {
+				let severity = match old_pallet.calls {
+					Some(_) => Severity::BreaksTransactions,
+					None => Severity::Breaking,
+				};
+				differ.push(severity, format!("pallet {} removed", old_pallet.name));
+			},
+		}
+	}
+	for new_pallet in &new.pallets {
+		if !old.pallets.iter().any(|pallet| pallet.name == new_pallet.name) {
+			let description = format!("pallet {} added", new_pallet.name);
+			differ.push(Severity::NonBreaking, description);
+		}
+	}
+	differ.changes
+}
+
+struct Differ<'a> {
+	old: &'a RuntimeMetadataV14,
+	new: &'a RuntimeMetadataV14,
+	changes: Vec<Change>,
+}
+
+impl<'a> Differ<'a> {
+	fn push(&mut self, severity: Severity, description: String) {
+		self.changes.push(Change { severity, description });
+	}
+
+	/// Whether the type `old` of the old registry is encoded like the type `new` of the new one.
+	fn same_type(&self, old: u32, new: u32) -> bool {
+		TypeComparison { old: &self.old.types, new: &self.new.types, assumed: HashSet::new() }
+			.same(old, new)
+	}
+
+	fn extrinsic(&mut self) {
+		let (old_metadata, new_metadata) = (self.old, self.new);
+		let (old, new) = (&old_metadata.extrinsic, &new_metadata.extrinsic);
+		if old.version != new.version {
+			let description =
+				format!("extrinsic version changed from {} to {}", old.version, new.version);
+			self.push(Severity::BreaksTransactions, description);
+		}
+		let same_extensions = old.signed_extensions.len() == new.signed_extensions.len() &&
+			old.signed_extensions.iter().zip(&new.signed_extensions).all(|(old, new)| {
+				old.identifier == new.identifier &&
+					self.same_type(old.ty.id(), new.ty.id()) &&
+					self.same_type(old.additional_signed.id(), new.additional_signed.id())
+			});
+		if !same_extensions {
+			let names = |metadata: &RuntimeMetadataV14| {
+				let extensions = &metadata.extrinsic.signed_extensions;
+				extensions.iter().map(|e| e.identifier.as_str()).collect::<Vec<_>>().join(", ")
+			};
+			let description = format!(
+				"signed extensions changed from [{}] to [{}]",
+				names(old_metadata),
+				names(new_metadata)
+			);
+			self.push(Severity::BreaksTransactions, description);
+		}
+	}
+
+	fn pallet(&mut self, old: &PalletMetadata<PortableForm>, new: &PalletMetadata<PortableForm>) {
+		let name = &old.name;
+		if old.index != new.index {
+			let severity = match old.calls {
+				Some(_) => Severity::BreaksTransactions,
+				None => Severity::Breaking,
+			};
+			let description =
+				format!("pallet {} moved from index {} to {}", name, old.index, new.index);
+			self.push(severity, description);
+		}
+
+		let (old_metadata, new_metadata) = (self.old, self.new);
+		let (old_types, new_types) = (&old_metadata.types, &new_metadata.types);
+		let old_calls = variants(old_types, old.calls.as_ref().map(|calls| calls.ty.id()));
+		let new_calls = variants(new_types, new.calls.as_ref().map(|calls| calls.ty.id()));
+		self.variants(name, "call", old_calls, new_calls, Severity::BreaksTransactions);
+		let old_events = variants(old_types, old.event.as_ref().map(|event| event.ty.id()));
+		let new_events = variants(new_types, new.event.as_ref().map(|event| event.ty.id()));
+		self.variants(name, "event", old_events, new_events, Severity::Breaking);
+
+		let no_entries = &[][..];
+		let old_storage = old.storage.as_ref();
+		let new_storage = new.storage.as_ref();
+		if let (Some(old), Some(new)) = (old_storage, new_storage) {
+			if old.prefix != new.prefix {
+				let description = format!(
+					"storage prefix of {} changed from {} to {}",
+					name, old.prefix, new.prefix
+				);
+				self.push(Severity::Breaking, description);
+			}
+		}
+		self.storage(
+			name,
+			old_storage.map_or(no_entries, |storage| &storage.entries[..]),
+			new_storage.map_or(no_entries, |storage| &storage.entries[..]),
+		);
+	}
+
+	/// Compares the calls or events of a pallet by name. Removing or changing one is a change of
+	/// `severity`, adding one isn't breaking.
+	fn variants(
+		&mut self,
+		pallet: &str,
+		what: &str,
+		old: &[Variant<PortableForm>],
+		new: &[Variant<PortableForm>],
+		severity: Severity,
+	) {
+		for old in old {
+			let new = match new.iter().find(|new| new.name() == old.name()) {
+				Some(new) => new,
+				None => {
+					self.push(severity, format!("{} {}::{} removed", what, pallet, old.name()));
+					continue
+				},
+			};
+			if old.index() != new.index() {
+				let description = format!(
+					"{} {}::{} moved from index {} to {}",
+					what,
+					pallet,
+					old.name(),
+					old.index(),
+					new.index()
+				);
+				self.push(severity, description);
+			}
+			if !self.same_fields(old.fields(), new.fields()) {
+				let description = format!("fields of {} {}::{} changed", what, pallet, old.name());
+				self.push(severity, description);
+			}
+		}
+		for new in new {
+			if !old.iter().any(|old| old.name() == new.name()) {
+				let description = format!("{} {}::{} added", what, pallet, new.name());
+				self.push(Severity::NonBreaking, description);
+			}
+		}
+	}
+
+	fn storage(
+		&mut self,
+		pallet: &str,
+		old: &[StorageEntryMetadata<PortableForm>],
+		new: &[StorageEntryMetadata<PortableForm>],
+	) {
+		for old in old {
+			let new = match new.iter().find(|new| new.name == old.name) {
+				Some(new) => new,
+				None => {
+					let description = format!("storage {}::{} removed", pallet, old.name);
+					self.push(Severity::Breaking, description);
+					continue
+				},
+			};
+			let same_layout =
+				old.modifier == new.modifier && self.same_entry_type(&old.ty, &new.ty);
+			if !same_layout {
+				let description = format!("layout of storage {}::{} changed", pallet, old.name);
+				self.push(Severity::Breaking, description);
+			} else if old.default != new.default {
+				let description = format!("default of storage {}::{} changed", pallet, old.name);
+				self.push(Severity::NonBreaking, description);
+			}
+		}
+		for new in new {
+			if !old.iter().any(|old| old.name == new.name) {
+				let description = format!("storage {}::{} added", pallet, new.name);
+				self.push(Severity::NonBreaking, description);
+			}
+		}
+	}
+
+	fn same_entry_type(
+		&self,
+		old: &StorageEntryType<PortableForm>,
+		new: &StorageEntryType<PortableForm>,
+	) -> bool {
+		match (old, new) {
+			(StorageEntryType::Plain(old), StorageEntryType::Plain(new)) =>
+				self.same_type(old.id(), new.id()),
+			(
+				StorageEntryType::Map { hashers, key, value },
+				StorageEntryType::Map { hashers: new_hashers, key: new_key, value: new_value },
+			) =>
+				hashers == new_hashers &&
+					self.same_type(key.id(), new_key.id()) &&
+					self.same_type(value.id(), new_value.id()),
+			_ => false,
+		}
+	}
+
+	fn same_fields(&self, old: &[Field<PortableForm>], new: &[Field<PortableForm>]) -> bool {
+		old.len() == new.len() &&
+			old.iter().zip(new).all(|(old, new)| self.same_type(old.ty().id(), new.ty().id()))
+	}
+}
+
+/// The variants of the enum `ty`, none if there is no such enum.
+fn variants(registry: &PortableRegistry, ty: Option<u32>) -> &[Variant<PortableForm>] {
+	match ty.and_then(|ty| registry.resolve(ty)).map(|ty| ty.type_def()) {
+		Some(TypeDef::Variant(def)) => def.variants(),
+		_ => &[],
+	}
+}
+
+/// Compares types of two registries by their encoding.
+///
+/// Pairs of types being compared are assumed to be the same when they are met again, so that
+/// recursive types such as a call holding calls terminate. A difference anywhere makes the whole
+/// comparison fail, so a comparison must not be reused once it returned `false`.
+struct TypeComparison<'a> {
+	old: &'a PortableRegistry,
+	new: &'a PortableRegistry,
+	assumed: HashSet<(u32, u32)>,
+}
+
+impl<'a> TypeComparison<'a> {
+	fn same(&mut self, old: u32, new: u32) -> bool {
+		if !self.assumed.insert((old, new)) {
+			return true
+		}
+		let (old, new) = match (self.old.resolve(old), self.new.resolve(new)) {
+			(Some(old), Some(new)) => (old.type_def(), new.type_def()),
+			_ => return false,
+		};
+
+		match (old, new) {
+			(TypeDef::Composite(old), TypeDef::Composite(new)) =>
+				self.same_fields(old.fields(), new.fields()),
+			(TypeDef::Variant(old), TypeDef::Variant(new)) =>
+				old.variants().len() == new.variants().len() &&
+					old.variants().iter().all(|old| {
+						match new.variants().iter().find(|new| new.index() == old.index()) {
+							Some(new) => self.same_fields(old.fields(), new.fields()),
+							None => false,
+						}
+					}),
+			(TypeDef::Sequence(old), TypeDef::Sequence(new)) =>
+				self.same(old.type_param().id(), new.type_param().id()),
+			(TypeDef::Array(old), TypeDef::Array(new)) =>
+				old.len() == new.len() && self.same(old.type_param().id(), new.type_param().id()),
+			(TypeDef::Tuple(old), TypeDef::Tuple(new)) =>
+				old.fields().len() == new.fields().len() &&
+					old.fields()
+						.iter()
+						.zip(new.fields())
+						.all(|(old, new)| self.same(old.id(), new.id())),
+			(TypeDef::Primitive(old), TypeDef::Primitive(new)) => old == new,
+			(TypeDef::Compact(old), TypeDef::Compact(new)) =>
+				self.same(old.type_param().id(), new.type_param().id()),
+			(TypeDef::BitSequence(old), TypeDef::BitSequence(new)) =>
+				self.same(old.bit_store_type().id(), new.bit_store_type().id()) &&
+					self.same(old.bit_order_type().id(), new.bit_order_type().id()),
+			_ => false,
+		}
+	}
+
+	fn same_fields(&mut self, old: &[Field<PortableForm>], new: &[Field<PortableForm>]) -> bool {
+		old.len() == new.len() &&
+			old.iter().zip(new).all(|(old, new)| self.same(old.ty().id(), new.ty().id()))
+	}
+}
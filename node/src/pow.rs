@@ -0,0 +1,155 @@
+//! Blake2 proof-of-work: the algorithm verifying seals against the runtime's difficulty and the
+//! tasks mining them.
+//!
+//! Only compiled with the `pow` feature.
+
+use codec::{Decode, Encode};
+use node_template_runtime::opaque::Block;
+use sc_consensus::JustificationSyncLink;
+use sc_consensus_pow::{Error as PowError, MiningHandle, PowAlgorithm, Seal as RawSeal};
+use sc_service::SpawnEssentialTaskHandle;
+use sp_api::ProvideRuntimeApi;
+use sp_consensus_pow::DifficultyApi;
+use sp_core::{hashing::blake2_256, H256, U256};
+use sp_runtime::generic::BlockId;
+use std::{sync::Arc, time::Duration};
+
+use crate::service::FullClient;
+
+/// Handle of the mining worker, through which miners get blocks to mine and submit their seals.
+type Handle<L, Proof> = MiningHandle<Block, Blake2Algorithm<FullClient>, FullClient, L, Proof>;
+
+/// Number of nonces a miner tries before checking for a new block to mine.
+const NONCES_PER_ROUND: usize = 10_000;
+
+/// Command line options of the miner.
+#[derive(Debug, Clone, clap::Args)]
+pub struct MiningConfiguration {
+	/// Number of threads mining blocks on authority nodes. No blocks are mined with `0`.
+	#[clap(long, value_name = "COUNT", default_value = "1")]
+	pub mining_threads: usize,
+}
+
+/// The seal of a mined block.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Seal {
+	/// Difficulty the block was mined at.
+	pub difficulty: U256,
+	/// Hash of the difficulty, the block's pre-hash and the nonce.
+	pub work: H256,
+	/// Nonce found by the miner.
+	pub nonce: U256,
+}
+
+impl Seal {
+	/// Seals the block with `pre_hash` with `nonce`, whether or not the work meets the difficulty.
+	pub fn compute(difficulty: U256, pre_hash: H256, nonce: U256) -> Self {
+		let work = H256(blake2_256(&(difficulty, pre_hash, nonce).encode()));
+		Self { difficulty, work, nonce }
+	}
+
+	/// Whether the work meets the seal's difficulty, i.e. `work * difficulty` doesn't overflow.
+	pub fn meets_difficulty(&self) -> bool {
+		let (_, overflowed) = U256::from(&self.work[..]).overflowing_mul(self.difficulty);
+		!overflowed
+	}
+}
+
+/// Verifies blake2 seals against the difficulty of the runtime.
+pub struct Blake2Algorithm<C> {
+	client: Arc<C>,
+}
+
+impl<C> Blake2Algorithm<C> {
+	/// Reads the difficulty from the runtime of `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+// Derived `Clone` would require `C: Clone`.
+impl<C> Clone for Blake2Algorithm<C> {
+	fn clone(&self) -> Self {
+		Self { client: self.client.clone() }
+	}
+}
+
+impl<C> PowAlgorithm<Block> for Blake2Algorithm<C>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: DifficultyApi<Block, U256>,
+{
+	type Difficulty = U256;
+
+	fn difficulty(&self, parent: H256) -> Result<U256, PowError<Block>> {
+		self.client.runtime_api().difficulty(&BlockId::Hash(parent)).map_err(|e| {
+			PowError::Environment(format!("Fetching the difficulty from the runtime failed: {}", e))
+		})
+	}
+
+	fn verify(
+		&self,
+		_parent: &BlockId<Block>,
+		pre_hash: &H256,
+		_pre_digest: Option<&[u8]>,
+		seal: &RawSeal,
+		difficulty: U256,
+	) -> Result<bool, PowError<Block>> {
+		let seal = match Seal::decode(&mut &seal[..]) {
+			Ok(seal) => seal,
+			Err(_) => return Ok(false),
+		};
+		Ok(seal.meets_difficulty() && seal == Seal::compute(difficulty, *pre_hash, seal.nonce))
+	}
+}
+
+/// Spawns `threads` miners of the blocks proposed by the mining worker behind `handle`, as
+/// essential blocking tasks of the service.
+///
+/// Each miner tries a distinct sequence of nonces, on a thread of its own, until the service stops.
+pub fn spawn_miners<L, Proof>(
+	threads: usize,
+	handle: Handle<L, Proof>,
+	spawner: &SpawnEssentialTaskHandle,
+) where
+	L: JustificationSyncLink<Block> + 'static,
+	Proof: Send + 'static,
+{
+	for index in 0..threads {
+		spawner.spawn_blocking(
+			"pow-miner",
+			Some("block-authoring"),
+			mine(handle.clone(), index, threads),
+		);
+	}
+}
+
+async fn mine<L, Proof>(handle: Handle<L, Proof>, first_nonce: usize, step: usize)
+where
+	L: JustificationSyncLink<Block>,
+{
+	let step = U256::from(step);
+	let mut nonce = U256::from(first_nonce);
+	loop {
+		let metadata = match handle.metadata() {
+			Some(metadata) => metadata,
+			None => {
+				// Nothing to mine while the node is syncing or building a block.
+				tokio::time::sleep(Duration::from_millis(500)).await;
+				continue
+			},
+		};
+
+		for _ in 0..NONCES_PER_ROUND {
+			let seal = Seal::compute(metadata.difficulty, metadata.pre_hash, nonce);
+			nonce = nonce.overflowing_add(step).0;
+			if seal.meets_difficulty() {
+				// Imports the mined block, which isn't interrupted by the service stopping.
+				futures::executor::block_on(handle.submit(seal.encode()));
+				break
+			}
+		}
+		// Lets the task stop with the service between rounds.
+		tokio::task::yield_now().await;
+	}
+}
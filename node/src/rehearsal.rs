@@ -0,0 +1,329 @@
+//! The `upgrade-rehearsal` subcommand: rehearses a runtime upgrade offline, on a fork of the state
+//! of the best block of a database.
+//!
+//! The new code is set by `System::set_code`, dispatched by the Wasm code of the runtime through
+//! its `UpgradeApi`, in a block on top of the best block. More blocks are then authored with the
+//! new runtime, the first of which runs its migrations. The blocks are
+//! executed in memory and never imported, so the database is left as it was. It must not be in use
+//! by a running node though.
+
+use std::{path::PathBuf, sync::Arc};
+
+use codec::{Decode, Encode};
+use frame_support::weights::{DispatchClass, PerDispatchClass, Weight};
+use node_template_runtime::{
+	apis::{TemplateApi, UpgradeApi},
+	opaque::{Block, UncheckedExtrinsic},
+	Header, SLOT_DURATION, WEIGHT_PER_SECOND,
+};
+use sc_cli::{CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::{Backend, HeaderBackend};
+use sc_service::{Configuration, SpawnTaskHandle};
+use sp_api::{RuntimeApiInfo, RuntimeVersion};
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_core::{
+	hashing::{blake2_256, twox_128},
+	storage::well_known_keys,
+	traits::{ReadRuntimeVersionExt, RuntimeCode, WrappedRuntimeCode},
+};
+use sp_externalities::Extensions;
+use sp_inherents::{InherentData, InherentDataProvider};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{BlakeTwo256, Header as _},
+	ApplyExtrinsicResult, Digest, DigestItem,
+};
+use sp_state_machine::{
+	Backend as StateBackend, ExecutionStrategy, OverlayedChanges, StateMachine,
+};
+
+use crate::service::{ExecutorDispatch, FullBackend, FullClient, NativeElseWasmExecutor};
+
+type Executor = NativeElseWasmExecutor<ExecutorDispatch>;
+
+/// The `upgrade-rehearsal` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct UpgradeRehearsalCmd {
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+
+	/// The Wasm blob of the runtime to upgrade to.
+	#[clap(long, parse(from_os_str))]
+	pub wasm: PathBuf,
+
+	/// Number of blocks authored with the new runtime.
+	#[clap(long, default_value = "3")]
+	pub blocks: u32,
+}
+
+impl UpgradeRehearsalCmd {
+	/// Rehearses the upgrade, prints a report and fails if a block can't be authored or the
+	/// invariants of the template pallet don't hold.
+	pub fn run(
+		&self,
+		config: &Configuration,
+		client: Arc<FullClient>,
+		backend: Arc<FullBackend>,
+		spawn_handle: SpawnTaskHandle,
+	) -> sc_cli::Result<()> {
+		if cfg!(any(feature = "babe", feature = "pow")) {
+			return Err("upgrade rehearsals author Aura blocks, the runtime doesn't use Aura".into())
+		}
+		if self.blocks == 0 {
+			return Err("`--blocks` must be positive".into())
+		}
+		let new_code = std::fs::read(&self.wasm)
+			.map_err(|e| format!("reading {}: {}", self.wasm.display(), e))?;
+
+		let best_hash = client.info().best_hash;
+		let best = client.header(BlockId::Hash(best_hash))?.ok_or("the best block is missing")?;
+		let state = backend.state_at(BlockId::Hash(best_hash))?;
+		let executor = Executor::new(
+			config.wasm_method,
+			config.default_heap_pages,
+			config.max_runtime_instances,
+			config.runtime_cache_size,
+		);
+		let mut fork = Fork::new(&state, &executor, spawn_handle, best)?;
+
+		let old_version = fork.version()?;
+		if !old_version.has_api_with(&<dyn UpgradeApi<Block>>::ID, |_| true) {
+			return Err("the runtime has no UpgradeApi to set the new code with".into())
+		}
+		println!(
+			"Forked {} at block #{} ({:?}), running {} version {}",
+			config.chain_spec.name(),
+			fork.parent.number(),
+			best_hash,
+			old_version.spec_name,
+			old_version.spec_version
+		);
+		let mut violations = Vec::new();
+		// The runtime upgraded from may predate the `TemplateApi`.
+		if old_version.has_api_with(&<dyn TemplateApi<Block>>::ID, |_| true) {
+			fork.check_invariants("before the upgrade", &mut violations)?;
+		}
+
+		fork.author_block(|fork| {
+			// Set by the Wasm code of the runtime, as on chain.
+			let result = fork.call("UpgradeApi_set_code", &new_code.encode())?;
+			Result::<(), String>::decode(&mut &result[..])
+				.map_err(|e| format!("invalid set_code result: {}", e))?
+				.map_err(|e| format!("set_code failed: {}", e))?;
+			fork.code = fork.storage(well_known_keys::CODE).ok_or("set_code removed the code")?;
+			Ok(())
+		})?;
+		let new_version = fork.version()?;
+		println!(
+			"Block #{}: set the code of {} version {}",
+			fork.parent.number(),
+			new_version.spec_name,
+			new_version.spec_version
+		);
+
+		let mut initialization_weights = Vec::new();
+		for _ in 0..self.blocks {
+			let weight = fork.author_block(|_| Ok(()))?;
+			let number = fork.parent.number();
+			println!("Block #{}: initialized with a weight of {}", number, seconds(weight));
+			initialization_weights.push(weight);
+			fork.check_invariants(&format!("after block #{}", number), &mut violations)?;
+		}
+		// Initializing the first block of the new runtime runs the migrations on top of what every
+		// block initializes.
+		if let [upgrade, next, ..] = initialization_weights[..] {
+			println!("Migration weight: {}", seconds(upgrade.saturating_sub(next)));
+		} else {
+			println!("Migration weight: unknown, `--blocks` must be at least 2 to estimate it");
+		}
+
+		let diff = fork.storage_diff();
+		println!(
+			"Storage diff: {} keys added, {} changed and {} removed, {} bytes written",
+			diff.added, diff.changed, diff.removed, diff.bytes
+		);
+
+		if violations.is_empty() {
+			println!("The invariants of TemplateModule hold");
+			Ok(())
+		} else {
+			Err(format!("The invariants of TemplateModule don't hold: {}", violations.join(", "))
+				.into())
+		}
+	}
+}
+
+/// Keys added, changed and removed by the blocks of a fork.
+#[derive(Default)]
+struct StorageDiff {
+	added: usize,
+	changed: usize,
+	removed: usize,
+	/// Length of the values added or changed.
+	bytes: usize,
+}
+
+/// The state of a block, with the changes of the blocks authored on top of it in memory.
+struct Fork<'a, S> {
+	state: &'a S,
+	overlay: OverlayedChanges,
+	executor: &'a Executor,
+	spawn_handle: SpawnTaskHandle,
+	/// Code of the runtime blocks are executed with.
+	code: Vec<u8>,
+	/// Header of the last block.
+	parent: Header,
+}
+
+impl<'a, S: StateBackend<BlakeTwo256>> Fork<'a, S> {
+	fn new(
+		state: &'a S,
+		executor: &'a Executor,
+		spawn_handle: SpawnTaskHandle,
+		parent: Header,
+	) -> Result<Self, String> {
+		let code = state
+			.storage(well_known_keys::CODE)
+			.map_err(|e| format!("reading the code: {}", e))?
+			.ok_or("the state has no code")?;
+		let overlay = OverlayedChanges::default();
+		Ok(Self { state, overlay, executor, spawn_handle, code, parent })
+	}
+
+	fn storage(&self, key: &[u8]) -> Option<Vec<u8>> {
+		match self.overlay.storage(key) {
+			Some(value) => value.map(|value| value.to_vec()),
+			None => self.state.storage(key).ok().flatten(),
+		}
+	}
+
+	fn decode_storage<T: Decode>(&self, pallet: &str, item: &str) -> Result<T, String> {
+		let key = [twox_128(pallet.as_bytes()), twox_128(item.as_bytes())].concat();
+		let value = self.storage(&key).ok_or_else(|| format!("{}::{} is empty", pallet, item))?;
+		T::decode(&mut &value[..]).map_err(|e| format!("invalid {}::{}: {}", pallet, item, e))
+	}
+
+	fn extensions(&self) -> Extensions {
+		let mut extensions = Extensions::new();
+		extensions.register(ReadRuntimeVersionExt::new(self.executor.clone()));
+		extensions
+	}
+
+	/// Calls `method` of the runtime API with the Wasm code of the fork.
+	fn call(&mut self, method: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+		let code = WrappedRuntimeCode(self.code.as_slice().into());
+		let hash = blake2_256(&self.code).to_vec();
+		let runtime_code = RuntimeCode { code_fetcher: &code, heap_pages: None, hash };
+		let extensions = self.extensions();
+		StateMachine::new(
+			self.state,
+			&mut self.overlay,
+			self.executor,
+			method,
+			data,
+			extensions,
+			&runtime_code,
+			self.spawn_handle.clone(),
+		)
+		.execute(ExecutionStrategy::AlwaysWasm)
+		.map_err(|e| format!("calling {}: {}", method, e))
+	}
+
+	fn version(&mut self) -> Result<RuntimeVersion, String> {
+		RuntimeVersion::decode(&mut &self.call("Core_version", &[])?[..])
+			.map_err(|e| format!("invalid runtime version: {}", e))
+	}
+
+	/// Authors a block in the slot after the last one, with its inherents and the changes of
+	/// `build`, and returns the weight its initialization took.
+	fn author_block(
+		&mut self,
+		build: impl FnOnce(&mut Self) -> Result<(), String>,
+	) -> Result<Weight, String> {
+		let now: u64 = self.decode_storage("Timestamp", "Now")?;
+		let timestamp = now + SLOT_DURATION;
+		let slot = Slot::from(timestamp / SLOT_DURATION);
+		let digest = Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] };
+		let header = Header::new(
+			self.parent.number() + 1,
+			Default::default(),
+			Default::default(),
+			self.parent.hash(),
+			digest,
+		);
+		self.call("Core_initialize_block", &header.encode())?;
+		let weight: PerDispatchClass<Weight> = self.decode_storage("System", "BlockWeight")?;
+
+		let mut inherent_data = InherentData::new();
+		sp_timestamp::InherentDataProvider::new(timestamp.into())
+			.provide_inherent_data(&mut inherent_data)
+			.map_err(|e| format!("creating inherent data: {:?}", e))?;
+		let inherents = self.call("BlockBuilder_inherent_extrinsics", &inherent_data.encode())?;
+		let inherents = Vec::<UncheckedExtrinsic>::decode(&mut &inherents[..])
+			.map_err(|e| format!("invalid inherents: {}", e))?;
+		for inherent in inherents {
+			let result = self.call("BlockBuilder_apply_extrinsic", &inherent.encode())?;
+			match ApplyExtrinsicResult::decode(&mut &result[..]) {
+				Ok(Ok(Ok(()))) => (),
+				result => return Err(format!("applying an inherent failed: {:?}", result)),
+			}
+		}
+		build(self)?;
+
+		let header = self.call("BlockBuilder_finalize_block", &[])?;
+		self.parent =
+			Header::decode(&mut &header[..]).map_err(|e| format!("invalid header: {}", e))?;
+		Ok(*weight.get(DispatchClass::Mandatory))
+	}
+
+	/// Records a violation of the invariants of the template pallet, if any, at `when`. They are
+	/// checked by the `TemplateApi` of the code of the fork, as upgraded.
+	fn check_invariants(&mut self, when: &str, violations: &mut Vec<String>) -> Result<(), String> {
+		let result = self.call("TemplateApi_check_invariants", &[])?;
+		// The `RuntimeString` of the error is encoded like a `String`.
+		let result = Result::<(), String>::decode(&mut &result[..])
+			.map_err(|e| format!("invalid invariants check: {}", e))?;
+		if let Err(e) = result {
+			violations.push(format!("{} {}", e, when));
+		}
+		Ok(())
+	}
+
+	/// Compares the changes of the fork with the state it forked.
+	fn storage_diff(&self) -> StorageDiff {
+		let mut diff = StorageDiff::default();
+		for (key, value) in self.overlay.changes() {
+			let old = self.state.storage(key).ok().flatten();
+			match (old, value.value()) {
+				(None, Some(new)) => {
+					diff.added += 1;
+					diff.bytes += new.len();
+				},
+				(Some(old), Some(new)) if &old != new => {
+					diff.changed += 1;
+					diff.bytes += new.len();
+				},
+				(Some(_), None) => diff.removed += 1,
+				_ => (),
+			}
+		}
+		diff
+	}
+}
+
+fn seconds(weight: Weight) -> String {
+	format!("{:.3} s", weight as f64 / WEIGHT_PER_SECOND as f64)
+}
+
+impl CliConfiguration for UpgradeRehearsalCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -5,10 +5,11 @@
 
 #![warn(missing_docs)]
 
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use jsonrpsee::RpcModule;
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
+use sc_rpc::SubscriptionTaskExecutor;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -16,6 +17,14 @@ use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 
 pub use sc_rpc_api::DenyUnsafe;
 
+pub mod assets;
+pub mod dry_run;
+#[cfg(feature = "evm")]
+pub mod eth;
+pub mod events;
+pub mod index;
+pub mod uniques;
+
 /// Full client dependencies.
 pub struct FullDeps<C, P> {
 	/// The client instance to use.
@@ -24,29 +33,55 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Executor to drive RPC subscriptions.
+	pub subscription_executor: SubscriptionTaskExecutor,
+	/// Database written by the indexer, if it is enabled.
+	pub index_db: Option<PathBuf>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(
+pub fn create_full<C, P, BE>(
 	deps: FullDeps<C, P>,
 ) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
 where
+	BE: sc_client_api::Backend<Block> + 'static,
 	C: ProvideRuntimeApi<Block>,
+	C: sc_client_api::StorageProvider<Block, BE> + sc_client_api::BlockchainEvents<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+	C::Api: node_template_runtime::apis::DryRunApi<Block>,
+	C::Api: node_template_runtime::apis::AssetsApi<Block>,
+	C::Api: node_template_runtime::apis::UniquesApi<Block>,
+	C::Api: sp_api::Metadata<Block>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
 {
+	use assets::{Assets, AssetsApiServer};
+	use dry_run::{DryRun, DryRunApiServer};
+	use events::{Events, EventsApiServer};
+	use index::IndexApiServer;
+	use pallet_contracts_rpc::{Contracts, ContractsApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
+	use uniques::{Uniques, UniquesApiServer};
 
 	let mut module = RpcModule::new(());
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps { client, pool, deny_unsafe, subscription_executor, index_db } = deps;
 
 	module.merge(System::new(client.clone(), pool.clone(), deny_unsafe).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	module.merge(Contracts::new(client.clone()).into_rpc())?;
+	module.merge(DryRun::new(client.clone(), deny_unsafe).into_rpc())?;
+	module.merge(Assets::new(client.clone()).into_rpc())?;
+	module.merge(Uniques::new(client.clone()).into_rpc())?;
+	module.merge(Events::new(client, subscription_executor).into_rpc())?;
+
+	if let Some(path) = index_db {
+		module.merge(index::Index::open(&path)?.into_rpc())?;
+	}
 
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
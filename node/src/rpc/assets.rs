@@ -0,0 +1,107 @@
+//! The `assets_balances` RPC: lists the balances of an account in all assets of `pallet_assets`.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{
+	apis::{AssetBalance as RuntimeAssetBalance, AssetsApi as AssetsRuntimeApi},
+	AccountId, AssetId,
+};
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+/// Balance of an account in an asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetBalance {
+	/// Id of the asset.
+	pub asset_id: AssetId,
+	/// Balance in the asset's smallest unit, as a decimal string since it may not fit a JSON
+	/// number.
+	pub balance: String,
+	/// Name of the asset, empty if it has no metadata.
+	pub name: String,
+	/// Symbol of the asset, empty if it has no metadata.
+	pub symbol: String,
+	/// Number of decimals of the asset, zero if it has no metadata.
+	pub decimals: u8,
+}
+
+impl From<RuntimeAssetBalance> for AssetBalance {
+	fn from(balance: RuntimeAssetBalance) -> Self {
+		Self {
+			asset_id: balance.asset_id,
+			balance: balance.balance.to_string(),
+			name: String::from_utf8_lossy(&balance.name).into_owned(),
+			symbol: String::from_utf8_lossy(&balance.symbol).into_owned(),
+			decimals: balance.decimals,
+		}
+	}
+}
+
+/// Asset RPC methods.
+#[rpc(server)]
+pub trait AssetsApi<BlockHash> {
+	/// Returns the nonzero balances of `account` in all assets at block `at` (the best block by
+	/// default), ordered by asset id.
+	#[method(name = "assets_balances")]
+	fn balances(&self, account: AccountId, at: Option<BlockHash>) -> RpcResult<Vec<AssetBalance>>;
+}
+
+/// Error codes of the asset RPC.
+pub enum Error {
+	/// The runtime could not list the balances.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+/// Implements [`AssetsApiServer`] on top of a client.
+pub struct Assets<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Assets<C, Block> {
+	/// Creates a new instance of the asset RPC.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block> AssetsApiServer<<Block as BlockT>::Hash> for Assets<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: AssetsRuntimeApi<Block>,
+{
+	fn balances(
+		&self,
+		account: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<AssetBalance>> {
+		let at = BlockId::Hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let balances = self
+			.client
+			.runtime_api()
+			.account_balances(&at, account)
+			.map_err(|e| error(Error::RuntimeError, "Unable to list the asset balances", e))?;
+		Ok(balances.into_iter().map(Into::into).collect())
+	}
+}
+
+fn error(code: Error, message: &str, data: impl ToString) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(code.into(), message, Some(data.to_string()))).into()
+}
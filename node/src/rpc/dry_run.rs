@@ -0,0 +1,234 @@
+//! The `dev_dryRun` RPC: applies an extrinsic on top of a block and reports its outcome without
+//! persisting any state.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Decode;
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{
+	apis::DryRunApi as DryRunRuntimeApi, AccountId, Address, Call, Event, Signature, SignedExtra,
+};
+use sc_rpc_api::DenyUnsafe;
+use serde::Serialize;
+use sp_api::{Core, Metadata as MetadataApi, ProvideRuntimeApi};
+use sp_block_builder::BlockBuilder;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{
+	generic::{BlockId, UncheckedExtrinsic},
+	traits::{Block as BlockT, Header as HeaderT, One},
+	transaction_validity::TransactionValidityError,
+	DispatchError,
+};
+
+use crate::metadata::{DecodedEvent, Metadata, NamedError};
+
+/// Outcome of a dry run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunResult {
+	/// Whether the extrinsic would be included and how its dispatch would end.
+	pub outcome: Outcome,
+	/// Weight consumed by the dispatch, after refunds. Zero if the extrinsic is invalid.
+	pub weight: u64,
+	/// Events deposited while applying the extrinsic.
+	pub events: Vec<DecodedEvent>,
+}
+
+/// How applying an extrinsic ends.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "result", rename_all = "camelCase")]
+pub enum Outcome {
+	/// The call was dispatched successfully.
+	Success,
+	/// The extrinsic would be included, but its call failed.
+	#[serde(rename_all = "camelCase")]
+	DispatchError {
+		/// Debug representation of the [`DispatchError`].
+		error: String,
+		/// The pallet error, if the call failed with one.
+		module_error: Option<NamedError>,
+	},
+	/// The extrinsic would not be included in a block, e.g. because of a bad nonce.
+	Invalid {
+		/// Debug representation of the [`TransactionValidityError`].
+		error: String,
+	},
+}
+
+/// Call simulation RPC methods.
+#[rpc(server)]
+pub trait DryRunApi<BlockHash> {
+	/// Applies `extrinsic` on top of block `at` (the best block by default) and returns its
+	/// outcome, the weight it consumed and the events it deposited.
+	///
+	/// Without `signer`, `extrinsic` is applied exactly as a block author would apply it. With
+	/// `signer`, `extrinsic` must be unsigned and its call is dispatched as if it was signed by
+	/// `signer`, skipping signature, nonce and fee checks.
+	///
+	/// The extrinsic runs in a fresh block without inherents, so the timestamp is not set.
+	#[method(name = "dev_dryRun")]
+	fn dry_run(
+		&self,
+		extrinsic: Bytes,
+		signer: Option<AccountId>,
+		at: Option<BlockHash>,
+	) -> RpcResult<DryRunResult>;
+}
+
+/// Error codes of the dry run RPC.
+pub enum Error {
+	/// The extrinsic could not be decoded.
+	DecodeError,
+	/// The runtime could not execute the dry run.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::DecodeError => 1,
+			Error::RuntimeError => 2,
+		}
+	}
+}
+
+/// Implements [`DryRunApiServer`] on top of a client.
+pub struct DryRun<C, Block> {
+	client: Arc<C>,
+	deny_unsafe: DenyUnsafe,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> DryRun<C, Block> {
+	/// Creates a new instance of the dry run RPC.
+	pub fn new(client: Arc<C>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, deny_unsafe, _marker: Default::default() }
+	}
+}
+
+impl<C, Block> DryRunApiServer<<Block as BlockT>::Hash> for DryRun<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: BlockBuilder<Block> + MetadataApi<Block> + DryRunRuntimeApi<Block>,
+{
+	fn dry_run(
+		&self,
+		extrinsic: Bytes,
+		signer: Option<AccountId>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<DryRunResult> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let parent = self
+			.client
+			.header(BlockId::Hash(at))
+			.map_err(|e| error(Error::RuntimeError, "Unable to query the block header", e))?
+			.ok_or_else(|| error(Error::RuntimeError, "Unknown block", at))?;
+		let header = <Block::Header as HeaderT>::new(
+			*parent.number() + One::one(),
+			Default::default(),
+			Default::default(),
+			at,
+			Default::default(),
+		);
+		let at = BlockId::Hash(at);
+
+		// All calls below share the overlay of `api`, which is dropped without being committed.
+		let api = self.client.runtime_api();
+		api.initialize_block(&at, &header)
+			.map_err(|e| error(Error::RuntimeError, "Unable to initialize a block", e))?;
+
+		let (result, weight) = match signer {
+			Some(signer) => {
+				let call = decode_unsigned_call(&extrinsic)?;
+				let outcome = api
+					.apply_as(&at, signer, call)
+					.map_err(|e| error(Error::RuntimeError, "Unable to dispatch the call", e))?;
+				(Ok(outcome.result), Some(outcome.weight))
+			},
+			None => {
+				let extrinsic = Block::Extrinsic::decode(&mut &extrinsic[..])
+					.map_err(|e| error(Error::DecodeError, "Unable to decode the extrinsic", e))?;
+				let result = api
+					.apply_extrinsic(&at, extrinsic)
+					.map_err(|e| error(Error::RuntimeError, "Unable to apply the extrinsic", e))?;
+				(result, None)
+			},
+		};
+
+		let records = api
+			.events(&at)
+			.map_err(|e| error(Error::RuntimeError, "Unable to read the events", e))?;
+		// Events deposited while initializing the block are not part of the extrinsic's effects.
+		let records = records
+			.into_iter()
+			.filter(|record| matches!(record.phase, frame_system::Phase::ApplyExtrinsic(_)));
+
+		let metadata = Metadata::at(&*self.client, &at)
+			.map_err(|e| error(Error::RuntimeError, "Unable to decode the events", e))?;
+		let mut events = Vec::new();
+		let mut dispatch_weight = 0;
+		for record in records {
+			if let Event::System(
+				frame_system::Event::ExtrinsicSuccess { dispatch_info } |
+				frame_system::Event::ExtrinsicFailed { dispatch_info, .. },
+			) = &record.event
+			{
+				dispatch_weight = dispatch_info.weight;
+			}
+			let event = metadata
+				.decode_event(&mut &codec::Encode::encode(&record.event)[..])
+				.map_err(|e| error(Error::RuntimeError, "Unable to decode the events", e))?;
+			events.push(event);
+		}
+
+		Ok(DryRunResult {
+			outcome: outcome(&metadata, result),
+			weight: weight.unwrap_or(dispatch_weight),
+			events,
+		})
+	}
+}
+
+/// Decodes an unsigned extrinsic and returns its call.
+fn decode_unsigned_call(extrinsic: &[u8]) -> RpcResult<Call> {
+	let extrinsic =
+		UncheckedExtrinsic::<Address, Call, Signature, SignedExtra>::decode(&mut &extrinsic[..])
+			.map_err(|e| error(Error::DecodeError, "Unable to decode the extrinsic", e))?;
+	if extrinsic.signature.is_some() {
+		return Err(error(
+			Error::DecodeError,
+			"Extrinsic is signed",
+			"only unsigned extrinsics can be dispatched with a `signer`",
+		))
+	}
+	Ok(extrinsic.function)
+}
+
+fn outcome(
+	metadata: &Metadata,
+	result: Result<Result<(), DispatchError>, TransactionValidityError>,
+) -> Outcome {
+	match result {
+		Ok(Ok(())) => Outcome::Success,
+		Ok(Err(e)) => Outcome::DispatchError {
+			error: format!("{:?}", e),
+			module_error: match e {
+				DispatchError::Module(module_error) => metadata.module_error(&module_error),
+				_ => None,
+			},
+		},
+		Err(e) => Outcome::Invalid { error: format!("{:?}", e) },
+	}
+}
+
+fn error(code: Error, message: &str, data: impl ToString) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(code.into(), message, Some(data.to_string()))).into()
+}
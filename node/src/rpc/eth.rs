@@ -0,0 +1,153 @@
+//! Ethereum `eth_*`, `net_*` and `web3_*` RPC methods served by Frontier.
+//!
+//! Only compiled with the `evm` feature.
+
+use std::sync::Arc;
+
+use fc_rpc::{EthBlockDataCacheTask, EthDevSigner, EthSigner, OverrideHandle};
+use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
+use jsonrpsee::RpcModule;
+use node_template_runtime::{evm::TransactionConverter, opaque::Block, Hash};
+use sc_client_api::{
+	backend::{Backend, StateBackend, StorageProvider},
+	client::BlockchainEvents,
+	AuxStore,
+};
+use sc_network::NetworkService;
+use sc_rpc::SubscriptionTaskExecutor;
+use sc_transaction_pool::{ChainApi, Pool};
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::ProvideRuntimeApi;
+use sp_block_builder::BlockBuilder;
+use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::BlakeTwo256;
+
+/// Dependencies of the Ethereum RPC.
+pub struct EthDeps<C, P, A: ChainApi> {
+	/// The client instance to use.
+	pub client: Arc<C>,
+	/// Transaction pool instance.
+	pub pool: Arc<P>,
+	/// Graph of the transaction pool, used to look up pending Ethereum transactions.
+	pub graph: Arc<Pool<A>>,
+	/// Whether the node is an authority, which enables `eth_mining`.
+	pub is_authority: bool,
+	/// Whether `eth_sendTransaction` may sign with the built-in development key.
+	pub enable_dev_signer: bool,
+	/// Network service.
+	pub network: Arc<NetworkService<Block, Hash>>,
+	/// Maps Ethereum block and transaction hashes to substrate blocks.
+	pub frontier_backend: Arc<fc_db::Backend<Block>>,
+	/// Filters created by `eth_newFilter` and friends.
+	pub filter_pool: FilterPool,
+	/// Maximum number of filters kept in `filter_pool`.
+	pub max_stored_filters: usize,
+	/// Maximum number of logs returned by a single query.
+	pub max_past_logs: u32,
+	/// Recent fee history served by `eth_feeHistory`.
+	pub fee_history_cache: FeeHistoryCache,
+	/// Maximum number of blocks kept in `fee_history_cache`.
+	pub fee_history_limit: u64,
+	/// Reads the Ethereum state directly from storage for known schemas.
+	pub overrides: Arc<OverrideHandle<Block>>,
+	/// Cache of Ethereum blocks and transaction statuses.
+	pub block_data_cache: Arc<EthBlockDataCacheTask<Block>>,
+	/// Executor for the `eth_subscribe` subscriptions.
+	pub subscription_executor: SubscriptionTaskExecutor,
+}
+
+/// Merges the Ethereum RPC methods into `module`.
+pub fn create_eth<C, BE, P, A>(
+	mut module: RpcModule<()>,
+	deps: EthDeps<C, P, A>,
+) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
+where
+	BE: Backend<Block> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + AuxStore,
+	C: BlockchainEvents<Block>,
+	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: Send + Sync + 'static,
+	C::Api: BlockBuilder<Block>,
+	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
+	C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
+	P: TransactionPool<Block = Block> + 'static,
+	A: ChainApi<Block = Block> + 'static,
+{
+	use fc_rpc::{
+		Eth, EthApiServer, EthFilter, EthFilterApiServer, EthPubSub, EthPubSubApiServer, Net,
+		NetApiServer, Web3, Web3ApiServer,
+	};
+
+	let EthDeps {
+		client,
+		pool,
+		graph,
+		is_authority,
+		enable_dev_signer,
+		network,
+		frontier_backend,
+		filter_pool,
+		max_stored_filters,
+		max_past_logs,
+		fee_history_cache,
+		fee_history_limit,
+		overrides,
+		block_data_cache,
+		subscription_executor,
+	} = deps;
+
+	let mut signers = Vec::new();
+	if enable_dev_signer {
+		signers.push(Box::new(EthDevSigner::new()) as Box<dyn EthSigner>);
+	}
+
+	module.merge(
+		Eth::new(
+			client.clone(),
+			pool.clone(),
+			graph,
+			Some(TransactionConverter),
+			network.clone(),
+			signers,
+			overrides.clone(),
+			frontier_backend.clone(),
+			is_authority,
+			block_data_cache.clone(),
+			fee_history_cache,
+			fee_history_limit,
+		)
+		.into_rpc(),
+	)?;
+
+	module.merge(
+		EthFilter::new(
+			client.clone(),
+			frontier_backend,
+			filter_pool,
+			max_stored_filters,
+			max_past_logs,
+			block_data_cache,
+		)
+		.into_rpc(),
+	)?;
+
+	module.merge(
+		Net::new(
+			client.clone(),
+			network.clone(),
+			// Whether to format the `peer_count` response as Hex (default) or not.
+			true,
+		)
+		.into_rpc(),
+	)?;
+
+	module.merge(Web3::new(client.clone()).into_rpc())?;
+
+	module.merge(
+		EthPubSub::new(pool, client, network, subscription_executor, overrides).into_rpc(),
+	)?;
+
+	Ok(module)
+}
+
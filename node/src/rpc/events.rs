@@ -0,0 +1,203 @@
+//! The `events_subscribe` RPC: pushes runtime events matching a filter as blocks are imported or
+//! finalized, so clients don't have to watch and decode `System::Events` themselves.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use futures::{future, stream, FutureExt, StreamExt};
+use jsonrpsee::{proc_macros::rpc, types::SubscriptionResult, SubscriptionSink};
+use node_template_runtime::AccountId;
+use sc_client_api::{BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_api::{Metadata as MetadataApi, ProvideRuntimeApi};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+use crate::metadata::{read_events, DecodedEvent, MetadataCache};
+
+/// Selects the events pushed to a subscriber. Unset fields match any event.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EventFilter {
+	/// Name of the pallet that deposited the event, e.g. `TemplateModule`.
+	pub pallet: Option<String>,
+	/// Name of the event variant, e.g. `SomethingStored`.
+	pub variant: Option<String>,
+	/// An account that must appear in one of the event's fields.
+	pub account: Option<AccountId>,
+}
+
+impl EventFilter {
+	fn matches(&self, event: &DecodedEvent) -> bool {
+		self.pallet.as_ref().map_or(true, |pallet| *pallet == event.pallet) &&
+			self.variant.as_ref().map_or(true, |variant| *variant == event.event) &&
+			self.account.as_ref().map_or(true, |account| event.mentions(account.as_ref()))
+	}
+}
+
+/// Which blocks events are pushed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Mode {
+	/// Blocks becoming part of the best chain. Events may be pushed for blocks that are later
+	/// retracted.
+	Best,
+	/// Finalized blocks only.
+	Finalized,
+}
+
+impl Default for Mode {
+	fn default() -> Self {
+		Mode::Best
+	}
+}
+
+/// An event pushed to a subscriber.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredEvent<Hash> {
+	/// Hash of the block that deposited the event.
+	pub block_hash: Hash,
+	/// Index of the extrinsic that deposited the event, if any.
+	pub extrinsic_index: Option<u32>,
+	/// The event.
+	#[serde(flatten)]
+	pub event: DecodedEvent,
+}
+
+/// Event subscription RPC methods.
+#[rpc(server)]
+pub trait EventsApi<BlockHash> {
+	/// Subscribes to the events matching `filter` in new best blocks, or in finalized blocks with
+	/// `mode` `finalized`.
+	#[subscription(
+		name = "events_subscribe" => "events_event",
+		unsubscribe = "events_unsubscribe",
+		item = FilteredEvent<BlockHash>,
+	)]
+	fn subscribe(&self, filter: EventFilter, mode: Option<Mode>);
+}
+
+/// Implements [`EventsApiServer`] on top of a client.
+pub struct Events<C, BE, Block> {
+	client: Arc<C>,
+	executor: SubscriptionTaskExecutor,
+	_marker: PhantomData<(BE, Block)>,
+}
+
+impl<C, BE, Block> Events<C, BE, Block> {
+	/// Creates a new instance of the events RPC.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, executor, _marker: Default::default() }
+	}
+}
+
+impl<C, BE, Block> EventsApiServer<<Block as BlockT>::Hash> for Events<C, BE, Block>
+where
+	Block: BlockT,
+	BE: sc_client_api::Backend<Block> + 'static,
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	C::Api: MetadataApi<Block>,
+{
+	fn subscribe(
+		&self,
+		mut sink: SubscriptionSink,
+		filter: EventFilter,
+		mode: Option<Mode>,
+	) -> SubscriptionResult {
+		let blocks = match mode.unwrap_or_default() {
+			Mode::Best => self
+				.client
+				.import_notification_stream()
+				.filter(|n| future::ready(n.is_new_best))
+				.map(|n| {
+					// Blocks of the new best chain that were imported before.
+					let mut hashes: Vec<_> = n
+						.tree_route
+						.iter()
+						.flat_map(|route| route.enacted().iter().map(|block| block.hash))
+						.collect();
+					hashes.push(n.hash);
+					hashes
+				})
+				.boxed(),
+			Mode::Finalized => self
+				.client
+				.finality_notification_stream()
+				.map(|n| {
+					// Blocks finalized implicitly by finalizing `n.hash`.
+					let mut hashes = n.tree_route.to_vec();
+					hashes.push(n.hash);
+					hashes
+				})
+				.boxed(),
+		};
+
+		let mut source = EventSource::<C, BE, Block>::new(self.client.clone());
+		let events = blocks
+			.map(move |hashes| {
+				let events: Vec<_> =
+					hashes.into_iter().flat_map(|hash| source.matching(hash, &filter)).collect();
+				stream::iter(events)
+			})
+			.flatten();
+
+		let fut = async move {
+			sink.pipe_from_stream(events).await;
+		};
+		self.executor.spawn("events-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
+
+/// Reads and decodes the events of blocks, caching the metadata of the latest runtime.
+struct EventSource<C, BE, Block> {
+	client: Arc<C>,
+	metadata: MetadataCache,
+	_marker: PhantomData<(BE, Block)>,
+}
+
+impl<C, BE, Block> EventSource<C, BE, Block>
+where
+	Block: BlockT,
+	BE: sc_client_api::Backend<Block>,
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE>,
+	C::Api: MetadataApi<Block>,
+{
+	fn new(client: Arc<C>) -> Self {
+		Self { client, metadata: Default::default(), _marker: Default::default() }
+	}
+
+	/// Returns the events of block `hash` matching `filter`. Blocks whose events can't be decoded
+	/// are skipped.
+	fn matching(
+		&mut self,
+		hash: Block::Hash,
+		filter: &EventFilter,
+	) -> Vec<FilteredEvent<Block::Hash>> {
+		let at = BlockId::Hash(hash);
+		let events = self
+			.metadata
+			.at(&*self.client, &at)
+			.and_then(|metadata| read_events(&*self.client, &at, &metadata));
+
+		match events {
+			Ok(events) => events
+				.into_iter()
+				.filter(|(_, event)| filter.matches(event))
+				.map(|(phase, event)| FilteredEvent {
+					block_hash: hash,
+					extrinsic_index: match phase {
+						frame_system::Phase::ApplyExtrinsic(index) => Some(index),
+						_ => None,
+					},
+					event,
+				})
+				.collect(),
+			Err(e) => {
+				log::warn!("Skipping the events of block {}: {}", hash, e);
+				Vec::new()
+			},
+		}
+	}
+}
@@ -0,0 +1,83 @@
+//! The `index_query` RPC: serves account history from the database written by the indexer.
+//!
+//! Only served when the node runs with `--index-db`.
+
+use std::{path::Path, sync::Mutex};
+
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::AccountId;
+use serde::{Deserialize, Serialize};
+
+use crate::indexer::db::{HistoryItem, IndexDb};
+
+/// Number of items per page if the request doesn't say otherwise.
+const DEFAULT_PAGE_SIZE: u32 = 20;
+
+/// Selects a page of history.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Page {
+	/// Number of items to skip.
+	#[serde(default)]
+	pub offset: u32,
+	/// Maximum number of items to return, at most
+	/// [`MAX_PAGE_SIZE`](crate::indexer::db::MAX_PAGE_SIZE).
+	pub limit: Option<u32>,
+}
+
+/// A page of account history.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountHistory {
+	/// Events mentioning the account, newest first.
+	pub items: Vec<HistoryItem>,
+	/// Offset of the next page, if there may be one.
+	pub next_offset: Option<u32>,
+}
+
+/// Indexer RPC methods.
+#[rpc(server)]
+pub trait IndexApi {
+	/// Returns the events mentioning `account`, newest first, with the balance changes they
+	/// caused.
+	#[method(name = "index_query")]
+	fn query(&self, account: AccountId, page: Option<Page>) -> RpcResult<AccountHistory>;
+}
+
+/// Implements [`IndexApiServer`] on top of the index database.
+pub struct Index {
+	db: Mutex<IndexDb>,
+}
+
+impl Index {
+	/// Opens the index database at `path`.
+	pub fn open(path: &Path) -> rusqlite::Result<Self> {
+		Ok(Self { db: Mutex::new(IndexDb::open(path)?) })
+	}
+}
+
+impl IndexApiServer for Index {
+	fn query(&self, account: AccountId, page: Option<Page>) -> RpcResult<AccountHistory> {
+		let Page { offset, limit } = page.unwrap_or_default();
+		let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(crate::indexer::db::MAX_PAGE_SIZE);
+
+		let items = self
+			.db
+			.lock()
+			.expect("index database lock poisoned")
+			.account_history(account.as_ref(), offset, limit)
+			.map_err(error)?;
+		let next_offset = (items.len() as u32 == limit).then(|| offset + limit);
+
+		Ok(AccountHistory { items, next_offset })
+	}
+}
+
+fn error(e: rusqlite::Error) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(1, "Failed to query the index", Some(e.to_string())))
+		.into()
+}
@@ -0,0 +1,85 @@
+//! The `uniques_ownedItems` RPC: lists the unique items of `pallet_uniques` owned by an account.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{apis::UniquesApi as UniquesRuntimeApi, AccountId, CollectionId, ItemId};
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+/// A unique item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnedItem {
+	/// Id of the collection of the item.
+	pub collection: CollectionId,
+	/// Id of the item within its collection.
+	pub item: ItemId,
+}
+
+/// Unique item RPC methods.
+#[rpc(server)]
+pub trait UniquesApi<BlockHash> {
+	/// Returns the items owned by `account` at block `at` (the best block by default), ordered
+	/// by collection and item id.
+	#[method(name = "uniques_ownedItems")]
+	fn owned_items(&self, account: AccountId, at: Option<BlockHash>) -> RpcResult<Vec<OwnedItem>>;
+}
+
+/// Error codes of the unique item RPC.
+pub enum Error {
+	/// The runtime could not list the items.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+/// Implements [`UniquesApiServer`] on top of a client.
+pub struct Uniques<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Uniques<C, Block> {
+	/// Creates a new instance of the unique item RPC.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block> UniquesApiServer<<Block as BlockT>::Hash> for Uniques<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: UniquesRuntimeApi<Block>,
+{
+	fn owned_items(
+		&self,
+		account: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<OwnedItem>> {
+		let at = BlockId::Hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let items = self
+			.client
+			.runtime_api()
+			.owned_items(&at, account)
+			.map_err(|e| error(Error::RuntimeError, "Unable to list the owned items", e))?;
+		Ok(items.into_iter().map(|(collection, item)| OwnedItem { collection, item }).collect())
+	}
+}
+
+fn error(code: Error, message: &str, data: impl ToString) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(code.into(), message, Some(data.to_string()))).into()
+}
@@ -1,15 +1,20 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use crate::chain_spec::{BackoffSettings, GrandpaSettings, SlotSettings};
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_client_api::{BlockBackend, ExecutorProvider};
-use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+#[cfg(not(any(feature = "babe", feature = "pow")))]
+use sc_consensus_aura::{ImportQueueParams, StartAuraParams};
+#[cfg(not(feature = "pow"))]
+use sc_consensus_slots::SlotProportion;
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
+#[cfg(not(any(feature = "babe", feature = "pow")))]
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -31,10 +36,38 @@ impl sc_executor::NativeExecutionDispatch for ExecutorDispatch {
 	}
 }
 
-pub(crate) type FullClient =
+/// Client of a full node.
+pub type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
-type FullBackend = sc_service::TFullBackend<Block>;
+/// Backend of a full node.
+pub type FullBackend = sc_service::TFullBackend<Block>;
+/// Transaction pool of a full node.
+pub type FullPool = sc_transaction_pool::FullPool<Block, FullClient>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+type FullGrandpaBlockImport =
+	sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
+
+/// Block import of the block authoring consensus, wrapping GRANDPA's.
+#[cfg(not(any(feature = "babe", feature = "pow")))]
+type ConsensusBlockImport = FullGrandpaBlockImport;
+/// Block import of the block authoring consensus, wrapping GRANDPA's.
+#[cfg(feature = "babe")]
+type ConsensusBlockImport =
+	sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>;
+
+/// Block import of the block authoring consensus, wrapping GRANDPA's. Boxed, because the type of
+/// proof-of-work's includes the closure creating its inherent data providers.
+#[cfg(feature = "pow")]
+type ConsensusBlockImport =
+	sc_consensus::BoxBlockImport<Block, sp_api::TransactionFor<FullClient, Block>>;
+
+/// State the import queue of the block authoring consensus shares with its worker. Aura and
+/// proof-of-work have none.
+#[cfg(not(feature = "babe"))]
+type ConsensusLink = ();
+/// State the import queue of the block authoring consensus shares with its worker.
+#[cfg(feature = "babe")]
+type ConsensusLink = sc_consensus_babe::BabeLink<Block>;
 
 pub fn new_partial(
 	config: &Configuration,
@@ -44,14 +77,10 @@ pub fn new_partial(
 		FullBackend,
 		FullSelectChain,
 		sc_consensus::DefaultImportQueue<Block, FullClient>,
-		sc_transaction_pool::FullPool<Block, FullClient>,
+		FullPool,
 		(
-			sc_finality_grandpa::GrandpaBlockImport<
-				FullBackend,
-				Block,
-				FullClient,
-				FullSelectChain,
-			>,
+			ConsensusBlockImport,
+			ConsensusLink,
 			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
 			Option<Telemetry>,
 		),
@@ -110,32 +139,99 @@ pub fn new_partial(
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
-	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+	#[cfg(not(any(feature = "babe", feature = "pow")))]
+	let (import_queue, block_import, consensus_link) = {
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 
-	let import_queue =
-		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
-			block_import: grandpa_block_import.clone(),
-			justification_import: Some(Box::new(grandpa_block_import.clone())),
-			client: client.clone(),
-			create_inherent_data_providers: move |_, ()| async move {
+		let import_queue =
+			sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
+				block_import: grandpa_block_import.clone(),
+				justification_import: Some(Box::new(grandpa_block_import.clone())),
+				client: client.clone(),
+				create_inherent_data_providers: move |_, ()| async move {
+					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+					let slot =
+						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+							*timestamp,
+							slot_duration,
+						);
+
+					Ok((timestamp, slot))
+				},
+				spawner: &task_manager.spawn_essential_handle(),
+				can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
+					client.executor().clone(),
+				),
+				registry: config.prometheus_registry(),
+				check_for_equivocation: Default::default(),
+				telemetry: telemetry.as_ref().map(|x| x.handle()),
+			})?;
+
+		(import_queue, grandpa_block_import, ())
+	};
+
+	#[cfg(feature = "babe")]
+	let (import_queue, block_import, consensus_link) = {
+		let (block_import, babe_link) = sc_consensus_babe::block_import(
+			sc_consensus_babe::Config::get(&*client)?,
+			grandpa_block_import.clone(),
+			client.clone(),
+		)?;
+
+		let slot_duration = babe_link.config().slot_duration();
+
+		let import_queue = sc_consensus_babe::import_queue(
+			babe_link.clone(),
+			block_import.clone(),
+			Some(Box::new(grandpa_block_import)),
+			client.clone(),
+			select_chain.clone(),
+			move |_, ()| async move {
 				let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
 				let slot =
-					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+					sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
 						*timestamp,
 						slot_duration,
 					);
 
 				Ok((timestamp, slot))
 			},
-			spawner: &task_manager.spawn_essential_handle(),
-			can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
-				client.executor().clone(),
-			),
-			registry: config.prometheus_registry(),
-			check_for_equivocation: Default::default(),
-			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+			telemetry.as_ref().map(|x| x.handle()),
+		)?;
+
+		(import_queue, block_import, babe_link)
+	};
+
+	#[cfg(feature = "pow")]
+	let (import_queue, block_import, consensus_link) = {
+		let algorithm = crate::pow::Blake2Algorithm::new(client.clone());
+
+		let block_import = sc_consensus_pow::PowBlockImport::new(
+			grandpa_block_import.clone(),
+			client.clone(),
+			algorithm.clone(),
+			// check the inherents of all blocks
+			0,
+			select_chain.clone(),
+			|_, ()| async { Ok(sp_timestamp::InherentDataProvider::from_system_time()) },
+			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+		);
+
+		let import_queue = sc_consensus_pow::import_queue(
+			Box::new(block_import.clone()),
+			Some(Box::new(grandpa_block_import)),
+			algorithm,
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		)?;
+
+		(import_queue, Box::new(block_import) as ConsensusBlockImport, ())
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -145,10 +241,79 @@ pub fn new_partial(
 		keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (grandpa_block_import, grandpa_link, telemetry),
+		other: (block_import, consensus_link, grandpa_link, telemetry),
 	})
 }
 
+/// How a node takes part in GRANDPA finality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ArgEnum)]
+pub enum GrandpaMode {
+	/// Run the full voter, voting if the node holds a GRANDPA authority key.
+	Voter,
+	/// Follow finality through the observer protocol without ever voting.
+	Observer,
+	/// Don't run GRANDPA, blocks are only finalized by imported justifications.
+	Off,
+}
+
+/// Node options that are not part of substrate's [`sc_cli::RunCmd`].
+#[derive(Debug, Clone, clap::Args)]
+pub struct NodeOptions {
+	/// Index blocks, extrinsics, events and balance changes into the SQLite database at this path
+	/// and serve the `index_query` RPC.
+	#[clap(long, value_name = "PATH")]
+	pub index_db: Option<PathBuf>,
+
+	/// How this node takes part in GRANDPA finality. `--no-grandpa` implies `off`.
+	#[clap(long, arg_enum, value_name = "MODE", default_value = "voter")]
+	pub grandpa_mode: GrandpaMode,
+
+	/// Interval in milliseconds at which GRANDPA gossips votes. Overrides the chain spec.
+	#[clap(long, value_name = "MS")]
+	pub grandpa_gossip_duration: Option<u64>,
+
+	/// Number of blocks after which a GRANDPA justification is stored. Overrides the chain spec.
+	#[clap(long, value_name = "BLOCKS")]
+	pub grandpa_justification_period: Option<u32>,
+
+	/// Portion of a slot a block author may spend proposing a block. Overrides the chain spec.
+	#[clap(long, value_name = "PORTION")]
+	pub block_proposal_slot_portion: Option<f32>,
+
+	/// Upper bound of the slot portion spent proposing after missed slots. Overrides the chain
+	/// spec.
+	#[clap(long, value_name = "PORTION")]
+	pub max_block_proposal_slot_portion: Option<f32>,
+
+	/// Whether block authoring backs off while finality lags behind. Overrides the chain spec.
+	#[clap(long, value_name = "BOOL")]
+	pub backoff_authoring: Option<bool>,
+
+	/// Number of unfinalized blocks tolerated before authoring backs off. Overrides the chain
+	/// spec.
+	#[clap(long, value_name = "BLOCKS")]
+	pub backoff_unfinalized_slack: Option<u32>,
+
+	/// Number of further unfinalized blocks per slot skipped when backing off. Overrides the
+	/// chain spec.
+	#[clap(long, value_name = "BLOCKS")]
+	pub backoff_authoring_bias: Option<u32>,
+
+	/// Maximum number of slots skipped in a row when backing off. Overrides the chain spec.
+	#[clap(long, value_name = "SLOTS")]
+	pub backoff_max_interval: Option<u32>,
+
+	/// Options of the proof-of-work miner.
+	#[cfg(feature = "pow")]
+	#[clap(flatten)]
+	pub pow: crate::pow::MiningConfiguration,
+
+	/// Options of the Ethereum RPC.
+	#[cfg(feature = "evm")]
+	#[clap(flatten)]
+	pub eth: crate::eth::EthConfiguration,
+}
+
 fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 	// FIXME: here would the concrete keystore be built,
 	//        must return a concrete type (NOT `LocalKeystore`) that
@@ -156,8 +321,79 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 	Err("Remote Keystore not supported.")
 }
 
+/// Resolves the consensus tuning of the chain spec with the command line overrides on top.
+fn consensus_settings(
+	config: &Configuration,
+	options: &NodeOptions,
+) -> Result<(GrandpaSettings, SlotSettings, BackoffSettings), ServiceError> {
+	let extensions = config.chain_spec.extensions();
+	let mut grandpa =
+		sc_chain_spec::get_extension::<GrandpaSettings>(extensions).cloned().unwrap_or_default();
+	let mut slots =
+		sc_chain_spec::get_extension::<SlotSettings>(extensions).cloned().unwrap_or_default();
+	let mut backoff =
+		sc_chain_spec::get_extension::<BackoffSettings>(extensions).cloned().unwrap_or_default();
+
+	if let Some(gossip_duration_ms) = options.grandpa_gossip_duration {
+		grandpa.gossip_duration_ms = gossip_duration_ms;
+	}
+	if let Some(justification_period) = options.grandpa_justification_period {
+		grandpa.justification_period = justification_period;
+	}
+	if let Some(portion) = options.block_proposal_slot_portion {
+		slots.block_proposal_slot_portion = portion;
+	}
+	if let Some(portion) = options.max_block_proposal_slot_portion {
+		slots.max_block_proposal_slot_portion = Some(portion);
+	}
+	if let Some(enabled) = options.backoff_authoring {
+		backoff.enabled = enabled;
+	}
+	if let Some(unfinalized_slack) = options.backoff_unfinalized_slack {
+		backoff.unfinalized_slack = unfinalized_slack;
+	}
+	if let Some(authoring_bias) = options.backoff_authoring_bias {
+		backoff.authoring_bias = authoring_bias;
+	}
+	if let Some(max_interval) = options.backoff_max_interval {
+		backoff.max_interval = max_interval;
+	}
+
+	grandpa
+		.validate()
+		.and_then(|()| slots.validate())
+		.and_then(|()| backoff.validate())
+		.map_err(|e| ServiceError::Other(format!("Invalid consensus settings: {}", e)))?;
+	Ok((grandpa, slots, backoff))
+}
+
+/// The components of a running full node that its users, e.g. in-process tests, get to drive.
+pub struct NewFullBase {
+	/// Manager of the node's tasks. The node stops when it is dropped.
+	pub task_manager: TaskManager,
+	/// The node's client.
+	pub client: Arc<FullClient>,
+	/// The node's transaction pool.
+	pub transaction_pool: Arc<FullPool>,
+}
+
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(config: Configuration, options: NodeOptions) -> Result<TaskManager, ServiceError> {
+	new_full_base(config, options).map(|NewFullBase { task_manager, .. }| task_manager)
+}
+
+/// Builds a new service for a full client and returns its components.
+pub fn new_full_base(
+	mut config: Configuration,
+	options: NodeOptions,
+) -> Result<NewFullBase, ServiceError> {
+	// Proof-of-work isn't slot based, it has no use for the slot and backoff settings.
+	#[cfg_attr(feature = "pow", allow(unused_variables))]
+	let (grandpa_settings, slot_settings, backoff_settings) =
+		consensus_settings(&config, &options)?;
+
+	// Only BABE shares state between its import queue and its worker.
+	#[cfg_attr(not(feature = "babe"), allow(unused_variables))]
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -166,7 +402,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		mut keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (block_import, grandpa_link, mut telemetry),
+		other: (block_import, consensus_link, grandpa_link, mut telemetry),
 	} = new_partial(&config)?;
 
 	if let Some(url) = &config.keystore_remote {
@@ -215,20 +451,89 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	}
 
 	let role = config.role.clone();
+	#[cfg(not(feature = "pow"))]
 	let force_authoring = config.force_authoring;
-	let backoff_authoring_blocks: Option<()> = None;
 	let name = config.network.node_name.clone();
-	let enable_grandpa = !config.disable_grandpa;
+	let grandpa_mode =
+		if config.disable_grandpa { GrandpaMode::Off } else { options.grandpa_mode };
 	let prometheus_registry = config.prometheus_registry().cloned();
+	#[cfg(not(feature = "pow"))]
+	let backoff_authoring_blocks = backoff_settings
+		.enabled
+		.then(|| {
+			crate::backoff::MeteredBackoff::new(&backoff_settings, prometheus_registry.as_ref())
+		})
+		.transpose()?;
+
+	#[cfg(feature = "evm")]
+	let frontier = {
+		let frontier = crate::eth::FrontierComponents::new(&config, client.clone())
+			.map_err(ServiceError::Other)?;
+		crate::eth::spawn_frontier_tasks(
+			&task_manager,
+			client.clone(),
+			backend.clone(),
+			&frontier,
+			&options.eth,
+		);
+		frontier
+	};
+
+	if let Some(path) = &options.index_db {
+		crate::indexer::spawn(&task_manager, client.clone(), path).map_err(ServiceError::Other)?;
+	}
 
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let index_db = options.index_db.clone();
+
+		#[cfg(feature = "evm")]
+		let eth_rpc = {
+			let block_data_cache = Arc::new(fc_rpc::EthBlockDataCacheTask::new(
+				task_manager.spawn_handle(),
+				frontier.overrides.clone(),
+				options.eth.eth_log_block_cache,
+				options.eth.eth_statuses_cache,
+				prometheus_registry.clone(),
+			));
+			let network = network.clone();
+			let is_authority = role.is_authority();
+			let eth_config = options.eth.clone();
+
+			move |subscription_executor| crate::rpc::eth::EthDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				graph: pool.pool().clone(),
+				is_authority,
+				enable_dev_signer: eth_config.enable_dev_signer,
+				network: network.clone(),
+				frontier_backend: frontier.backend.clone(),
+				filter_pool: frontier.filter_pool.clone(),
+				max_stored_filters: eth_config.max_stored_filters,
+				max_past_logs: eth_config.max_past_logs,
+				fee_history_cache: frontier.fee_history_cache.clone(),
+				fee_history_limit: eth_config.fee_history_limit,
+				overrides: frontier.overrides.clone(),
+				block_data_cache: block_data_cache.clone(),
+				subscription_executor,
+			}
+		};
+
+		Box::new(move |deny_unsafe, subscription_executor: sc_rpc::SubscriptionTaskExecutor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				subscription_executor: subscription_executor.clone(),
+				index_db: index_db.clone(),
+			};
+			let module = crate::rpc::create_full::<_, _, FullBackend>(deps)?;
+
+			#[cfg(feature = "evm")]
+			let module = crate::rpc::eth::create_eth(module, eth_rpc(subscription_executor))?;
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
-			crate::rpc::create_full(deps).map_err(Into::into)
+			Ok(module)
 		})
 	};
 
@@ -249,7 +554,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
 			client.clone(),
-			transaction_pool,
+			transaction_pool.clone(),
 			prometheus_registry.as_ref(),
 			telemetry.as_ref().map(|x| x.handle()),
 		);
@@ -257,20 +562,71 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let can_author_with =
 			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
 
-		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+		#[cfg(not(feature = "pow"))]
+		let block_proposal_slot_portion =
+			SlotProportion::new(slot_settings.block_proposal_slot_portion);
+		#[cfg(not(feature = "pow"))]
+		let max_block_proposal_slot_portion =
+			slot_settings.max_block_proposal_slot_portion.map(SlotProportion::new);
+
+		#[cfg(not(any(feature = "babe", feature = "pow")))]
+		{
+			let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+
+			let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
+				StartAuraParams {
+					slot_duration,
+					client: client.clone(),
+					select_chain,
+					block_import,
+					proposer_factory,
+					create_inherent_data_providers: move |_, ()| async move {
+						let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+						let slot =
+							sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+								*timestamp,
+								slot_duration,
+							);
+
+						Ok((timestamp, slot))
+					},
+					force_authoring,
+					backoff_authoring_blocks,
+					keystore: keystore_container.sync_keystore(),
+					can_author_with,
+					sync_oracle: network.clone(),
+					justification_sync_link: network.clone(),
+					block_proposal_slot_portion,
+					max_block_proposal_slot_portion,
+					telemetry: telemetry.as_ref().map(|x| x.handle()),
+				},
+			)?;
 
-		let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
-			StartAuraParams {
-				slot_duration,
-				client,
+			// the AURA authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager
+				.spawn_essential_handle()
+				.spawn_blocking("aura", Some("block-authoring"), aura);
+		}
+
+		#[cfg(feature = "babe")]
+		{
+			let slot_duration = consensus_link.config().slot_duration();
+
+			let babe = sc_consensus_babe::start_babe(sc_consensus_babe::BabeParams {
+				keystore: keystore_container.sync_keystore(),
+				client: client.clone(),
 				select_chain,
+				env: proposer_factory,
 				block_import,
-				proposer_factory,
+				sync_oracle: network.clone(),
+				justification_sync_link: network.clone(),
 				create_inherent_data_providers: move |_, ()| async move {
 					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
 					let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+						sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
 							*timestamp,
 							slot_duration,
 						);
@@ -279,66 +635,120 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 				},
 				force_authoring,
 				backoff_authoring_blocks,
-				keystore: keystore_container.sync_keystore(),
+				babe_link: consensus_link,
 				can_author_with,
-				sync_oracle: network.clone(),
-				justification_sync_link: network.clone(),
-				block_proposal_slot_portion: SlotProportion::new(2f32 / 3f32),
-				max_block_proposal_slot_portion: None,
+				block_proposal_slot_portion,
+				max_block_proposal_slot_portion,
 				telemetry: telemetry.as_ref().map(|x| x.handle()),
-			},
-		)?;
-
-		// the AURA authoring task is considered essential, i.e. if it
-		// fails we take down the service with it.
-		task_manager
-			.spawn_essential_handle()
-			.spawn_blocking("aura", Some("block-authoring"), aura);
+			})?;
+
+			// the BABE authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking(
+				"babe-proposer",
+				Some("block-authoring"),
+				babe,
+			);
+		}
+
+		#[cfg(feature = "pow")]
+		{
+			let (mining_handle, mining_worker) = sc_consensus_pow::start_mining_worker(
+				block_import,
+				client.clone(),
+				select_chain,
+				crate::pow::Blake2Algorithm::new(client.clone()),
+				proposer_factory,
+				network.clone(),
+				network.clone(),
+				None,
+				|_, ()| async { Ok(sp_timestamp::InherentDataProvider::from_system_time()) },
+				// rebuild the block being mined after this long, to include new transactions
+				Duration::from_secs(10),
+				// time to build a block
+				Duration::from_secs(2),
+				can_author_with,
+			);
+
+			// the mining worker proposes the blocks to mine and imports the mined ones, it is
+			// considered essential, i.e. if it fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking(
+				"pow",
+				Some("block-authoring"),
+				mining_worker,
+			);
+			crate::pow::spawn_miners(
+				options.pow.mining_threads,
+				mining_handle,
+				&task_manager.spawn_essential_handle(),
+			);
+		}
 	}
 
 	// if the node isn't actively participating in consensus then it doesn't
 	// need a keystore, regardless of which protocol we use below.
-	let keystore =
-		if role.is_authority() { Some(keystore_container.sync_keystore()) } else { None };
+	// Observers never vote, so they don't need access to the authority keys.
+	let keystore = if role.is_authority() && grandpa_mode == GrandpaMode::Voter {
+		Some(keystore_container.sync_keystore())
+	} else {
+		None
+	};
 
 	let grandpa_config = sc_finality_grandpa::Config {
-		// FIXME #1578 make this available through chainspec
-		gossip_duration: Duration::from_millis(333),
-		justification_period: 512,
+		gossip_duration: Duration::from_millis(grandpa_settings.gossip_duration_ms),
+		justification_period: grandpa_settings.justification_period,
 		name: Some(name),
-		observer_enabled: false,
+		observer_enabled: grandpa_mode == GrandpaMode::Observer,
 		keystore,
-		local_role: role,
+		local_role: role.clone(),
 		telemetry: telemetry.as_ref().map(|x| x.handle()),
 		protocol_name: grandpa_protocol_name,
 	};
 
-	if enable_grandpa {
-		// start the full GRANDPA voter
-		// NOTE: non-authorities could run the GRANDPA observer protocol, but at
-		// this point the full voter should provide better guarantees of block
-		// and vote data availability than the observer. The observer has not
-		// been tested extensively yet and having most nodes in a network run it
-		// could lead to finality stalls.
-		let grandpa_config = sc_finality_grandpa::GrandpaParams {
-			config: grandpa_config,
-			link: grandpa_link,
-			network,
-			voting_rule: sc_finality_grandpa::VotingRulesBuilder::default().build(),
-			prometheus_registry,
-			shared_voter_state: SharedVoterState::empty(),
-			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		};
-
-		// the GRANDPA voter task is considered infallible, i.e.
-		// if it fails we take down the service with it.
-		task_manager.spawn_essential_handle().spawn_blocking(
-			"grandpa-voter",
-			None,
-			sc_finality_grandpa::run_grandpa_voter(grandpa_config)?,
-		);
+	match grandpa_mode {
+		GrandpaMode::Voter => {
+			// start the full GRANDPA voter
+			// NOTE: non-authorities could run the GRANDPA observer protocol with
+			// `--grandpa-mode observer`, but by default the full voter is run, as it
+			// provides better guarantees of block and vote data availability than the
+			// observer. Having most nodes in a network run the observer could lead to
+			// finality stalls.
+			let grandpa_config = sc_finality_grandpa::GrandpaParams {
+				config: grandpa_config,
+				link: grandpa_link,
+				network,
+				voting_rule: sc_finality_grandpa::VotingRulesBuilder::default().build(),
+				prometheus_registry,
+				shared_voter_state: SharedVoterState::empty(),
+				telemetry: telemetry.as_ref().map(|x| x.handle()),
+			};
+
+			// the GRANDPA voter task is considered infallible, i.e.
+			// if it fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking(
+				"grandpa-voter",
+				None,
+				sc_finality_grandpa::run_grandpa_voter(grandpa_config)?,
+			);
+		},
+		GrandpaMode::Observer => {
+			if role.is_authority() {
+				log::warn!(
+					"Running GRANDPA as an observer on an authority node, its votes are not cast."
+				);
+			}
+
+			// the observer only follows the votes of others to finalize blocks, like
+			// the voter it is essential to the service.
+			task_manager.spawn_essential_handle().spawn_blocking(
+				"grandpa-observer",
+				None,
+				sc_finality_grandpa::run_grandpa_observer(grandpa_config, grandpa_link, network)?,
+			);
+		},
+		GrandpaMode::Off => {},
 	}
 
 	network_starter.start_network();
-	Ok(task_manager)
+	Ok(NewFullBase { task_manager, client, transaction_pool })
 }
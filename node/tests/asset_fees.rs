@@ -0,0 +1,123 @@
+//! Pays transaction fees in an asset of `pallet_assets` on a development node.
+
+mod common;
+
+use codec::{Decode, Encode};
+use common::{Node, TIMEOUT};
+use jsonrpsee::{core::client::ClientT, rpc_params, ws_client::WsClient};
+use node_template_runtime::{
+	pallet_asset_rate, AccountId, AssetId, AssetsCall, Balance, Call, Runtime, SudoCall,
+	UncheckedExtrinsic,
+};
+use serde_json::Value;
+use sp_core::{blake2_128, twox_128, Bytes};
+use sp_keyring::AccountKeyring;
+use sp_runtime::{FixedPointNumber, FixedU128, MultiAddress};
+use std::time::{Duration, Instant};
+
+const ASSET: AssetId = 7;
+const MINTED: Balance = 1_000_000_000_000_000;
+const RATE: u128 = 2;
+
+#[tokio::test]
+async fn accounts_without_native_balance_pay_fees_in_assets() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	// Charlie isn't endowed in the development chain spec, the sufficient asset alone keeps
+	// their account alive.
+	let alice = AccountKeyring::Alice;
+	let charlie = AccountKeyring::Charlie.to_account_id();
+	let create = AssetsCall::force_create {
+		id: ASSET,
+		owner: MultiAddress::Id(alice.to_account_id()),
+		is_sufficient: true,
+		min_balance: 1,
+	};
+	let set_rate = pallet_asset_rate::Call::set_rate {
+		asset_id: ASSET,
+		rate: Some(FixedU128::saturating_from_integer(RATE)),
+	};
+	let mint = AssetsCall::mint {
+		id: ASSET,
+		beneficiary: MultiAddress::Id(charlie.clone()),
+		amount: MINTED,
+	};
+	common::submit(&client, alice, sudo(create), 0).await;
+	common::submit(&client, alice, sudo(set_rate), 1).await;
+	common::submit(&client, alice, mint, 2).await;
+
+	let started = Instant::now();
+	while asset_balance(&client, &charlie).await != Some(MINTED) {
+		assert!(started.elapsed() < TIMEOUT, "the asset was not minted");
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+
+	let transfer = AssetsCall::transfer {
+		id: ASSET,
+		target: MultiAddress::Id(AccountKeyring::Bob.to_account_id()),
+		amount: 1_000,
+	};
+	let charlie = AccountKeyring::Charlie;
+	let native = common::sign(&client, charlie, transfer.clone(), 0).await;
+	let in_asset = common::sign_paying_in(&client, charlie, transfer, 0, Some(ASSET)).await;
+
+	let native_fee = partial_fee(&client, &native).await;
+	let asset_fee = partial_fee(&client, &in_asset).await;
+	assert!(native_fee > 0);
+	assert_eq!(asset_fee, native_fee * RATE);
+
+	client
+		.request::<Value>(
+			"author_submitExtrinsic",
+			rpc_params![format!("0x{}", hex::encode(in_asset.encode()))],
+		)
+		.await
+		.expect("submitting the transfer paying in the asset");
+	let charlie = charlie.to_account_id();
+	let started = Instant::now();
+	loop {
+		let balance = asset_balance(&client, &charlie).await.expect("Charlie holds the asset");
+		if balance < MINTED {
+			assert!(balance < MINTED - 1_000, "no fee was paid in the asset");
+			break
+		}
+		assert!(started.elapsed() < TIMEOUT, "the transfer was not included");
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+}
+
+fn sudo(call: impl Into<Call>) -> SudoCall<Runtime> {
+	SudoCall::sudo { call: Box::new(call.into()) }
+}
+
+/// Fee of `tx` as reported by `payment_queryInfo`.
+async fn partial_fee(client: &WsClient, tx: &UncheckedExtrinsic) -> Balance {
+	let info: Value = client
+		.request("payment_queryInfo", rpc_params![Bytes(tx.encode())])
+		.await
+		.expect("querying the fee");
+	match &info["partialFee"] {
+		Value::String(fee) => fee.parse().expect("the fee is a number"),
+		fee => fee.as_u64().expect("the fee is a number").into(),
+	}
+}
+
+/// Balance of `ASSET` held by `who` at the best block.
+async fn asset_balance(client: &WsClient, who: &AccountId) -> Option<Balance> {
+	let key = [
+		&twox_128(b"Assets")[..],
+		&twox_128(b"Account")[..],
+		&blake2_128(&ASSET.encode())[..],
+		&ASSET.encode()[..],
+		&blake2_128(&who.encode())[..],
+		&who.encode()[..],
+	]
+	.concat();
+	let account: Option<Bytes> = client
+		.request("state_getStorage", rpc_params![Bytes(key)])
+		.await
+		.expect("querying the asset account");
+	// The balance comes first in `AssetAccount`.
+	account.map(|account| Balance::decode(&mut &account[..]).expect("decoding the balance"))
+}
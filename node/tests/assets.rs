@@ -0,0 +1,47 @@
+//! Lists the asset balances of accounts with `assets_balances` on a development node.
+
+mod common;
+
+use common::Node;
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+
+#[tokio::test]
+async fn lists_the_genesis_asset_of_endowed_accounts() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let alice = AccountKeyring::Alice.to_account_id().to_ss58check();
+	let balances: Value = client
+		.request("assets_balances", rpc_params![alice])
+		.await
+		.expect("querying Alice's balances");
+
+	// The development chain spec endows Alice with `1 << 60` of the asset `1`.
+	assert_eq!(
+		balances,
+		json!([{
+			"assetId": 1,
+			"balance": (1u128 << 60).to_string(),
+			"name": "Development Token",
+			"symbol": "DEV",
+			"decimals": 12,
+		}])
+	);
+}
+
+#[tokio::test]
+async fn lists_nothing_for_accounts_without_assets() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let ferdie = AccountKeyring::Ferdie.to_account_id().to_ss58check();
+	let balances: Value = client
+		.request("assets_balances", rpc_params![ferdie])
+		.await
+		.expect("querying Ferdie's balances");
+
+	assert_eq!(balances, json!([]));
+}
@@ -0,0 +1,41 @@
+//! Authors blocks with BABE instead of Aura.
+//!
+//! Requires the `babe` feature: `cargo test -p node-template --features babe --test babe`.
+
+#![cfg(feature = "babe")]
+
+mod common;
+
+use common::Node;
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use sp_keyring::AccountKeyring;
+
+#[tokio::test]
+async fn authors_and_finalizes_blocks_with_babe_keys() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	common::wait_for(&client, common::finalized_number, |n| n >= 2).await;
+
+	let alice = format!("0x{}", hex::encode(AccountKeyring::Alice.public()));
+	let has_babe_key: bool = client
+		.request("author_hasKey", rpc_params![alice.clone(), "babe"])
+		.await
+		.expect("querying the keystore");
+	assert!(has_babe_key);
+	let has_aura_key: bool = client
+		.request("author_hasKey", rpc_params![alice, "aura"])
+		.await
+		.expect("querying the keystore");
+	assert!(!has_aura_key);
+}
+
+#[tokio::test]
+async fn local_validators_share_the_slots() {
+	let alice = Node::start(&["--chain", "local", "--alice"]);
+	let bootnode = alice.multiaddr().await;
+	let bob = Node::start(&["--chain", "local", "--bob", "--bootnodes", &bootnode]);
+
+	let client = bob.client().await;
+	common::wait_for(&client, common::finalized_number, |n| n >= 3).await;
+}
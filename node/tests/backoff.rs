@@ -0,0 +1,45 @@
+//! Block authoring backs off while finality lags behind. Blocks are mined rather than authored in
+//! slots with the `pow` feature, which has no backoff.
+
+#![cfg(not(feature = "pow"))]
+
+mod common;
+
+use common::{Node, TIMEOUT};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn backs_off_without_finality() {
+	// Without GRANDPA nothing is finalized, so the author backs off once two blocks are
+	// unfinalized.
+	let node = Node::start(&[
+		"--dev",
+		"--grandpa-mode",
+		"off",
+		"--backoff-authoring",
+		"true",
+		"--backoff-unfinalized-slack",
+		"2",
+		"--backoff-authoring-bias",
+		"1",
+	]);
+	let client = node.client().await;
+	common::wait_for(&client, common::best_number, |n| n >= 4).await;
+
+	let started = Instant::now();
+	while node.metric("substrate_authoring_backoff") != Some(1.0) {
+		assert!(started.elapsed() < TIMEOUT, "authoring did not back off");
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+	assert!(node.metric("substrate_authoring_unfinalized_blocks").unwrap() >= 3.0);
+	assert_eq!(common::finalized_number(&client).await, 0);
+}
+
+#[tokio::test]
+async fn does_not_back_off_by_default() {
+	let node = Node::start(&["--dev", "--grandpa-mode", "off"]);
+	let client = node.client().await;
+	common::wait_for(&client, common::best_number, |n| n >= 4).await;
+
+	assert_eq!(node.metric("substrate_authoring_backoff"), None);
+}
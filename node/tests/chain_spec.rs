@@ -0,0 +1,78 @@
+//! Consensus tuning through the chain spec extensions and their command line overrides.
+
+use serde_json::Value;
+use std::process::{Command, Output};
+
+fn node_template(args: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_node-template"))
+		.args(args)
+		.output()
+		.expect("running node-template")
+}
+
+#[test]
+fn chain_specs_contain_the_default_consensus_settings() {
+	let output = node_template(&["build-spec", "--chain", "dev", "--disable-default-bootnode"]);
+	assert!(output.status.success());
+
+	let spec: Value = serde_json::from_slice(&output.stdout).expect("chain spec is JSON");
+	assert_eq!(spec["grandpa"]["gossipDurationMs"], 333);
+	assert_eq!(spec["grandpa"]["justificationPeriod"], 512);
+	assert_eq!(spec["slots"]["maxBlockProposalSlotPortion"], Value::Null);
+	assert_eq!(spec["backoff"]["enabled"], false);
+	assert_eq!(spec["backoff"]["unfinalizedSlack"], 50);
+}
+
+#[test]
+fn rejects_invalid_settings_of_the_chain_spec() {
+	let output = node_template(&["build-spec", "--chain", "dev", "--disable-default-bootnode"]);
+	let mut spec: Value = serde_json::from_slice(&output.stdout).unwrap();
+	spec["grandpa"]["justificationPeriod"] = 0.into();
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("spec.json");
+	std::fs::write(&path, spec.to_string()).unwrap();
+
+	let output = node_template(&["--chain", path.to_str().unwrap(), "--tmp", "--alice"]);
+	assert!(!output.status.success());
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("the GRANDPA justification period must be positive"), "{}", stderr);
+}
+
+#[test]
+fn reads_the_slot_settings_of_chain_specs_naming_them_aura() {
+	let output = node_template(&["build-spec", "--chain", "dev", "--disable-default-bootnode"]);
+	let mut spec: Value = serde_json::from_slice(&output.stdout).unwrap();
+	let mut slots = spec.as_object_mut().unwrap().remove("slots").expect("spec has slot settings");
+	slots["blockProposalSlotPortion"] = 1.5.into();
+	spec["aura"] = slots;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("spec.json");
+	std::fs::write(&path, spec.to_string()).unwrap();
+
+	// The node reads the settings, and refuses the portion out of range.
+	let output = node_template(&["--chain", path.to_str().unwrap(), "--tmp", "--alice"]);
+	assert!(!output.status.success());
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("the block proposal slot portion must be in (0, 1]"), "{}", stderr);
+}
+
+#[test]
+fn rejects_invalid_command_line_overrides() {
+	let output = node_template(&[
+		"--dev",
+		"--tmp",
+		"--block-proposal-slot-portion",
+		"0.8",
+		"--max-block-proposal-slot-portion",
+		"0.5",
+	]);
+	assert!(!output.status.success());
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(
+		stderr.contains("must not be smaller than the block proposal slot portion"),
+		"{}",
+		stderr
+	);
+}
@@ -0,0 +1,177 @@
+//! Runs a development node inside the test process, to drive its client and transaction pool
+//! directly instead of over RPC.
+
+use super::{connect, free_port, TIMEOUT};
+use clap::Parser;
+use codec::Decode;
+use frame_system::EventRecord;
+use futures::StreamExt;
+use jsonrpsee::ws_client::WsClient;
+use node_template::{
+	chain_spec,
+	command_helper::create_benchmark_extrinsic,
+	service::{self, FullClient, FullPool, NewFullBase, NodeOptions},
+};
+use node_template_runtime::{self as runtime, opaque::Block, Event, Hash};
+use sc_cli::{ChainSpec, CliConfiguration, RunCmd, RuntimeVersion, SubstrateCli};
+use sc_client_api::StorageProvider;
+use sc_service::TaskManager;
+use sc_transaction_pool_api::{TransactionPool, TransactionSource, TransactionStatus};
+use sp_core::{storage::StorageKey, twox_128};
+use sp_keyring::AccountKeyring;
+use sp_runtime::generic::BlockId;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Command line of the in-process node, parsed like the one of the binary.
+#[derive(Debug, Parser)]
+struct Cli {
+	#[clap(flatten)]
+	run: RunCmd,
+
+	#[clap(flatten)]
+	options: NodeOptions,
+}
+
+impl SubstrateCli for Cli {
+	fn impl_name() -> String {
+		"Substrate Node".into()
+	}
+
+	fn impl_version() -> String {
+		env!("CARGO_PKG_VERSION").into()
+	}
+
+	fn description() -> String {
+		env!("CARGO_PKG_DESCRIPTION").into()
+	}
+
+	fn author() -> String {
+		env!("CARGO_PKG_AUTHORS").into()
+	}
+
+	fn support_url() -> String {
+		"support.anonymous.an".into()
+	}
+
+	fn copyright_start_year() -> i32 {
+		2017
+	}
+
+	fn load_spec(&self, id: &str) -> Result<Box<dyn ChainSpec>, String> {
+		match id {
+			"dev" => Ok(Box::new(chain_spec::development_config()?)),
+			id => Err(format!("the in-process node only runs the development chain, not {}", id)),
+		}
+	}
+
+	fn native_runtime_version(_: &Box<dyn ChainSpec>) -> &'static RuntimeVersion {
+		&runtime::VERSION
+	}
+}
+
+/// A development node running in the test process, with its own base path and free ports. The
+/// node stops on drop.
+pub struct InProcessNode {
+	/// The node's client.
+	pub client: Arc<FullClient>,
+	/// The node's transaction pool.
+	pub transaction_pool: Arc<FullPool>,
+	/// Port of the websocket RPC server.
+	pub ws_port: u16,
+	// Declared before the base path, so that the node stops before its files are removed.
+	task_manager: TaskManager,
+	base_path: TempDir,
+}
+
+impl InProcessNode {
+	/// Starts a development node, Alice authoring and finalizing its blocks, with `args` on top of
+	/// `--dev`. Must be called from a multi-threaded Tokio runtime, which runs the node's tasks.
+	pub fn start(args: &[&str]) -> Self {
+		let base_path = tempfile::tempdir().expect("creating a temporary base path");
+		let ws_port = free_port();
+		let mut command_line = vec![
+			"node-template".to_string(),
+			"--dev".to_string(),
+			"--base-path".to_string(),
+			base_path.path().display().to_string(),
+			"--ws-port".to_string(),
+			ws_port.to_string(),
+			"--rpc-port".to_string(),
+			free_port().to_string(),
+			"--port".to_string(),
+			free_port().to_string(),
+			"--prometheus-port".to_string(),
+			free_port().to_string(),
+			"--no-telemetry".to_string(),
+			"--no-mdns".to_string(),
+		];
+		command_line.extend(args.iter().map(|arg| arg.to_string()));
+		let cli = Cli::parse_from(command_line);
+
+		let config = cli
+			.run
+			.create_configuration(&cli, tokio::runtime::Handle::current())
+			.expect("configuring the node");
+		let NewFullBase { task_manager, client, transaction_pool } =
+			service::new_full_base(config, cli.options).expect("starting the node");
+
+		Self { client, transaction_pool, ws_port, task_manager, base_path }
+	}
+
+	/// Connects to the node's websocket RPC.
+	pub async fn rpc(&self) -> WsClient {
+		connect(self.ws_port).await
+	}
+
+	/// Signs `call` by `signer` with `command_helper::create_benchmark_extrinsic`, the way the
+	/// `benchmark overhead` command does.
+	pub fn sign(
+		&self,
+		signer: AccountKeyring,
+		call: impl Into<runtime::Call>,
+		nonce: u32,
+	) -> runtime::UncheckedExtrinsic {
+		create_benchmark_extrinsic(&self.client, signer.pair(), call.into(), nonce)
+	}
+
+	/// Submits `extrinsic` to the transaction pool and waits for the block including it to be
+	/// finalized. Returns the hash of that block.
+	pub async fn submit_and_finalize(&self, extrinsic: runtime::UncheckedExtrinsic) -> Hash {
+		let best = BlockId::Hash(self.client.chain_info().best_hash);
+		let mut statuses = self
+			.transaction_pool
+			.submit_and_watch(&best, TransactionSource::External, extrinsic.into())
+			.await
+			.expect("submitting the extrinsic");
+
+		let finalized = async {
+			while let Some(status) = statuses.next().await {
+				match status {
+					TransactionStatus::Finalized(hash) => return hash,
+					TransactionStatus::Future |
+					TransactionStatus::Ready |
+					TransactionStatus::Broadcast(_) |
+					TransactionStatus::InBlock(_) |
+					TransactionStatus::Retracted(_) => (),
+					status => panic!("the extrinsic was not finalized: {:?}", status),
+				}
+			}
+			panic!("the transaction pool stopped watching the extrinsic")
+		};
+		tokio::time::timeout(TIMEOUT, finalized)
+			.await
+			.expect("timed out waiting for the extrinsic to be finalized")
+	}
+
+	/// Events deposited in the block `hash`.
+	pub fn events(&self, hash: Hash) -> Vec<EventRecord<Event, Hash>> {
+		let key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+		let events = self
+			.client
+			.storage(&BlockId::<Block>::Hash(hash), &key)
+			.expect("reading the events")
+			.expect("every block has events");
+		Decode::decode(&mut &events.0[..]).expect("decoding the events")
+	}
+}
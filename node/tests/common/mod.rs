@@ -0,0 +1,298 @@
+//! Helpers for running `node-template` binaries in integration tests.
+
+#![allow(dead_code)]
+
+pub mod in_process;
+
+use codec::Encode;
+use jsonrpsee::{
+	core::client::ClientT,
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_template_runtime as runtime;
+use serde_json::Value;
+use sp_keyring::AccountKeyring;
+use sp_runtime::generic::Era;
+use std::{
+	io::{Read, Write},
+	net::{TcpListener, TcpStream},
+	ops::Range,
+	path::Path,
+	process::{self, Child, Command, Stdio},
+	sync::atomic::{AtomicU32, Ordering},
+	time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+/// How long to wait for a node to reach an expected state before failing the test.
+pub const TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Ports [`free_ports`] picks from, below the ephemeral ports taken by outgoing connections.
+const PORT_RANGE: Range<u16> = 20_000..30_000;
+
+/// A running `node-template` process with its own base path. The process is killed on drop.
+pub struct Node {
+	process: Child,
+	base_path: TempDir,
+	/// Port of the websocket RPC server.
+	pub ws_port: u16,
+	/// Port of the libp2p listener.
+	pub p2p_port: u16,
+	/// Port of the Prometheus exporter.
+	pub prometheus_port: u16,
+}
+
+impl Node {
+	/// Starts `node-template` with `args`, a fresh base path and free ports.
+	pub fn start(args: &[&str]) -> Self {
+		let base_path = tempfile::tempdir().expect("creating a temporary base path");
+		let ws_port = free_port();
+		let p2p_port = free_port();
+		let prometheus_port = free_port();
+
+		let process = spawn(args, base_path.path(), ws_port, p2p_port, prometheus_port);
+		Self { process, base_path, ws_port, p2p_port, prometheus_port }
+	}
+
+	/// Stops the node and starts it again with `args`, keeping its base path and ports.
+	pub fn restart(&mut self, args: &[&str]) {
+		self.stop();
+		self.process = spawn(
+			args,
+			self.base_path.path(),
+			self.ws_port,
+			self.p2p_port,
+			self.prometheus_port,
+		);
+	}
+
+	/// Connects to the node's websocket RPC, waiting for the node to start listening.
+	pub async fn client(&self) -> WsClient {
+		connect(self.ws_port).await
+	}
+
+	/// Multiaddress other nodes can use to connect to this node.
+	pub async fn multiaddr(&self) -> String {
+		let peer_id: String = self
+			.client()
+			.await
+			.request("system_localPeerId", None)
+			.await
+			.expect("querying the local peer id");
+		format!("/ip4/127.0.0.1/tcp/{}/p2p/{}", self.p2p_port, peer_id)
+	}
+
+	/// Base path of the node, whose database other processes can open once the node is stopped.
+	pub fn base_path(&self) -> &Path {
+		self.base_path.path()
+	}
+
+	/// Kills the node.
+	pub fn stop(&mut self) {
+		let _ = self.process.kill();
+		let _ = self.process.wait();
+	}
+
+	/// Current value of the Prometheus metric `name`, if the node exports it.
+	pub fn metric(&self, name: &str) -> Option<f64> {
+		let mut stream = TcpStream::connect(("127.0.0.1", self.prometheus_port)).ok()?;
+		stream
+			.write_all(b"GET /metrics HTTP/1.0\r\nHost: 127.0.0.1\r\n\r\n")
+			.expect("requesting the metrics");
+		let mut response = String::new();
+		stream.read_to_string(&mut response).expect("reading the metrics");
+
+		response.lines().find_map(|line| {
+			let (metric, value) = line.rsplit_once(' ')?;
+			if metric.split('{').next()? != name {
+				return None
+			}
+			value.parse().ok()
+		})
+	}
+}
+
+impl Drop for Node {
+	fn drop(&mut self) {
+		let _ = self.process.kill();
+		let _ = self.process.wait();
+	}
+}
+
+/// Spawns `node-template` with `args` on the given base path and ports.
+fn spawn(
+	args: &[&str],
+	base_path: &Path,
+	ws_port: u16,
+	p2p_port: u16,
+	prometheus_port: u16,
+) -> Child {
+	Command::new(env!("CARGO_BIN_EXE_node-template"))
+		.args(args)
+		.arg("--base-path")
+		.arg(base_path)
+		.args(["--ws-port", &ws_port.to_string()])
+		.args(["--rpc-port", &free_port().to_string()])
+		.args(["--port", &p2p_port.to_string()])
+		.args(["--prometheus-port", &prometheus_port.to_string()])
+		.args(["--no-telemetry", "--no-mdns"])
+		.stdout(Stdio::null())
+		.stderr(Stdio::null())
+		.spawn()
+		.expect("spawning node-template")
+}
+
+/// Connects to the websocket RPC listening on `ws_port`, waiting for the node to start listening.
+pub async fn connect(ws_port: u16) -> WsClient {
+	let url = format!("ws://127.0.0.1:{}", ws_port);
+	let started = Instant::now();
+	loop {
+		match WsClientBuilder::default().build(&url).await {
+			Ok(client) => return client,
+			Err(e) if started.elapsed() > TIMEOUT => panic!("connecting to {}: {}", url, e),
+			Err(_) => tokio::time::sleep(Duration::from_millis(250)).await,
+		}
+	}
+}
+
+/// Returns a TCP port that is currently not in use.
+pub fn free_port() -> u16 {
+	TcpListener::bind("127.0.0.1:0")
+		.and_then(|listener| listener.local_addr())
+		.map(|addr| addr.port())
+		.expect("binding to a free port")
+}
+
+/// Returns the first of `len` consecutive TCP ports that are currently not in use.
+pub fn free_ports(len: u16) -> u16 {
+	static NEXT: AtomicU32 = AtomicU32::new(0);
+	let span = u32::from(PORT_RANGE.end - PORT_RANGE.start - len);
+	// Test binaries running at the same time start from different ports.
+	let offset = process::id().wrapping_mul(157);
+	for _ in 0..span / u32::from(len) {
+		let next = NEXT.fetch_add(u32::from(len), Ordering::Relaxed);
+		let base = PORT_RANGE.start + (offset.wrapping_add(next) % span) as u16;
+		if (base..base + len).all(|port| TcpListener::bind(("127.0.0.1", port)).is_ok()) {
+			return base
+		}
+	}
+	panic!("no {} consecutive ports are free", len)
+}
+
+/// Number of the best block.
+pub async fn best_number(client: &WsClient) -> u32 {
+	let header: Value =
+		client.request("chain_getHeader", None).await.expect("querying the best header");
+	header_number(&header)
+}
+
+/// Number of the latest finalized block.
+pub async fn finalized_number(client: &WsClient) -> u32 {
+	let hash: Value =
+		client.request("chain_getFinalizedHead", None).await.expect("querying the finalized head");
+	let header: Value = client
+		.request("chain_getHeader", rpc_params![hash])
+		.await
+		.expect("querying the finalized header");
+	header_number(&header)
+}
+
+/// Waits until `condition` holds for the block number returned by `number`.
+pub async fn wait_for<F, Fut>(client: &WsClient, number: F, condition: impl Fn(u32) -> bool)
+where
+	F: Fn(&WsClient) -> Fut,
+	Fut: std::future::Future<Output = u32>,
+{
+	let started = Instant::now();
+	loop {
+		let n = number(client).await;
+		if condition(n) {
+			return
+		}
+		assert!(started.elapsed() < TIMEOUT, "timed out at block {}", n);
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+}
+
+/// Submits `call` signed by `signer` with an immortal era and returns the extrinsic hash.
+pub async fn submit(
+	client: &WsClient,
+	signer: AccountKeyring,
+	call: impl Into<runtime::Call>,
+	nonce: u32,
+) -> String {
+	let extrinsic = sign(client, signer, call, nonce).await;
+	client
+		.request(
+			"author_submitExtrinsic",
+			rpc_params![format!("0x{}", hex::encode(extrinsic.encode()))],
+		)
+		.await
+		.expect("submitting the extrinsic")
+}
+
+/// Signs `call` by `signer` with an immortal era.
+pub async fn sign(
+	client: &WsClient,
+	signer: AccountKeyring,
+	call: impl Into<runtime::Call>,
+	nonce: u32,
+) -> runtime::UncheckedExtrinsic {
+	sign_paying_in(client, signer, call, nonce, None).await
+}
+
+/// Signs `call` by `signer` with an immortal era, paying the fee in `asset_id` if it is set.
+pub async fn sign_paying_in(
+	client: &WsClient,
+	signer: AccountKeyring,
+	call: impl Into<runtime::Call>,
+	nonce: u32,
+	asset_id: Option<runtime::AssetId>,
+) -> runtime::UncheckedExtrinsic {
+	let genesis_hash: sp_core::H256 = client
+		.request("chain_getBlockHash", rpc_params![0])
+		.await
+		.expect("querying the genesis hash");
+	let call = call.into();
+	let extra: runtime::SignedExtra = (
+		frame_system::CheckNonZeroSender::<runtime::Runtime>::new(),
+		frame_system::CheckSpecVersion::<runtime::Runtime>::new(),
+		frame_system::CheckTxVersion::<runtime::Runtime>::new(),
+		frame_system::CheckGenesis::<runtime::Runtime>::new(),
+		frame_system::CheckEra::<runtime::Runtime>::from(Era::Immortal),
+		frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
+		frame_system::CheckWeight::<runtime::Runtime>::new(),
+		runtime::pallet_feeless::CheckFeeless::from(
+			pallet_asset_tx_payment::ChargeAssetTxPayment::<runtime::Runtime>::from(0, asset_id),
+		),
+		runtime::pallet_priority::PrioritizeCalls::<runtime::Runtime>::new(),
+	);
+	let payload = runtime::SignedPayload::from_raw(
+		call.clone(),
+		extra.clone(),
+		(
+			(),
+			runtime::VERSION.spec_version,
+			runtime::VERSION.transaction_version,
+			genesis_hash,
+			genesis_hash,
+			(),
+			(),
+			(),
+			(),
+		),
+	);
+	let signature = payload.using_encoded(|e| signer.sign(e));
+	runtime::UncheckedExtrinsic::new_signed(
+		call,
+		signer.to_account_id().into(),
+		runtime::Signature::Sr25519(signature),
+		extra,
+	)
+}
+
+fn header_number(header: &Value) -> u32 {
+	let number = header["number"].as_str().expect("header has a number");
+	u32::from_str_radix(number.trim_start_matches("0x"), 16).expect("number is hex encoded")
+}
@@ -0,0 +1,62 @@
+//! Dry-runs calls of a contract using the template chain extension with `contracts_call` on a
+//! development node.
+
+mod common;
+
+use codec::Encode;
+use common::{Node, TIMEOUT};
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use node_template_runtime::{Contracts, ContractsCall};
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use std::time::{Duration, Instant};
+
+/// Contract writing `42` through the chain extension on deployment and returning what it reads
+/// through it when called.
+const TEMPLATE_EXTENSION: &str = include_str!("../../runtime/fixtures/template_extension.wat");
+
+#[tokio::test]
+async fn calls_a_contract_reading_something_through_the_chain_extension() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let alice = AccountKeyring::Alice;
+	let code = wat::parse_str(TEMPLATE_EXTENSION).expect("the fixture is valid");
+	let contract =
+		Contracts::contract_address(&alice.to_account_id(), &BlakeTwo256::hash(&code), &[]);
+	let instantiate = ContractsCall::instantiate_with_code {
+		value: 0,
+		gas_limit: 100_000_000_000,
+		storage_deposit_limit: None,
+		code,
+		data: vec![],
+		salt: vec![],
+	};
+	common::submit(&client, alice, instantiate, 0).await;
+
+	let request = json!({
+		"origin": alice.to_account_id().to_ss58check(),
+		"dest": contract.to_ss58check(),
+		"value": 0,
+		"gasLimit": 100_000_000_000u64,
+		"storageDepositLimit": null,
+		"inputData": "0x",
+	});
+	let expected = format!("0x{}", hex::encode(Some(42u32).encode()));
+	let started = Instant::now();
+	loop {
+		let outcome: Value = client
+			.request("contracts_call", rpc_params![&request])
+			.await
+			.expect("dry-running the call");
+		// The call fails with `ContractNotFound` until the contract is instantiated.
+		if let Some(data) = outcome["result"]["Ok"]["data"].as_str() {
+			assert_eq!(data, expected);
+			break
+		}
+		assert!(started.elapsed() < TIMEOUT, "the contract was not instantiated: {}", outcome);
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+}
@@ -0,0 +1,91 @@
+//! Simulates calls with `dev_dryRun` on a development node.
+
+mod common;
+
+use codec::Encode;
+use common::Node;
+use jsonrpsee::{core::client::ClientT, rpc_params, ws_client::WsClient};
+use node_template_runtime::{pallet_template, Address, Call, Signature, SignedExtra};
+use serde_json::Value;
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use sp_runtime::generic::UncheckedExtrinsic;
+
+#[tokio::test]
+async fn reports_the_decoded_pallet_error() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let result = dry_run_as_alice(&client, pallet_template::Call::cause_error {}).await;
+
+	assert_eq!(result["outcome"]["result"], "dispatchError");
+	assert_eq!(result["outcome"]["moduleError"]["pallet"], "TemplateModule");
+	assert_eq!(result["outcome"]["moduleError"]["error"], "NoneValue");
+}
+
+#[tokio::test]
+async fn reports_events_and_weight_without_persisting_state() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let result =
+		dry_run_as_alice(&client, pallet_template::Call::do_something { something: 7 }).await;
+
+	assert_eq!(result["outcome"]["result"], "success");
+	assert!(result["weight"].as_u64().unwrap() > 0);
+	let events = result["events"].as_array().unwrap();
+	assert!(events
+		.iter()
+		.any(|e| e["pallet"] == "TemplateModule" && e["event"] == "SomethingStored"));
+
+	// `Something` is still unset, so `cause_error` keeps failing.
+	let result = dry_run_as_alice(&client, pallet_template::Call::cause_error {}).await;
+	assert_eq!(result["outcome"]["moduleError"]["error"], "NoneValue");
+}
+
+#[tokio::test]
+async fn applies_signed_extrinsics_without_persisting_the_nonce() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let call = pallet_template::Call::do_something { something: 7 };
+	let extrinsic = common::sign(&client, AccountKeyring::Alice, call, 0).await;
+	let extrinsic = format!("0x{}", hex::encode(extrinsic.encode()));
+	for _ in 0..2 {
+		let result: Value = client
+			.request("dev_dryRun", rpc_params![&extrinsic])
+			.await
+			.expect("dry running the extrinsic");
+
+		// The same extrinsic applies twice, so its nonce was not bumped by the first dry run.
+		assert_eq!(result["outcome"]["result"], "success");
+		let events = result["events"].as_array().unwrap();
+		assert!(events
+			.iter()
+			.any(|e| e["pallet"] == "TemplateModule" && e["event"] == "SomethingStored"));
+		assert!(events
+			.iter()
+			.any(|e| e["pallet"] == "System" && e["event"] == "ExtrinsicSuccess"));
+	}
+
+	let alice = AccountKeyring::Alice.to_account_id().to_ss58check();
+	let nonce: u32 = client
+		.request("system_accountNextIndex", rpc_params![alice])
+		.await
+		.expect("querying Alice's nonce");
+	assert_eq!(nonce, 0);
+}
+
+async fn dry_run_as_alice(client: &WsClient, call: impl Into<Call>) -> Value {
+	let extrinsic =
+		UncheckedExtrinsic::<Address, Call, Signature, SignedExtra>::new_unsigned(call.into());
+	let alice = AccountKeyring::Alice.to_account_id().to_ss58check();
+
+	client
+		.request(
+			"dev_dryRun",
+			rpc_params![format!("0x{}", hex::encode(extrinsic.encode())), alice],
+		)
+		.await
+		.expect("dry running the call")
+}
@@ -0,0 +1,83 @@
+//! Subscribes to filtered events with `events_subscribe` on a development node.
+
+mod common;
+
+use common::{Node, TIMEOUT};
+use jsonrpsee::{
+	core::client::{ClientT, Subscription, SubscriptionClientT},
+	rpc_params,
+	ws_client::WsClient,
+};
+use node_template_runtime::pallet_template;
+use serde_json::{json, Value};
+use sp_keyring::AccountKeyring;
+use std::time::Duration;
+
+#[tokio::test]
+async fn pushes_matching_events_of_best_blocks() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let filter = json!({
+		"pallet": "TemplateModule",
+		"variant": "SomethingStored",
+		"account": AccountKeyring::Alice.to_account_id(),
+	});
+	let mut alice = subscribe(&client, filter, "best").await;
+	let bob = json!({ "account": AccountKeyring::Bob.to_account_id() });
+	let mut bob = subscribe(&client, bob, "best").await;
+
+	let call = pallet_template::Call::do_something { something: 42 };
+	common::submit(&client, AccountKeyring::Alice, call, 0).await;
+
+	let event = next(&mut alice).await;
+	assert_eq!(event["pallet"], "TemplateModule");
+	assert_eq!(event["event"], "SomethingStored");
+	assert!(event["blockHash"].is_string());
+	// The timestamp inherent is the first extrinsic of every block.
+	assert_eq!(event["extrinsicIndex"], 1);
+
+	// Bob took no part in anything so far.
+	assert!(tokio::time::timeout(Duration::from_secs(12), bob.next()).await.is_err());
+}
+
+#[tokio::test]
+async fn pushes_events_of_finalized_blocks() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let filter = json!({ "pallet": "System", "variant": "ExtrinsicSuccess" });
+	let mut subscription = subscribe(&client, filter, "finalized").await;
+
+	let event = next(&mut subscription).await;
+	assert_eq!(event["extrinsicIndex"], 0);
+	let header: Value = client
+		.request("chain_getHeader", rpc_params![event["blockHash"].clone()])
+		.await
+		.expect("querying the event's block");
+	let number = header["number"].as_str().expect("header has a number");
+	let number = u32::from_str_radix(number.trim_start_matches("0x"), 16).unwrap();
+
+	// The block is finalized, so it is the canonical block at its height.
+	assert!(number <= common::finalized_number(&client).await);
+	let canonical: Value = client
+		.request("chain_getBlockHash", rpc_params![number])
+		.await
+		.expect("querying the canonical block");
+	assert_eq!(canonical, event["blockHash"]);
+}
+
+async fn subscribe(client: &WsClient, filter: Value, mode: &str) -> Subscription<Value> {
+	client
+		.subscribe("events_subscribe", rpc_params![filter, mode], "events_unsubscribe")
+		.await
+		.expect("subscribing to events")
+}
+
+async fn next(subscription: &mut Subscription<Value>) -> Value {
+	tokio::time::timeout(TIMEOUT, subscription.next())
+		.await
+		.expect("timed out waiting for an event")
+		.expect("subscription closed")
+		.expect("invalid event")
+}
@@ -0,0 +1,171 @@
+//! Deploys and calls EVM contracts on a development node.
+//!
+//! Requires the `evm` feature: `cargo test -p node-template --features evm --test evm`.
+
+#![cfg(feature = "evm")]
+
+mod common;
+
+use common::{Node, TIMEOUT};
+use ethereum::{LegacyTransaction, LegacyTransactionMessage, TransactionAction, TransactionSignature};
+use jsonrpsee::{core::client::ClientT, rpc_params, ws_client::WsClient};
+use serde_json::{json, Value};
+use sp_core::{H160, H256, U256};
+use std::{
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+/// Chain id of the node template's EVM.
+const CHAIN_ID: u64 = 42;
+
+/// Development account pre-funded in the `dev` chain spec.
+const GERALD: &str = "0x6be02d1d3665660d22ff9624b7be0551ee1ac91b";
+const GERALD_KEY: &str = "99b3c12287537e38c90a9219d4cb074a89a16e9cdb20bf85728ebd97c343e342";
+
+/// Init code of a contract whose runtime code returns `42` for any call:
+/// `PUSH1 0x2a PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN`.
+const ANSWER_INIT_CODE: &str = "600a600c600039600a6000f3602a60005260206000f3";
+const ANSWER_RUNTIME_CODE: &str = "0x602a60005260206000f3";
+
+/// Address of the `pallet_template` precompile.
+const TEMPLATE_PRECOMPILE: &str = "0x0000000000000000000000000000000000000400";
+
+/// Address of the `pallet_balances` precompile.
+const BALANCES_PRECOMPILE: &str = "0x0000000000000000000000000000000000000401";
+
+/// Init code of a contract whose runtime code `DELEGATECALL`s the address in the first word of
+/// its input with the rest of the input, and returns whether the delegate call succeeded:
+/// `PUSH1 0x20 CALLDATASIZE SUB DUP1 PUSH1 0x20 PUSH1 0 CALLDATACOPY PUSH1 0 PUSH1 0 DUP3 PUSH1 0
+/// PUSH1 0 CALLDATALOAD GAS DELEGATECALL PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN`.
+const DELEGATOR_INIT_CODE: &str =
+	"601e600c600039601e6000f360203603806020600037600060008260006000355af460005260206000f3";
+
+#[tokio::test]
+async fn deploys_and_calls_a_contract() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let deploy = sign(0, TransactionAction::Create, hex::decode(ANSWER_INIT_CODE).unwrap());
+	let receipt = submit_and_wait(&client, deploy).await;
+	assert_eq!(receipt["status"], "0x1");
+
+	let contract = receipt["contractAddress"].as_str().expect("deployment returns an address");
+	let code: String = client.request("eth_getCode", rpc_params![contract, "latest"]).await.unwrap();
+	assert_eq!(code, ANSWER_RUNTIME_CODE);
+
+	let output = call(&client, contract, "0x").await;
+	assert_eq!(output, U256::from(42));
+}
+
+#[tokio::test]
+async fn template_precompile_writes_and_reads_something() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	// `set(7)`
+	let mut input = hex::decode("60fe47b1").unwrap();
+	input.extend_from_slice(H256::from_low_u64_be(7).as_bytes());
+	let to = H160::from_str(TEMPLATE_PRECOMPILE).unwrap();
+	let receipt = submit_and_wait(&client, sign(0, TransactionAction::Call(to), input)).await;
+	assert_eq!(receipt["status"], "0x1");
+
+	// `get()`
+	let output = call(&client, TEMPLATE_PRECOMPILE, "0x6d4ce63c").await;
+	assert_eq!(output, U256::from(7));
+}
+
+#[tokio::test]
+async fn precompiles_revert_state_changes_in_delegate_calls() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let deploy = sign(0, TransactionAction::Create, hex::decode(DELEGATOR_INIT_CODE).unwrap());
+	let receipt = submit_and_wait(&client, deploy).await;
+	assert_eq!(receipt["status"], "0x1");
+	let delegator = receipt["contractAddress"].as_str().expect("deployment returns an address");
+
+	let delegate = |precompile: &str, input: &str| {
+		let target = H256::from(H160::from_str(precompile).unwrap());
+		format!("0x{}{}", hex::encode(target), input)
+	};
+	// Reads are not dispatched, and succeed.
+	let get = delegate(TEMPLATE_PRECOMPILE, "6d4ce63c");
+	assert_eq!(call(&client, delegator, &get).await, U256::one());
+
+	// `set(7)`
+	let set = delegate(TEMPLATE_PRECOMPILE, &format!("60fe47b1{:064x}", 7));
+	assert_eq!(call(&client, delegator, &set).await, U256::zero());
+	// `transfer(<Alice>, 1)`
+	let alice = hex::encode(sp_keyring::AccountKeyring::Alice.to_raw_public());
+	let transfer = delegate(BALANCES_PRECOMPILE, &format!("6a467394{}{:064x}", alice, 1));
+	assert_eq!(call(&client, delegator, &transfer).await, U256::zero());
+}
+
+/// Signs an EIP-155 legacy transaction from Gerald.
+fn sign(nonce: u64, action: TransactionAction, input: Vec<u8>) -> Vec<u8> {
+	let message = LegacyTransactionMessage {
+		nonce: nonce.into(),
+		gas_price: 1_000_000_000u64.into(),
+		gas_limit: 1_000_000u64.into(),
+		action,
+		value: U256::zero(),
+		input,
+		chain_id: Some(CHAIN_ID),
+	};
+
+	let secret = libsecp256k1::SecretKey::parse_slice(&hex::decode(GERALD_KEY).unwrap()).unwrap();
+	let (signature, recovery_id) = libsecp256k1::sign(
+		&libsecp256k1::Message::parse(message.hash().as_fixed_bytes()),
+		&secret,
+	);
+	let rs = signature.serialize();
+	let signature = TransactionSignature::new(
+		recovery_id.serialize() as u64 + CHAIN_ID * 2 + 35,
+		H256::from_slice(&rs[..32]),
+		H256::from_slice(&rs[32..]),
+	)
+	.expect("signature is valid");
+
+	let transaction = LegacyTransaction {
+		nonce: message.nonce,
+		gas_price: message.gas_price,
+		gas_limit: message.gas_limit,
+		action: message.action,
+		value: message.value,
+		input: message.input,
+		signature,
+	};
+	rlp::encode(&transaction).to_vec()
+}
+
+/// Submits a raw transaction and waits for its receipt.
+async fn submit_and_wait(client: &WsClient, transaction: Vec<u8>) -> Value {
+	let hash: H256 = client
+		.request(
+			"eth_sendRawTransaction",
+			rpc_params![format!("0x{}", hex::encode(transaction))],
+		)
+		.await
+		.expect("transaction is accepted by the pool");
+
+	let started = Instant::now();
+	loop {
+		let receipt: Value =
+			client.request("eth_getTransactionReceipt", rpc_params![hash]).await.unwrap();
+		if !receipt.is_null() {
+			return receipt
+		}
+		assert!(started.elapsed() < TIMEOUT, "transaction {:?} was not included", hash);
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+}
+
+/// Executes a read-only call from Gerald and decodes the returned word.
+async fn call(client: &WsClient, to: &str, data: &str) -> U256 {
+	let output: String = client
+		.request("eth_call", rpc_params![json!({ "from": GERALD, "to": to, "data": data }), "latest"])
+		.await
+		.expect("call succeeds");
+	U256::from_str(output.trim_start_matches("0x")).expect("output is a single word")
+}
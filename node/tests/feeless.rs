@@ -0,0 +1,82 @@
+//! Waives the fees of the calls made feeless by `pallet_feeless` in the development chain spec.
+
+mod common;
+
+use codec::{Decode, Encode};
+use common::{Node, TIMEOUT};
+use jsonrpsee::{core::client::ClientT, rpc_params, ws_client::WsClient};
+use node_template_runtime::{pallet_template, Balance, BalancesCall};
+use sp_core::{twox_128, Bytes};
+use sp_keyring::AccountKeyring;
+use sp_runtime::MultiAddress;
+use std::time::{Duration, Instant};
+
+/// `ExistentialDeposit` of the runtime's balances.
+const EXISTENTIAL_DEPOSIT: Balance = 500;
+
+#[tokio::test]
+async fn accounts_without_funds_call_the_template_pallet_once() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	// Neither Eve nor Ferdie are endowed in the development chain spec, so Eve doesn't exist and
+	// is charged.
+	let call = pallet_template::Call::do_something { something: 41 };
+	let extrinsic = common::sign(&client, AccountKeyring::Eve, call, 0).await;
+	let result: Result<String, _> = client
+		.request(
+			"author_submitExtrinsic",
+			rpc_params![format!("0x{}", hex::encode(extrinsic.encode()))],
+		)
+		.await;
+	assert!(result.is_err());
+
+	// Ferdie holds no more than the existential deposit, and can't pay for anything.
+	let dest = MultiAddress::Id(AccountKeyring::Ferdie.to_account_id());
+	let call = BalancesCall::transfer { dest, value: EXISTENTIAL_DEPOSIT };
+	common::submit(&client, AccountKeyring::Alice, call, 0).await;
+	let call = pallet_template::Call::do_something { something: 42 };
+	let started = Instant::now();
+	loop {
+		let extrinsic = common::sign(&client, AccountKeyring::Ferdie, call.clone(), 0).await;
+		let result: Result<String, _> = client
+			.request(
+				"author_submitExtrinsic",
+				rpc_params![format!("0x{}", hex::encode(extrinsic.encode()))],
+			)
+			.await;
+		// Ferdie is charged until the transfer is included.
+		if result.is_ok() {
+			break
+		}
+		assert!(started.elapsed() < TIMEOUT, "the transfer to Ferdie was not included");
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+
+	let started = Instant::now();
+	while something(&client).await != Some(42) {
+		assert!(started.elapsed() < TIMEOUT, "the feeless call was not included");
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+
+	let call = pallet_template::Call::do_something { something: 43 };
+	let extrinsic = common::sign(&client, AccountKeyring::Ferdie, call, 1).await;
+	let result: Result<String, _> = client
+		.request(
+			"author_submitExtrinsic",
+			rpc_params![format!("0x{}", hex::encode(extrinsic.encode()))],
+		)
+		.await;
+	// Ferdie can't pay for the second call.
+	assert!(result.is_err());
+}
+
+/// Value of `TemplateModule::Something` at the best block.
+async fn something(client: &WsClient) -> Option<u32> {
+	let key = [twox_128(b"TemplateModule"), twox_128(b"Something")].concat();
+	let value: Option<Bytes> = client
+		.request("state_getStorage", rpc_params![Bytes(key)])
+		.await
+		.expect("querying the template pallet storage");
+	value.map(|value| u32::decode(&mut &value[..]).expect("`Something` is a u32"))
+}
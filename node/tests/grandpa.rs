@@ -0,0 +1,34 @@
+//! GRANDPA modes on a local network of two validators and a third node.
+
+mod common;
+
+use common::Node;
+
+#[tokio::test]
+async fn observers_follow_the_finalized_head() {
+	let alice = Node::start(&["--chain", "local", "--alice"]);
+	let bootnode = alice.multiaddr().await;
+	let bob = Node::start(&["--chain", "local", "--bob", "--bootnodes", &bootnode]);
+	let observer =
+		Node::start(&["--chain", "local", "--grandpa-mode", "observer", "--bootnodes", &bootnode]);
+
+	let validator = bob.client().await;
+	common::wait_for(&validator, common::finalized_number, |n| n >= 3).await;
+
+	let observer = observer.client().await;
+	let finalized = common::finalized_number(&validator).await;
+	common::wait_for(&observer, common::finalized_number, |n| n >= finalized).await;
+	common::wait_for(&observer, common::finalized_number, |n| n > finalized).await;
+}
+
+#[tokio::test]
+async fn nodes_without_grandpa_still_import_blocks() {
+	let alice = Node::start(&["--chain", "local", "--alice"]);
+	let bootnode = alice.multiaddr().await;
+	let _bob = Node::start(&["--chain", "local", "--bob", "--bootnodes", &bootnode]);
+	let follower =
+		Node::start(&["--chain", "local", "--grandpa-mode", "off", "--bootnodes", &bootnode]);
+
+	let follower = follower.client().await;
+	common::wait_for(&follower, common::best_number, |n| n >= 3).await;
+}
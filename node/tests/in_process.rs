@@ -0,0 +1,65 @@
+//! Drives a development node running in the test process: submits signed extrinsics to its
+//! transaction pool, and checks the events of the finalized blocks and the RPC responses.
+
+mod common;
+
+use codec::Encode;
+use common::in_process::InProcessNode;
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use node_template_runtime::{pallet_template, Event, Hash, SystemCall};
+use serde_json::Value;
+use sp_blockchain::HeaderBackend;
+use sp_core::{blake2_256, H256};
+use sp_keyring::AccountKeyring;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn finalized_extrinsics_deposit_their_events() {
+	let node = InProcessNode::start(&[]);
+	let alice = AccountKeyring::Alice;
+
+	let remark = b"in-process".to_vec();
+	let call = SystemCall::remark_with_event { remark: remark.clone() };
+	let block = node.submit_and_finalize(node.sign(alice, call, 0)).await;
+	assert!(node.events(block).iter().any(|record| matches!(
+		&record.event,
+		Event::System(frame_system::Event::Remarked { sender, hash })
+			if *sender == alice.to_account_id() && *hash == H256(blake2_256(&remark))
+	)));
+
+	let call = pallet_template::Call::do_something { something: 42 };
+	let block = node.submit_and_finalize(node.sign(alice, call, 1)).await;
+	assert!(node.events(block).iter().any(|record| matches!(
+		&record.event,
+		Event::TemplateModule(pallet_template::Event::SomethingStored(42, who))
+			if *who == alice.to_account_id()
+	)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rpc_serves_the_finalized_extrinsics() {
+	let node = InProcessNode::start(&[]);
+	let rpc = node.rpc().await;
+	let bob = AccountKeyring::Bob;
+
+	let extrinsic = node.sign(bob, SystemCall::remark { remark: vec![1, 2, 3] }, 0);
+	let encoded = format!("0x{}", hex::encode(extrinsic.encode()));
+	let block = node.submit_and_finalize(extrinsic).await;
+
+	let signed_block: Value =
+		rpc.request("chain_getBlock", rpc_params![block]).await.expect("querying the block");
+	let extrinsics = signed_block["block"]["extrinsics"].as_array().expect("the block has a body");
+	assert!(extrinsics.iter().any(|extrinsic| extrinsic.as_str() == Some(&encoded)));
+
+	let finalized: Hash = rpc
+		.request("chain_getFinalizedHead", None)
+		.await
+		.expect("querying the finalized head");
+	let number = |hash| node.client.number(hash).expect("reading the header").expect("known block");
+	assert!(number(finalized) >= number(block));
+
+	let nonce: u32 = rpc
+		.request("system_accountNextIndex", rpc_params![bob.to_account_id()])
+		.await
+		.expect("querying the nonce");
+	assert_eq!(nonce, 1);
+}
@@ -0,0 +1,78 @@
+//! Indexes a development node with `--index-db` and queries it with `index_query`.
+
+mod common;
+
+use common::{Node, TIMEOUT};
+use jsonrpsee::{core::client::ClientT, rpc_params, ws_client::WsClient};
+use node_template_runtime::BalancesCall;
+use serde_json::Value;
+use sp_keyring::AccountKeyring;
+use sp_runtime::{AccountId32, MultiAddress};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn serves_the_finalized_history_of_an_account() {
+	let index_dir = tempfile::tempdir().unwrap();
+	let index_db = index_dir.path().join("index.sqlite");
+	let node = Node::start(&["--dev", "--index-db", index_db.to_str().unwrap()]);
+	let client = node.client().await;
+
+	let bob = AccountKeyring::Bob.to_account_id();
+	let call = BalancesCall::transfer { dest: MultiAddress::Id(bob.clone()), value: 1_000 };
+	common::submit(&client, AccountKeyring::Alice, call, 0).await;
+
+	let transfer = wait_for_transfer(&client, &bob, true).await;
+
+	assert_eq!(transfer["pallet"], "Balances");
+	assert_eq!(transfer["balanceChange"], "+1000");
+	assert_eq!(transfer["extrinsicIndex"], 1);
+	assert_eq!(transfer["signed"], false);
+}
+
+#[tokio::test]
+async fn keeps_best_blocks_finalized_while_the_indexer_was_stopped() {
+	let index_dir = tempfile::tempdir().unwrap();
+	let index_db = index_dir.path().join("index.sqlite");
+	let index_db = index_db.to_str().unwrap();
+
+	// Without GRANDPA, the transfer is only ever indexed as part of the best chain.
+	let mut node = Node::start(&["--dev", "--no-grandpa", "--index-db", index_db]);
+	let client = node.client().await;
+	let bob = AccountKeyring::Bob.to_account_id();
+	let call = BalancesCall::transfer { dest: MultiAddress::Id(bob.clone()), value: 1_000 };
+	common::submit(&client, AccountKeyring::Alice, call, 0).await;
+	wait_for_transfer(&client, &bob, false).await;
+	let best = common::best_number(&client).await;
+
+	// Finalize the blocks while the indexer isn't running.
+	node.restart(&["--dev"]);
+	let client = node.client().await;
+	common::wait_for(&client, common::finalized_number, |n| n >= best).await;
+
+	// Catching up marks the stored blocks as finalized instead of pruning them.
+	node.restart(&["--dev", "--index-db", index_db]);
+	let client = node.client().await;
+	wait_for_transfer(&client, &bob, true).await;
+}
+
+/// Waits until the index holds a transfer to `who` that is finalized or not, and returns it.
+async fn wait_for_transfer(client: &WsClient, who: &AccountId32, finalized: bool) -> Value {
+	let started = Instant::now();
+	loop {
+		let history: Value = client
+			.request("index_query", rpc_params![who.clone(), serde_json::json!({ "limit": 10 })])
+			.await
+			.expect("querying the index");
+		let transfer = history["items"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.find(|item| item["event"] == "Transfer" && item["finalized"] == finalized)
+			.cloned();
+		if let Some(transfer) = transfer {
+			return transfer
+		}
+		assert!(started.elapsed() < TIMEOUT, "the transfer was not indexed: {}", history);
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+}
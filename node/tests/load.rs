@@ -0,0 +1,24 @@
+//! Loads a development node with the `load` subcommand.
+
+mod common;
+
+use common::Node;
+use std::process::Command;
+
+#[tokio::test]
+async fn loads_a_development_node() {
+	let node = Node::start(&["--dev"]);
+	let _client = node.client().await;
+
+	let url = format!("ws://127.0.0.1:{}", node.ws_port);
+	let output = Command::new(env!("CARGO_BIN_EXE_node-template"))
+		.args(["load", "--rpc", &url, "--duration", "3", "--tps", "5", "--accounts", "3"])
+		.output()
+		.expect("running node-template load");
+
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(stdout.contains("rejected by the pool: 0, dropped: 0"), "{}", stdout);
+	assert!(stdout.contains("not included within 60 s: 0"), "{}", stdout);
+	assert!(stdout.contains("Inclusion latency: p50"), "{}", stdout);
+}
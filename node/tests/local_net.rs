@@ -0,0 +1,80 @@
+//! Runs a local network with the `local-net` subcommand.
+
+mod common;
+
+use common::TIMEOUT;
+use jsonrpsee::core::client::ClientT;
+use serde_json::Value;
+use std::{
+	net::TcpStream,
+	process::{Child, Command, Stdio},
+	time::{Duration, Instant},
+};
+
+/// A running `local-net` process, interrupted like with Ctrl-C on drop so that it stops its nodes.
+struct LocalNet(Child);
+
+impl LocalNet {
+	fn interrupt(&self) {
+		let status = Command::new("kill")
+			.args(["-INT", &self.0.id().to_string()])
+			.status()
+			.expect("sending SIGINT");
+		assert!(status.success());
+	}
+}
+
+impl Drop for LocalNet {
+	fn drop(&mut self) {
+		if let Ok(None) = self.0.try_wait() {
+			self.interrupt();
+			let _ = self.0.wait();
+		}
+	}
+}
+
+#[tokio::test]
+async fn validators_and_full_nodes_finalize_and_stop_on_ctrl_c() {
+	let base_path = tempfile::tempdir().expect("creating a temporary base path");
+	// The nth node listens on each of the ports plus n.
+	let nodes = 3;
+	let ports = common::free_ports(4 * nodes);
+	let ws_port = ports;
+	let mut network = LocalNet(
+		Command::new(env!("CARGO_BIN_EXE_node-template"))
+			.args(["local-net", "--validators", "2", "--full-nodes", "1"])
+			.arg("--base-path")
+			.arg(base_path.path())
+			.args(["--port", &(ports + nodes).to_string()])
+			.args(["--rpc-port", &(ports + 2 * nodes).to_string()])
+			.args(["--ws-port", &ws_port.to_string()])
+			.args(["--prometheus-port", &(ports + 3 * nodes).to_string()])
+			.stderr(Stdio::null())
+			.spawn()
+			.expect("spawning node-template local-net"),
+	);
+
+	// The full node comes after the validators.
+	let full_node_port = ws_port + 2;
+	let client = common::connect(full_node_port).await;
+	common::wait_for(&client, common::finalized_number, |n| n >= 2).await;
+	let health: Value = client.request("system_health", None).await.expect("querying the health");
+	assert_eq!(health["peers"], 2);
+	assert!(base_path.path().join("chain-spec.json").exists());
+	assert!(base_path.path().join("alice/chains/local_net/keystore").exists());
+	drop(client);
+
+	network.interrupt();
+	let started = Instant::now();
+	let status = loop {
+		if let Some(status) = network.0.try_wait().expect("waiting for local-net") {
+			break status
+		}
+		assert!(started.elapsed() < TIMEOUT, "local-net did not stop");
+		tokio::time::sleep(Duration::from_millis(250)).await;
+	};
+	assert!(status.success());
+	for port in ws_port..=full_node_port {
+		assert!(TcpStream::connect(("127.0.0.1", port)).is_err(), "a node is still running");
+	}
+}
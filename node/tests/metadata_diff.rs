@@ -0,0 +1,113 @@
+//! Compares the metadata of runtimes with the `metadata-diff` subcommand, and of modified
+//! runtimes with `metadata_diff::diff`.
+
+mod common;
+
+use codec::Encode;
+use common::Node;
+use frame_metadata::{
+	v14::{PalletMetadata, RuntimeMetadataV14},
+	RuntimeMetadata,
+};
+use node_template::{
+	metadata::Metadata,
+	metadata_diff::{check_versions, diff, Change, Severity},
+};
+use node_template_runtime::{Runtime, VERSION, WASM_BINARY};
+use scale_info::form::PortableForm;
+use std::process::Command;
+
+/// The metadata of the runtime, modified by `modify`.
+fn metadata(modify: impl FnOnce(&mut RuntimeMetadataV14)) -> Metadata {
+	let mut prefixed = Runtime::metadata();
+	match &mut prefixed.1 {
+		RuntimeMetadata::V14(metadata) => modify(metadata),
+		_ => panic!("the runtime metadata is V14"),
+	}
+	Metadata::decode(&prefixed.encode()).expect("the metadata is valid")
+}
+
+fn template_module(metadata: &mut RuntimeMetadataV14) -> &mut PalletMetadata<PortableForm> {
+	metadata.pallets.iter_mut().find(|pallet| pallet.name == "TemplateModule").unwrap()
+}
+
+#[test]
+fn moving_a_pallet_breaks_transactions() {
+	let mut index = 0;
+	let old = metadata(|metadata| index = template_module(metadata).index);
+	let new = metadata(|metadata| template_module(metadata).index = 200);
+
+	assert_eq!(
+		diff(&old, &new),
+		vec![Change {
+			severity: Severity::BreaksTransactions,
+			description: format!("pallet TemplateModule moved from index {} to 200", index),
+		}]
+	);
+
+	let mut new_version = VERSION;
+	new_version.spec_version += 1;
+	assert!(check_versions(&diff(&old, &new), &VERSION, &new_version).is_err());
+	new_version.transaction_version += 1;
+	assert_eq!(check_versions(&diff(&old, &new), &VERSION, &new_version), Ok(()));
+}
+
+#[test]
+fn removing_a_storage_entry_is_breaking() {
+	let old = metadata(|_| {});
+	let new = metadata(|metadata| {
+		let storage = template_module(metadata).storage.as_mut().unwrap();
+		storage.entries.retain(|entry| entry.name != "Something");
+	});
+
+	let changes = diff(&old, &new);
+	assert_eq!(
+		changes,
+		vec![Change {
+			severity: Severity::Breaking,
+			description: "storage TemplateModule::Something removed".into(),
+		}]
+	);
+
+	assert!(check_versions(&changes, &VERSION, &VERSION).is_err());
+	let mut new_version = VERSION;
+	new_version.spec_version += 1;
+	assert_eq!(check_versions(&changes, &VERSION, &new_version), Ok(()));
+}
+
+#[test]
+fn adding_a_pallet_is_not_breaking() {
+	let old =
+		metadata(|metadata| metadata.pallets.retain(|pallet| pallet.name != "TemplateModule"));
+	let new = metadata(|_| {});
+
+	assert_eq!(
+		diff(&old, &new),
+		vec![Change {
+			severity: Severity::NonBreaking,
+			description: "pallet TemplateModule added".into(),
+		}]
+	);
+	assert_eq!(diff(&new, &new), vec![]);
+}
+
+#[tokio::test]
+async fn compares_the_runtime_of_a_node_with_a_wasm_blob() {
+	let node = Node::start(&["--dev"]);
+	let _client = node.client().await;
+	let dir = tempfile::tempdir().expect("creating a temporary directory");
+	let wasm = dir.path().join("runtime.wasm");
+	std::fs::write(&wasm, WASM_BINARY.expect("the runtime is built with its Wasm blob"))
+		.expect("writing the Wasm blob");
+
+	let url = format!("ws://127.0.0.1:{}", node.ws_port);
+	let output = Command::new(env!("CARGO_BIN_EXE_node-template"))
+		.args(["metadata-diff", &url])
+		.arg(&wasm)
+		.output()
+		.expect("running node-template metadata-diff");
+
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(stdout.contains("No changes to the metadata"), "{}", stdout);
+}
@@ -0,0 +1,66 @@
+//! Elects the validators by stake with the `npos` feature.
+//!
+//! Requires the `npos` feature: `cargo test -p node-template --features npos --test npos`.
+
+#![cfg(feature = "npos")]
+
+mod common;
+
+use codec::{Decode, Encode};
+use common::Node;
+use jsonrpsee::{core::client::ClientT, rpc_params, ws_client::WsClient};
+use node_template_runtime::AccountId;
+use sp_core::{crypto::Pair, sr25519, twox_128, twox_64, Bytes};
+use sp_keyring::AccountKeyring;
+
+#[tokio::test]
+async fn the_bonded_stash_validates_the_development_chain() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	// Authority set changes would stall finality if the session keys weren't registered.
+	common::wait_for(&client, common::finalized_number, |n| n >= 2).await;
+
+	let stash: AccountId = sr25519::Pair::from_string("//Alice//stash", None)
+		.expect("static values are valid")
+		.public()
+		.into();
+	let validators = storage(&client, storage_key(b"Session", b"Validators"))
+		.await
+		.expect("the validators are set");
+	let validators =
+		Vec::<AccountId>::decode(&mut &validators[..]).expect("decoding the validators");
+	assert_eq!(validators, vec![stash.clone()]);
+
+	// Alice is the controller of her stash.
+	let key = [
+		storage_key(b"Staking", b"Bonded"),
+		twox_64(&stash.encode()).to_vec(),
+		stash.encode(),
+	]
+	.concat();
+	let controller = storage(&client, key).await.expect("the stash is bonded");
+	assert_eq!(
+		AccountId::decode(&mut &controller[..]).expect("decoding the controller"),
+		AccountKeyring::Alice.to_account_id()
+	);
+
+	let key = [
+		storage_key(b"Staking", b"Validators"),
+		twox_64(&stash.encode()).to_vec(),
+		stash.encode(),
+	]
+	.concat();
+	assert!(storage(&client, key).await.is_some(), "the stash is not a validator candidate");
+}
+
+fn storage_key(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+	[twox_128(pallet), twox_128(item)].concat()
+}
+
+async fn storage(client: &WsClient, key: Vec<u8>) -> Option<Bytes> {
+	client
+		.request("state_getStorage", rpc_params![Bytes(key)])
+		.await
+		.expect("querying the storage")
+}
@@ -0,0 +1,46 @@
+//! Mines blocks with proof-of-work instead of authoring them with Aura.
+//!
+//! Requires the `pow` feature: `cargo test -p node-template --features pow --test pow`.
+
+#![cfg(feature = "pow")]
+
+mod common;
+
+use codec::Decode;
+use common::Node;
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use sp_core::{Bytes, U256};
+use std::time::Duration;
+
+#[tokio::test]
+async fn mines_and_finalizes_blocks() {
+	let node = Node::start(&["--dev", "--mining-threads", "2"]);
+	let client = node.client().await;
+
+	common::wait_for(&client, common::finalized_number, |n| n >= 2).await;
+
+	let difficulty: Bytes = client
+		.request("state_call", rpc_params!["DifficultyApi_difficulty", "0x"])
+		.await
+		.expect("calling the difficulty runtime API");
+	let difficulty = U256::decode(&mut &difficulty[..]).expect("difficulty is a U256");
+	assert!(difficulty > U256::zero());
+}
+
+#[tokio::test]
+async fn mines_without_grandpa() {
+	let node = Node::start(&["--dev", "--grandpa-mode", "off"]);
+	let client = node.client().await;
+
+	common::wait_for(&client, common::best_number, |n| n >= 3).await;
+	assert_eq!(common::finalized_number(&client).await, 0);
+}
+
+#[tokio::test]
+async fn does_not_mine_without_threads() {
+	let node = Node::start(&["--dev", "--mining-threads", "0"]);
+	let client = node.client().await;
+
+	tokio::time::sleep(Duration::from_secs(15)).await;
+	assert_eq!(common::best_number(&client).await, 0);
+}
@@ -0,0 +1,94 @@
+//! Prioritizes transactions by the policies of `pallet_priority` on a development node.
+
+mod common;
+
+use codec::{Decode, Encode};
+use common::{Node, TIMEOUT};
+use jsonrpsee::{core::client::ClientT, rpc_params, ws_client::WsClient};
+use node_template_runtime::{
+	apis::EffectivePriority, pallet_priority, pallet_template, Call, Runtime, SudoCall,
+	UncheckedExtrinsic,
+};
+use sp_core::{Bytes, H256};
+use sp_keyring::AccountKeyring;
+use sp_runtime::transaction_validity::{TransactionSource, TransactionValidityError};
+use std::time::{Duration, Instant};
+
+const BOOST: u64 = 1_000;
+const ALLOW_LISTED_BOOST: u64 = 1_000_000_000;
+const LONGEVITY: u64 = 64;
+
+#[tokio::test]
+async fn boosts_the_calls_of_allow_listed_accounts() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let policy = pallet_priority::Policy {
+		boost: BOOST,
+		allow_listed_boost: ALLOW_LISTED_BOOST,
+		longevity: Some(LONGEVITY),
+	};
+	let set_policy = pallet_priority::Call::set_policy {
+		pallet: b"TemplateModule".to_vec().try_into().unwrap(),
+		call: None,
+		policy: Some(policy),
+	};
+	let allow_bob = pallet_priority::Call::set_allow_listed {
+		account: AccountKeyring::Bob.to_account_id(),
+		allowed: true,
+	};
+	common::submit(&client, AccountKeyring::Alice, sudo(set_policy), 0).await;
+	common::submit(&client, AccountKeyring::Alice, sudo(allow_bob), 1).await;
+
+	let call = pallet_template::Call::do_something { something: 7 };
+	let bob = common::sign(&client, AccountKeyring::Bob, call.clone(), 0).await;
+	let charlie = common::sign(&client, AccountKeyring::Charlie, call, 0).await;
+
+	let started = Instant::now();
+	let bob = loop {
+		let priority = effective_priority(&client, &bob).await.expect("the call is valid");
+		if priority.boost > 0 {
+			break priority
+		}
+		assert!(started.elapsed() < TIMEOUT, "the policy was not set");
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	};
+	let charlie = effective_priority(&client, &charlie).await.expect("the call is valid");
+
+	assert_eq!(bob.boost, ALLOW_LISTED_BOOST);
+	assert_eq!(charlie.boost, BOOST);
+	assert!(bob.priority > charlie.priority);
+	assert!(bob.priority >= ALLOW_LISTED_BOOST);
+	assert_eq!(bob.longevity, LONGEVITY);
+}
+
+#[tokio::test]
+async fn leaves_calls_without_policy_alone() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let call = pallet_template::Call::do_something { something: 7 };
+	let tx = common::sign(&client, AccountKeyring::Bob, call, 0).await;
+	let priority = effective_priority(&client, &tx).await.expect("the call is valid");
+
+	assert_eq!(priority.boost, 0);
+	assert!(priority.longevity > LONGEVITY);
+}
+
+fn sudo(call: impl Into<Call>) -> SudoCall<Runtime> {
+	SudoCall::sudo { call: Box::new(call.into()) }
+}
+
+async fn effective_priority(
+	client: &WsClient,
+	tx: &UncheckedExtrinsic,
+) -> Result<EffectivePriority, TransactionValidityError> {
+	let best: H256 =
+		client.request("chain_getBlockHash", None).await.expect("querying the best hash");
+	let args = (TransactionSource::External, tx, best).encode();
+	let result: Bytes = client
+		.request("state_call", rpc_params!["PriorityApi_effective_priority", Bytes(args), best])
+		.await
+		.expect("calling the priority runtime API");
+	Decode::decode(&mut &result[..]).expect("decoding the effective priority")
+}
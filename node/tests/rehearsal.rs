@@ -0,0 +1,108 @@
+//! Rehearses runtime upgrades with the `upgrade-rehearsal` subcommand on the database of a stopped
+//! development node.
+
+mod common;
+
+use codec::Encode;
+use common::Node;
+use node_template_runtime::{VERSION, WASM_BINARY};
+use std::{
+	path::Path,
+	process::{Command, Output},
+};
+
+fn rehearse(base_path: &Path, wasm: &Path) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_node-template"))
+		.args(["upgrade-rehearsal", "--dev", "--blocks", "2"])
+		.arg("--base-path")
+		.arg(base_path)
+		.arg("--wasm")
+		.arg(wasm)
+		.output()
+		.expect("running node-template upgrade-rehearsal")
+}
+
+/// The runtime's Wasm blob, with the version embedded in its `runtime_version` custom section
+/// replaced by `version`. `set_code` reads that section, while the code still runs as `VERSION`.
+fn with_embedded_version(version: &sp_api::RuntimeVersion) -> Vec<u8> {
+	let wasm = WASM_BINARY.expect("the runtime is built with its Wasm blob");
+	let wasm = sp_maybe_compressed_blob::decompress(wasm, 50 * 1024 * 1024)
+		.expect("decompressing the runtime");
+
+	let mut patched = wasm[..8].to_vec();
+	let mut rest = &wasm[8..];
+	while !rest.is_empty() {
+		let id = rest[0];
+		rest = &rest[1..];
+		let len = read_leb128(&mut rest) as usize;
+		let (section, next) = rest.split_at(len);
+		rest = next;
+
+		let mut contents = section.to_vec();
+		if id == 0 {
+			let mut name = section;
+			let name_len = read_leb128(&mut name) as usize;
+			if name.get(..name_len) == Some(&b"runtime_version"[..]) {
+				contents.truncate(section.len() - name.len() + name_len);
+				contents.extend(version.encode());
+			}
+		}
+		patched.push(id);
+		write_leb128(&mut patched, contents.len() as u32);
+		patched.extend(contents);
+	}
+	patched
+}
+
+fn read_leb128(bytes: &mut &[u8]) -> u32 {
+	let mut value = 0;
+	for shift in (0..).step_by(7) {
+		let byte = bytes[0];
+		*bytes = &bytes[1..];
+		value |= u32::from(byte & 0x7f) << shift;
+		if byte & 0x80 == 0 {
+			break
+		}
+	}
+	value
+}
+
+fn write_leb128(bytes: &mut Vec<u8>, mut value: u32) {
+	loop {
+		let byte = (value & 0x7f) as u8;
+		value >>= 7;
+		if value == 0 {
+			return bytes.push(byte)
+		}
+		bytes.push(byte | 0x80);
+	}
+}
+
+#[tokio::test]
+async fn rehearses_upgrades_without_importing_blocks() {
+	let mut node = Node::start(&["--dev"]);
+	let client = node.client().await;
+	common::wait_for(&client, common::best_number, |n| n >= 2).await;
+	node.stop();
+	let dir = tempfile::tempdir().expect("creating a temporary directory");
+
+	let same_version = dir.path().join("same.wasm");
+	std::fs::write(&same_version, with_embedded_version(&VERSION)).expect("writing the runtime");
+	let output = rehearse(node.base_path(), &same_version);
+	assert!(!output.status.success());
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert!(stderr.contains("SpecVersionNeedsToIncrease"), "{}", stderr);
+
+	let mut version = VERSION;
+	version.spec_version += 1;
+	let upgrade = dir.path().join("upgrade.wasm");
+	std::fs::write(&upgrade, with_embedded_version(&version)).expect("writing the runtime");
+	// The database is left as it was, so rehearsals can be repeated.
+	for _ in 0..2 {
+		let output = rehearse(node.base_path(), &upgrade);
+		let stdout = String::from_utf8_lossy(&output.stdout);
+		assert!(output.status.success(), "{}{}", stdout, String::from_utf8_lossy(&output.stderr));
+		assert!(stdout.contains("Storage diff:"), "{}", stdout);
+		assert!(stdout.contains("The invariants of TemplateModule hold"), "{}", stdout);
+	}
+}
@@ -0,0 +1,50 @@
+//! Lists the unique items of accounts with `uniques_ownedItems` on a development node.
+
+mod common;
+
+use common::{Node, TIMEOUT};
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use node_template_runtime::UniquesCall;
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use sp_runtime::MultiAddress;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn lists_the_items_minted_to_an_account() {
+	let node = Node::start(&["--dev"]);
+	let client = node.client().await;
+
+	let alice = AccountKeyring::Alice;
+	let bob = AccountKeyring::Bob.to_account_id();
+	let admin = MultiAddress::Id(alice.to_account_id());
+	let create = UniquesCall::create { collection: 3, admin };
+	common::submit(&client, alice, create, 0).await;
+	for (nonce, item) in [(1, 9), (2, 4)] {
+		let mint = UniquesCall::mint { collection: 3, item, owner: MultiAddress::Id(bob.clone()) };
+		common::submit(&client, alice, mint, nonce).await;
+	}
+
+	let bob = bob.to_ss58check();
+	let expected = json!([{ "collection": 3, "item": 4 }, { "collection": 3, "item": 9 }]);
+	let started = Instant::now();
+	loop {
+		let items: Value = client
+			.request("uniques_ownedItems", rpc_params![&bob])
+			.await
+			.expect("querying Bob's items");
+		if items == expected {
+			break
+		}
+		assert!(started.elapsed() < TIMEOUT, "the items were not minted, Bob owns {}", items);
+		tokio::time::sleep(Duration::from_millis(500)).await;
+	}
+
+	let alice = alice.to_account_id().to_ss58check();
+	let items: Value = client
+		.request("uniques_ownedItems", rpc_params![alice])
+		.await
+		.expect("querying Alice's items");
+	assert_eq!(items, json!([]));
+}
@@ -0,0 +1,118 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Converts native balances to asset balances at rates set by governance, so that transaction fees
+//! can be paid in assets.
+//!
+//! The rate of an asset is the amount of the asset worth one unit of the native balance. Balances
+//! can't be converted to assets without rate.
+pub use pallet::*;
+
+use frame_support::traits::tokens::BalanceConversion;
+use sp_runtime::{FixedPointNumber, FixedPointOperand};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::{
+		traits::{MaybeSerializeDeserialize, Zero},
+		FixedU128,
+	};
+	use sp_std::prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin allowed to set rates.
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Identifier of an asset.
+		type AssetId: Member + Parameter + Copy + MaybeSerializeDeserialize + MaxEncodedLen;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Amount of an asset worth one unit of the native balance.
+	#[pallet::storage]
+	#[pallet::getter(fn rate)]
+	pub type Rates<T: Config> = StorageMap<_, Blake2_128Concat, T::AssetId, FixedU128>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Rates of assets.
+		pub rates: Vec<(T::AssetId, FixedU128)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { rates: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (asset_id, rate) in &self.rates {
+				assert!(!rate.is_zero(), "the rate of an asset must be positive");
+				Rates::<T>::insert(asset_id, rate);
+			}
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The rate of an asset was set, or removed if `rate` is `None`.
+		RateSet { asset_id: T::AssetId, rate: Option<FixedU128> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The asset has no rate.
+		UnknownRate,
+		/// Rates must be positive.
+		ZeroRate,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Sets the rate of `asset_id`, or removes it if `rate` is `None`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_rate(
+			origin: OriginFor<T>,
+			asset_id: T::AssetId,
+			rate: Option<FixedU128>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match rate {
+				Some(rate) if rate.is_zero() => return Err(Error::<T>::ZeroRate.into()),
+				Some(rate) => Rates::<T>::insert(asset_id, rate),
+				None => Rates::<T>::remove(asset_id),
+			}
+			Self::deposit_event(Event::RateSet { asset_id, rate });
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config, B: FixedPointOperand> BalanceConversion<B, T::AssetId, B> for Pallet<T> {
+	type Error = Error<T>;
+
+	/// Converts the native `balance` to the amount of `asset_id` worth as much, rounded down.
+	fn to_asset_balance(balance: B, asset_id: T::AssetId) -> Result<B, Error<T>> {
+		let rate = Rates::<T>::get(asset_id).ok_or(Error::<T>::UnknownRate)?;
+		Ok(rate.saturating_mul_int(balance))
+	}
+}
@@ -0,0 +1,56 @@
+use crate::{mock::*, Error, Event};
+use frame_support::{assert_noop, assert_ok, traits::tokens::BalanceConversion};
+use sp_runtime::{traits::Zero, DispatchError, FixedPointNumber, FixedU128};
+
+fn convert(balance: u128, asset_id: u32) -> Result<u128, Error<Test>> {
+	AssetRate::to_asset_balance(balance, asset_id)
+}
+
+#[test]
+fn converts_at_the_genesis_rate() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(convert(1_000, STABLECOIN), Ok(500));
+		assert_eq!(convert(1_001, STABLECOIN), Ok(500));
+	});
+}
+
+#[test]
+fn does_not_convert_assets_without_rate() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(convert(1_000, POINTS), Err(Error::<Test>::UnknownRate));
+	});
+}
+
+#[test]
+fn sets_and_removes_rates() {
+	new_test_ext().execute_with(|| {
+		let rate = FixedU128::saturating_from_integer(100);
+		assert_ok!(AssetRate::set_rate(Origin::root(), POINTS, Some(rate)));
+		let event = Event::<Test>::RateSet { asset_id: POINTS, rate: Some(rate) };
+		System::assert_last_event(event.into());
+		assert_eq!(convert(1_000, POINTS), Ok(100_000));
+
+		assert_ok!(AssetRate::set_rate(Origin::root(), POINTS, None));
+		assert_eq!(convert(1_000, POINTS), Err(Error::<Test>::UnknownRate));
+	});
+}
+
+#[test]
+fn rejects_zero_rates() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetRate::set_rate(Origin::root(), POINTS, Some(FixedU128::zero())),
+			Error::<Test>::ZeroRate
+		);
+	});
+}
+
+#[test]
+fn only_the_admin_origin_sets_rates() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetRate::set_rate(Origin::signed(1), STABLECOIN, None),
+			DispatchError::BadOrigin
+		);
+	});
+}
@@ -0,0 +1,128 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Adjusts the proof-of-work difficulty so that blocks are mined at a target block time.
+//!
+//! The difficulty is the expected number of hashes needed to mine a block. Every
+//! `AdjustmentWindow` blocks it is scaled by the ratio of the time the window should have taken to
+//! the time it took, by at most a factor of [`MAX_ADJUSTMENT`] and never below `MinDifficulty`.
+pub use pallet::*;
+
+use sp_core::U256;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum factor by which a single adjustment changes the difficulty.
+pub const MAX_ADJUSTMENT: u64 = 4;
+
+/// Scales `difficulty` by `target / actual`, with the ratio limited to [`MAX_ADJUSTMENT`] in
+/// either direction.
+pub fn adjust(difficulty: U256, target: u64, actual: u64) -> U256 {
+	let actual = actual
+		.clamp(target / MAX_ADJUSTMENT, target.saturating_mul(MAX_ADJUSTMENT))
+		.max(1);
+	difficulty.saturating_mul(U256::from(target)) / U256::from(actual)
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_core::U256;
+	use sp_runtime::traits::{SaturatedConversion, Saturating};
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_timestamp::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Time in milliseconds it should take to mine a block.
+		#[pallet::constant]
+		type TargetBlockTime: Get<u64>;
+
+		/// Number of blocks after which the difficulty is adjusted.
+		#[pallet::constant]
+		type AdjustmentWindow: Get<u32>;
+
+		/// Lower bound of the difficulty.
+		#[pallet::constant]
+		type MinDifficulty: Get<U256>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Expected number of hashes needed to mine the next block.
+	#[pallet::storage]
+	#[pallet::getter(fn difficulty)]
+	pub type Difficulty<T> = StorageValue<_, U256, ValueQuery>;
+
+	/// Timestamp at which the current adjustment window started and the number of blocks in it so
+	/// far. Written when each block is built, from the `on_finalize` of the first block after
+	/// genesis on.
+	#[pallet::storage]
+	pub type Window<T: Config> = StorageValue<_, (T::Moment, u32)>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig {
+		/// Difficulty of the first blocks, until the first adjustment.
+		pub initial_difficulty: U256,
+	}
+
+	#[cfg(feature = "std")]
+	impl Default for GenesisConfig {
+		fn default() -> Self {
+			Self { initial_difficulty: U256::from(1_000_000u64) }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			Difficulty::<T>::put(self.initial_difficulty.max(T::MinDifficulty::get()));
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The difficulty was adjusted at the end of a window.
+		DifficultyAdjusted { difficulty: U256 },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+			// Reads the timestamp, the window and the difficulty in `on_finalize`.
+			T::DbWeight::get().reads_writes(3, 2)
+		}
+
+		fn on_finalize(_n: BlockNumberFor<T>) {
+			let now = pallet_timestamp::Pallet::<T>::get();
+			let (start, blocks) = match Window::<T>::get() {
+				Some((start, blocks)) => (start, blocks + 1),
+				None => {
+					Window::<T>::put((now, 0));
+					return
+				},
+			};
+			if blocks < T::AdjustmentWindow::get() {
+				Window::<T>::put((start, blocks));
+				return
+			}
+
+			let target = T::TargetBlockTime::get().saturating_mul(blocks.into());
+			let actual = now.saturating_sub(start).saturated_into::<u64>();
+			let difficulty =
+				super::adjust(Self::difficulty(), target, actual).max(T::MinDifficulty::get());
+
+			Difficulty::<T>::put(difficulty);
+			Window::<T>::put((now, 0));
+			Self::deposit_event(Event::DifficultyAdjusted { difficulty });
+		}
+	}
+}
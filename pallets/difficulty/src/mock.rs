@@ -0,0 +1,100 @@
+use crate as pallet_difficulty;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, GenesisBuild, Hooks},
+};
+use frame_system as system;
+use sp_core::{H256, U256};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// Block time the difficulty is adjusted to, in milliseconds.
+pub const TARGET_BLOCK_TIME: u64 = 6000;
+
+/// Number of blocks in an adjustment window.
+pub const WINDOW: u32 = 10;
+
+/// Difficulty of the genesis block.
+pub const INITIAL_DIFFICULTY: u64 = 1_000_000;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+		Difficulty: pallet_difficulty::{Pallet, Storage, Config, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub MinDifficulty: U256 = U256::from(1_000u64);
+}
+
+impl pallet_difficulty::Config for Test {
+	type Event = Event;
+	type TargetBlockTime = ConstU64<TARGET_BLOCK_TIME>;
+	type AdjustmentWindow = ConstU32<WINDOW>;
+	type MinDifficulty = MinDifficulty;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_difficulty::GenesisConfig { initial_difficulty: U256::from(INITIAL_DIFFICULTY) }
+		.assimilate_storage::<Test>(&mut storage)
+		.unwrap();
+	storage.into()
+}
+
+/// Finalizes `count` blocks mined `block_time` milliseconds apart.
+pub fn mine_blocks(count: u32, block_time: u64) {
+	for _ in 0..count {
+		let number = System::block_number() + 1;
+		System::set_block_number(number);
+		Timestamp::set_timestamp(Timestamp::get() + block_time);
+		Difficulty::on_finalize(number);
+	}
+}
@@ -0,0 +1,54 @@
+use crate::{mock::*, Event};
+use sp_core::U256;
+
+#[test]
+fn starts_with_the_genesis_difficulty() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Difficulty::difficulty(), U256::from(INITIAL_DIFFICULTY));
+	});
+}
+
+#[test]
+fn keeps_the_difficulty_within_a_window() {
+	new_test_ext().execute_with(|| {
+		// The first block starts the window.
+		mine_blocks(WINDOW, TARGET_BLOCK_TIME / 2);
+		assert_eq!(Difficulty::difficulty(), U256::from(INITIAL_DIFFICULTY));
+	});
+}
+
+#[test]
+fn raises_the_difficulty_when_blocks_are_too_fast() {
+	new_test_ext().execute_with(|| {
+		mine_blocks(WINDOW + 1, TARGET_BLOCK_TIME / 2);
+		let difficulty = U256::from(INITIAL_DIFFICULTY * 2);
+		assert_eq!(Difficulty::difficulty(), difficulty);
+		System::assert_last_event(Event::<Test>::DifficultyAdjusted { difficulty }.into());
+	});
+}
+
+#[test]
+fn lowers_the_difficulty_when_blocks_are_too_slow() {
+	new_test_ext().execute_with(|| {
+		mine_blocks(WINDOW + 1, TARGET_BLOCK_TIME * 2);
+		assert_eq!(Difficulty::difficulty(), U256::from(INITIAL_DIFFICULTY / 2));
+	});
+}
+
+#[test]
+fn limits_a_single_adjustment() {
+	new_test_ext().execute_with(|| {
+		mine_blocks(WINDOW + 1, 1);
+		assert_eq!(Difficulty::difficulty(), U256::from(INITIAL_DIFFICULTY * 4));
+	});
+}
+
+#[test]
+fn never_drops_below_the_minimum() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..10 {
+			mine_blocks(WINDOW, TARGET_BLOCK_TIME * 10);
+		}
+		assert_eq!(Difficulty::difficulty(), MinDifficulty::get());
+	});
+}
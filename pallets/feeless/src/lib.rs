@@ -0,0 +1,489 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Waives the transaction fees of calls configured on chain, so that accounts without the funds
+//! to pay for them can make them.
+//!
+//! A call is free for an account if it has a [`FeelessCall`] policy and the account is either
+//! whitelisted for it, or exists and has made it less than `free_calls` times. Every account makes
+//! at most `rate_limit` free calls of it within `RateLimitPeriod` blocks. All other calls are
+//! charged by the fee extension wrapped in [`CheckFeeless`].
+//!
+//! Only existing accounts, which hold a deposit or are provided for otherwise, make free calls
+//! without being whitelisted, and their [`Usages`] are removed when they are killed. A block holds
+//! at most `MaxFreeCallsPerBlock` free calls of accounts that aren't whitelisted for them. Further
+//! calls that would be free are charged, or wait for the next block if their signer can't pay.
+pub use pallet::*;
+
+use codec::{Compact, Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	traits::{tokens::fungibles, GetCallMetadata, OnKilledAccount},
+	weights::DispatchInfo,
+};
+use pallet_asset_tx_payment::ChargeAssetTxPayment;
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult, RuntimeDebug,
+};
+use sp_std::{fmt, marker::PhantomData};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Which accounts make a call for free, and how often.
+#[derive(
+	Clone, Copy, PartialEq, Eq, Default, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct FeelessCall {
+	/// Number of times every account makes the call for free. Whitelisted accounts aren't
+	/// limited.
+	pub free_calls: u32,
+	/// Maximum number of free calls per account within `RateLimitPeriod` blocks.
+	pub rate_limit: u32,
+}
+
+/// How often an account made a call for free.
+#[derive(
+	Clone, Copy, PartialEq, Eq, Default, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo,
+)]
+pub struct Usage<BlockNumber> {
+	/// Number of free calls in total.
+	pub total: u32,
+	/// Block at which the current rate limit period started.
+	pub period_start: BlockNumber,
+	/// Number of free calls in the current rate limit period.
+	pub in_period: u32,
+}
+
+/// Whether the fee of a call is waived for its signer.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Waiver {
+	/// The fee is waived.
+	Free,
+	/// The fee is charged.
+	Charged,
+	/// The fee would be waived, but the block holds `MaxFreeCallsPerBlock` free calls already. It
+	/// is charged if the signer can pay it.
+	BlockFull,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{FeelessCall, Usage, Waiver};
+	use frame_support::{pallet_prelude::*, traits::CallMetadata, weights::Weight};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::Saturating;
+	use sp_std::prelude::*;
+
+	/// Name of a pallet or call, as in the runtime metadata.
+	pub type Name<T> = BoundedVec<u8, <T as Config>::MaxNameLen>;
+
+	/// Names of a pallet and one of its calls.
+	pub type CallKey<T> = (Name<T>, Name<T>);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin allowed to change the feeless calls and the whitelist.
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Maximum length of pallet and call names.
+		#[pallet::constant]
+		type MaxNameLen: Get<u32>;
+
+		/// Number of blocks the `rate_limit` of feeless calls applies to.
+		#[pallet::constant]
+		type RateLimitPeriod: Get<Self::BlockNumber>;
+
+		/// Maximum number of free calls per block of accounts that aren't whitelisted for them.
+		#[pallet::constant]
+		type MaxFreeCallsPerBlock: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Calls that may be made for free, by pallet and call name.
+	#[pallet::storage]
+	pub type Calls<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Name<T>, Blake2_128Concat, Name<T>, FeelessCall>;
+
+	/// Accounts making calls for free regardless of their `free_calls`.
+	#[pallet::storage]
+	pub type Whitelist<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, CallKey<T>, ()>;
+
+	/// Free calls made by accounts, removed when the account is killed.
+	#[pallet::storage]
+	pub type Usages<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		CallKey<T>,
+		Usage<T::BlockNumber>,
+		ValueQuery,
+	>;
+
+	/// Block of the last free call of an account that isn't whitelisted for it, and the number of
+	/// such calls in that block.
+	#[pallet::storage]
+	pub type BlockFreeCalls<T: Config> = StorageValue<_, (T::BlockNumber, u32), ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Feeless calls by pallet and call name.
+		pub calls: Vec<(Vec<u8>, Vec<u8>, FeelessCall)>,
+		/// Whitelisted accounts with the pallet and call name they are whitelisted for.
+		pub whitelist: Vec<(T::AccountId, Vec<u8>, Vec<u8>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { calls: Vec::new(), whitelist: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let name = |name: &Vec<u8>| -> Name<T> {
+				name.clone().try_into().expect("pallet and call names fit `MaxNameLen`")
+			};
+			for (pallet, call, policy) in &self.calls {
+				Calls::<T>::insert(name(pallet), name(call), policy);
+			}
+			for (account, pallet, call) in &self.whitelist {
+				Whitelist::<T>::insert(account, (name(pallet), name(call)), ());
+			}
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A call was made feeless, or isn't anymore if `policy` is `None`.
+		CallPolicySet { pallet: Name<T>, call: Name<T>, policy: Option<FeelessCall> },
+		/// An account was added to or removed from the whitelist of a call.
+		WhitelistUpdated {
+			account: T::AccountId,
+			pallet: Name<T>,
+			call: Name<T>,
+			whitelisted: bool,
+		},
+		/// The fee of a call was waived.
+		FeeWaived { account: T::AccountId, pallet: Name<T>, call: Name<T> },
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Makes `call` of `pallet` feeless by `policy`, or charges it again if `policy` is
+		/// `None`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_call_policy(
+			origin: OriginFor<T>,
+			pallet: Name<T>,
+			call: Name<T>,
+			policy: Option<FeelessCall>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match policy {
+				Some(policy) => Calls::<T>::insert(&pallet, &call, policy),
+				None => Calls::<T>::remove(&pallet, &call),
+			}
+			Self::deposit_event(Event::CallPolicySet { pallet, call, policy });
+			Ok(())
+		}
+
+		/// Adds `account` to the whitelist of `call` of `pallet`, or removes it if `whitelisted`
+		/// is false. The usage of an account that doesn't exist is removed with it.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn set_whitelisted(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			pallet: Name<T>,
+			call: Name<T>,
+			whitelisted: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			let key = (pallet.clone(), call.clone());
+			if whitelisted {
+				Whitelist::<T>::insert(&account, key, ());
+			} else {
+				// Nothing else removes it, since the account is never killed.
+				if !Self::account_exists(&account) {
+					Usages::<T>::remove(&account, &key);
+				}
+				Whitelist::<T>::remove(&account, key);
+			}
+			Self::deposit_event(Event::WhitelistUpdated { account, pallet, call, whitelisted });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Returns the key of the call described by `metadata`, unless its names are too long to
+		/// be stored.
+		pub fn call_key(metadata: &CallMetadata) -> Option<CallKey<T>> {
+			let pallet = metadata.pallet_name.as_bytes().to_vec().try_into().ok()?;
+			let call = metadata.function_name.as_bytes().to_vec().try_into().ok()?;
+			Some((pallet, call))
+		}
+
+		/// Returns the usage of `key` by `account` after one more free call and whether the
+		/// account is whitelisted for it, or `None` if the call isn't free for the account.
+		pub fn free_usage(
+			account: &T::AccountId,
+			key: &CallKey<T>,
+		) -> Option<(Usage<T::BlockNumber>, bool)> {
+			let policy = Calls::<T>::get(&key.0, &key.1)?;
+			let mut usage = Usages::<T>::get(account, key);
+			let whitelisted = Whitelist::<T>::contains_key(account, key);
+			if !whitelisted && (usage.total >= policy.free_calls || !Self::account_exists(account))
+			{
+				return None
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			if now.saturating_sub(usage.period_start) >= T::RateLimitPeriod::get() {
+				usage.period_start = now;
+				usage.in_period = 0;
+			}
+			if usage.in_period >= policy.rate_limit {
+				return None
+			}
+
+			usage.total = usage.total.saturating_add(1);
+			usage.in_period += 1;
+			Some((usage, whitelisted))
+		}
+
+		/// Whether `account` is provided for, e.g. by a balance above the existential deposit.
+		/// The nonce of a signer is stored before its fee is checked, which doesn't make it exist.
+		pub fn account_exists(account: &T::AccountId) -> bool {
+			frame_system::Pallet::<T>::providers(account) > 0 ||
+				frame_system::Pallet::<T>::sufficients(account) > 0
+		}
+
+		/// Number of free calls of accounts that aren't whitelisted for them in the current block.
+		pub fn free_calls_in_block() -> u32 {
+			let (block, calls) = BlockFreeCalls::<T>::get();
+			if block == frame_system::Pallet::<T>::block_number() {
+				calls
+			} else {
+				0
+			}
+		}
+
+		/// Returns the waiver of the call described by `metadata` for `account`, with the key of
+		/// the call and the usage of the account after one more free call if it is free.
+		fn free_call(
+			account: &T::AccountId,
+			metadata: &CallMetadata,
+		) -> (Waiver, Option<(CallKey<T>, Usage<T::BlockNumber>, bool)>) {
+			let key = match Self::call_key(metadata) {
+				Some(key) => key,
+				None => return (Waiver::Charged, None),
+			};
+			let (usage, whitelisted) = match Self::free_usage(account, &key) {
+				Some(free) => free,
+				None => return (Waiver::Charged, None),
+			};
+			if !whitelisted && Self::free_calls_in_block() >= T::MaxFreeCallsPerBlock::get() {
+				return (Waiver::BlockFull, None)
+			}
+			(Waiver::Free, Some((key, usage, whitelisted)))
+		}
+
+		/// Whether the fee of the call described by `metadata` is waived for `account`.
+		pub fn waiver(account: &T::AccountId, metadata: &CallMetadata) -> Waiver {
+			Self::free_call(account, metadata).0
+		}
+
+		/// Returns whether the fee of the call described by `metadata` is waived for `account`,
+		/// and records the free call if it is.
+		pub fn use_free_call(account: &T::AccountId, metadata: &CallMetadata) -> Waiver {
+			let (key, usage, whitelisted) = match Self::free_call(account, metadata) {
+				(_, Some(free)) => free,
+				(waiver, None) => return waiver,
+			};
+
+			Usages::<T>::insert(account, &key, usage);
+			if !whitelisted {
+				let now = frame_system::Pallet::<T>::block_number();
+				BlockFreeCalls::<T>::put((now, Self::free_calls_in_block() + 1));
+			}
+			let (pallet, call) = key;
+			Self::deposit_event(Event::FeeWaived { account: account.clone(), pallet, call });
+			Waiver::Free
+		}
+
+		/// Weight of the storage accesses of [`Self::use_free_call`], at most: it reads the
+		/// policy, usage, whitelist entry and account, and the free calls of the block, and
+		/// writes the usage and the free calls of the block.
+		pub fn use_free_call_weight() -> Weight {
+			T::DbWeight::get().reads_writes(5, 2)
+		}
+	}
+}
+
+impl<T: Config> OnKilledAccount<T::AccountId> for Pallet<T> {
+	fn on_killed_account(who: &T::AccountId) {
+		// An account has a usage per feeless call at most, kept as long as its deposit is.
+		let _ = Usages::<T>::clear_prefix(who, u32::MAX, None);
+	}
+}
+
+/// Wraps the fee extension `S`, skipping it for calls that are free by the policies of this
+/// pallet.
+///
+/// Encodes like `S` and keeps its identifier, so wallets treat it like the wrapped extension.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckFeeless<T: Config + Send + Sync, S>(pub S, PhantomData<T>);
+
+impl<T: Config + Send + Sync, S> From<S> for CheckFeeless<T, S> {
+	fn from(inner: S) -> Self {
+		Self(inner, PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync, S: FeeAsset> CheckFeeless<T, S> {
+	/// The asset the wrapped extension charges the fee in, or `None` for the native currency.
+	pub fn fee_asset(&self) -> Option<S::AssetId> {
+		self.0.fee_asset()
+	}
+}
+
+/// A fee extension that may charge the fee in an asset rather than the native currency.
+pub trait FeeAsset {
+	/// Identifier of the assets.
+	type AssetId;
+
+	/// The asset the fee is charged in, or `None` for the native currency.
+	fn fee_asset(&self) -> Option<Self::AssetId>;
+}
+
+impl<T> FeeAsset for ChargeAssetTxPayment<T>
+where
+	T: pallet_asset_tx_payment::Config,
+	Self: Encode,
+{
+	type AssetId = <T::Fungibles as fungibles::Inspect<T::AccountId>>::AssetId;
+
+	fn fee_asset(&self) -> Option<Self::AssetId> {
+		// The fields are private, but the extension encodes as its compact tip and asset id.
+		let (_tip, asset_id) =
+			<(Compact<u128>, Option<Self::AssetId>)>::decode(&mut &self.encode()[..]).ok()?;
+		asset_id
+	}
+}
+
+impl<T: Config + Send + Sync, S: fmt::Debug> fmt::Debug for CheckFeeless<T, S> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckFeeless({:?})", self.0)
+	}
+}
+
+impl<T: Config + Send + Sync, S> SignedExtension for CheckFeeless<T, S>
+where
+	S: SignedExtension<AccountId = T::AccountId, Call = <T as frame_system::Config>::Call>,
+	<T as frame_system::Config>::Call: GetCallMetadata + Dispatchable<Info = DispatchInfo>,
+{
+	const IDENTIFIER: &'static str = S::IDENTIFIER;
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = S::AdditionalSigned;
+	/// `None` if the fee was waived.
+	type Pre = Option<S::Pre>;
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		self.0.additional_signed()
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		match Pallet::<T>::waiver(who, &call.get_call_metadata()) {
+			Waiver::Free => Ok(ValidTransaction::default()),
+			Waiver::Charged => self.0.validate(who, call, info, len),
+			// Signers who can't pay wait for the free calls of the next block.
+			Waiver::BlockFull => self
+				.0
+				.validate(who, call, info, len)
+				.map_err(|_| InvalidTransaction::ExhaustsResources.into()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		let waiver = Pallet::<T>::use_free_call(who, &call.get_call_metadata());
+		frame_system::Pallet::<T>::register_extra_weight_unchecked(
+			Pallet::<T>::use_free_call_weight(),
+			info.class,
+		);
+		match waiver {
+			Waiver::Free => Ok(None),
+			Waiver::Charged => self.0.pre_dispatch(who, call, info, len).map(Some),
+			Waiver::BlockFull => self
+				.0
+				.pre_dispatch(who, call, info, len)
+				.map(Some)
+				.map_err(|_| InvalidTransaction::ExhaustsResources.into()),
+		}
+	}
+
+	fn validate_unsigned(
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		S::validate_unsigned(call, info, len)
+	}
+
+	fn pre_dispatch_unsigned(
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<(), TransactionValidityError> {
+		S::pre_dispatch_unsigned(call, info, len)
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match pre {
+			// The fee was waived, there is nothing to refund.
+			Some(None) => Ok(()),
+			pre => S::post_dispatch(pre.flatten(), info, post_info, len, result),
+		}
+	}
+}
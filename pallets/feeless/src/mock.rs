@@ -0,0 +1,147 @@
+use crate as pallet_feeless;
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{ConstU16, ConstU32, ConstU64, GenesisBuild},
+	weights::constants::RocksDbWeight,
+};
+use frame_system as system;
+use scale_info::TypeInfo;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, DispatchInfoOf, IdentityLookup, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// An account that pays fees.
+pub const FUNDED: u64 = 1;
+
+/// An account that can't pay fees.
+pub const BROKE: u64 = 2;
+
+/// An account that doesn't exist, and can't pay fees either.
+pub const NEW: u64 = 3;
+
+/// Number of blocks the rate limit applies to.
+pub const PERIOD: u64 = 10;
+
+/// Maximum number of free calls per block of accounts that aren't whitelisted.
+pub const MAX_FREE_CALLS_PER_BLOCK: u32 = 5;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Feeless: pallet_feeless::{Pallet, Call, Storage, Config<T>, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = RocksDbWeight;
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = Feeless;
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_feeless::Config for Test {
+	type Event = Event;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type MaxNameLen = ConstU32<32>;
+	type RateLimitPeriod = ConstU64<PERIOD>;
+	type MaxFreeCallsPerBlock = ConstU32<MAX_FREE_CALLS_PER_BLOCK>;
+}
+
+/// Stands in for `ChargeAssetTxPayment`: only `FUNDED` pays its fees.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug, TypeInfo)]
+pub struct ChargeFee;
+
+impl SignedExtension for ChargeFee {
+	const IDENTIFIER: &'static str = "ChargeFee";
+	type AccountId = u64;
+	type Call = Call;
+	type AdditionalSigned = ();
+	/// The account charged.
+	type Pre = u64;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &u64,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if *who != FUNDED {
+			return Err(InvalidTransaction::Payment.into())
+		}
+		Ok(ValidTransaction { priority: 1, ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &u64,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<u64, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| *who)
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_feeless::GenesisConfig::<Test>::default()
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let mut ext: sp_io::TestExternalities = storage.into();
+	ext.execute_with(|| {
+		// Events aren't deposited in the genesis block.
+		System::set_block_number(1);
+		create_account(FUNDED);
+		create_account(BROKE);
+	});
+	ext
+}
+
+/// Makes `account` exist, as a balance of the existential deposit would.
+pub fn create_account(account: u64) {
+	System::inc_providers(&account);
+}
+
+/// Converts `name` to a pallet or call name.
+pub fn name(name: &str) -> pallet_feeless::Name<Test> {
+	name.as_bytes().to_vec().try_into().unwrap()
+}
@@ -0,0 +1,222 @@
+use crate::{mock::*, CheckFeeless, Event, FeelessCall, Usages};
+use frame_support::{assert_noop, assert_ok, weights::DispatchInfo};
+use sp_runtime::{
+	traits::SignedExtension,
+	transaction_validity::{InvalidTransaction, TransactionValidityError, ValidTransaction},
+	DispatchError,
+};
+
+const PAYMENT: TransactionValidityError =
+	TransactionValidityError::Invalid(InvalidTransaction::Payment);
+
+const EXHAUSTED: TransactionValidityError =
+	TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources);
+
+fn remark() -> Call {
+	frame_system::Call::remark { remark: Vec::new() }.into()
+}
+
+fn extension() -> CheckFeeless<Test, ChargeFee> {
+	ChargeFee.into()
+}
+
+fn validate(who: u64) -> Result<ValidTransaction, TransactionValidityError> {
+	extension().validate(&who, &remark(), &DispatchInfo::default(), 0)
+}
+
+fn pre_dispatch(who: u64) -> Result<Option<u64>, TransactionValidityError> {
+	extension().pre_dispatch(&who, &remark(), &DispatchInfo::default(), 0)
+}
+
+fn make_remark_feeless(free_calls: u32, rate_limit: u32) {
+	let policy = Some(FeelessCall { free_calls, rate_limit });
+	assert_ok!(Feeless::set_call_policy(Origin::root(), name("System"), name("remark"), policy));
+}
+
+#[test]
+fn charges_calls_without_policy() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(validate(FUNDED).unwrap().priority, 1);
+		assert_eq!(pre_dispatch(FUNDED), Ok(Some(FUNDED)));
+		assert_eq!(validate(BROKE), Err(PAYMENT));
+		assert_eq!(pre_dispatch(BROKE), Err(PAYMENT));
+	});
+}
+
+#[test]
+fn waives_the_first_calls_of_every_account() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(2, 10);
+
+		assert_eq!(validate(BROKE), Ok(ValidTransaction::default()));
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+		let (pallet, call) = (name("System"), name("remark"));
+		System::assert_last_event(Event::<Test>::FeeWaived { account: BROKE, pallet, call }.into());
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+		assert_eq!(validate(BROKE), Err(PAYMENT));
+		assert_eq!(pre_dispatch(BROKE), Err(PAYMENT));
+
+		// Funded accounts get their free calls too, and pay afterwards.
+		assert_eq!(pre_dispatch(FUNDED), Ok(None));
+		assert_eq!(pre_dispatch(FUNDED), Ok(None));
+		assert_eq!(pre_dispatch(FUNDED), Ok(Some(FUNDED)));
+	});
+}
+
+#[test]
+fn validating_does_not_use_free_calls() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(1, 1);
+
+		for _ in 0..3 {
+			assert_eq!(validate(BROKE), Ok(ValidTransaction::default()));
+		}
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+	});
+}
+
+#[test]
+fn rate_limits_whitelisted_accounts() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(0, 2);
+		assert_eq!(pre_dispatch(BROKE), Err(PAYMENT));
+
+		assert_ok!(Feeless::set_whitelisted(
+			Origin::root(),
+			BROKE,
+			name("System"),
+			name("remark"),
+			true
+		));
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+		assert_eq!(pre_dispatch(BROKE), Err(PAYMENT));
+
+		System::set_block_number(1 + PERIOD);
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+	});
+}
+
+#[test]
+fn limits_the_free_calls_of_a_block() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(1, 1);
+
+		// Every account makes a free call, but only so many fit in a block.
+		let accounts = 100..100 + u64::from(MAX_FREE_CALLS_PER_BLOCK);
+		for account in accounts.clone() {
+			create_account(account);
+			assert_eq!(pre_dispatch(account), Ok(None));
+		}
+		// Accounts that can pay are charged, the others wait for the next block.
+		assert_eq!(validate(FUNDED).unwrap().priority, 1);
+		assert_eq!(pre_dispatch(FUNDED), Ok(Some(FUNDED)));
+		assert_eq!(validate(BROKE), Err(EXHAUSTED));
+		assert_eq!(pre_dispatch(BROKE), Err(EXHAUSTED));
+		// Accounts that already used their free calls are charged as usual.
+		assert_eq!(pre_dispatch(accounts.start), Err(PAYMENT));
+
+		// Whitelisted accounts don't count towards the limit.
+		assert_ok!(Feeless::set_whitelisted(
+			Origin::root(),
+			FUNDED,
+			name("System"),
+			name("remark"),
+			true
+		));
+		assert_eq!(pre_dispatch(FUNDED), Ok(None));
+
+		System::set_block_number(2);
+		assert_eq!(validate(BROKE), Ok(ValidTransaction::default()));
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+	});
+}
+
+#[test]
+fn only_existing_or_whitelisted_accounts_make_free_calls() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(1, 1);
+		assert_eq!(validate(NEW), Err(PAYMENT));
+		assert_eq!(pre_dispatch(NEW), Err(PAYMENT));
+
+		let key = (name("System"), name("remark"));
+		let whitelist = |whitelisted| {
+			assert_ok!(Feeless::set_whitelisted(
+				Origin::root(),
+				NEW,
+				key.0.clone(),
+				key.1.clone(),
+				whitelisted
+			));
+		};
+		whitelist(true);
+		assert_eq!(pre_dispatch(NEW), Ok(None));
+		assert!(Usages::<Test>::contains_key(NEW, &key));
+
+		// The usage of an account that doesn't exist goes with its whitelisting.
+		whitelist(false);
+		assert!(!Usages::<Test>::contains_key(NEW, &key));
+	});
+}
+
+#[test]
+fn removes_the_usages_of_killed_accounts() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(1, 1);
+		assert_eq!(pre_dispatch(BROKE), Ok(None));
+		let key = (name("System"), name("remark"));
+		assert!(Usages::<Test>::contains_key(BROKE, &key));
+
+		assert_ok!(System::dec_providers(&BROKE));
+		assert!(!Usages::<Test>::contains_key(BROKE, &key));
+	});
+}
+
+#[test]
+fn weighs_its_storage_accesses() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(1, 1);
+
+		for who in [BROKE, FUNDED, NEW] {
+			let before = System::block_weight().total();
+			let _ = pre_dispatch(who);
+			assert_eq!(System::block_weight().total(), before + Feeless::use_free_call_weight());
+		}
+	});
+}
+
+#[test]
+fn charges_calls_again_once_their_policy_is_removed() {
+	new_test_ext().execute_with(|| {
+		make_remark_feeless(5, 5);
+		assert_ok!(Feeless::set_call_policy(Origin::root(), name("System"), name("remark"), None));
+
+		assert_eq!(pre_dispatch(BROKE), Err(PAYMENT));
+	});
+}
+
+#[test]
+fn only_the_admin_origin_changes_policies() {
+	new_test_ext().execute_with(|| {
+		let policy = FeelessCall { free_calls: 1, rate_limit: 1 };
+		assert_noop!(
+			Feeless::set_call_policy(
+				Origin::signed(BROKE),
+				name("System"),
+				name("remark"),
+				Some(policy)
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Feeless::set_whitelisted(
+				Origin::signed(BROKE),
+				BROKE,
+				name("System"),
+				name("remark"),
+				true
+			),
+			DispatchError::BadOrigin
+		);
+	});
+}
@@ -0,0 +1,262 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Adjusts the transaction pool priority and longevity of calls by a policy kept in storage.
+//!
+//! A [`Policy`] is set for a whole pallet or for a single call of it, the latter taking
+//! precedence, and raises the priority of matching transactions by its `boost`. Transactions of
+//! allow-listed accounts get the policy's `allow_listed_boost` instead, so calls of service
+//! accounts can be kept ahead of the fee market. The policy is applied by the
+//! [`PrioritizeCalls`] signed extension when the transaction pool validates transactions.
+pub use pallet::*;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::traits::GetCallMetadata;
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		TransactionLongevity, TransactionPriority, TransactionValidity, TransactionValidityError,
+	},
+	RuntimeDebug,
+};
+use sp_std::{fmt, marker::PhantomData};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// How transactions calling a pallet or call are prioritized.
+#[derive(
+	Clone, Copy, PartialEq, Eq, Default, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Policy {
+	/// Added to the priority of matching transactions.
+	pub boost: TransactionPriority,
+	/// Added instead of `boost` to the priority of matching transactions of allow-listed
+	/// accounts.
+	pub allow_listed_boost: TransactionPriority,
+	/// Number of blocks matching transactions stay valid for at most, regardless of their era.
+	pub longevity: Option<TransactionLongevity>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::Policy;
+	use frame_support::{pallet_prelude::*, traits::CallMetadata};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::transaction_validity::{TransactionLongevity, ValidTransaction};
+	use sp_std::prelude::*;
+
+	/// Name of a pallet or call, as in the runtime metadata.
+	pub type Name<T> = BoundedVec<u8, <T as Config>::MaxNameLen>;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Origin allowed to change policies and the allow list.
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Maximum length of pallet and call names.
+		#[pallet::constant]
+		type MaxNameLen: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Policies of whole pallets.
+	#[pallet::storage]
+	pub type PalletPolicies<T: Config> = StorageMap<_, Blake2_128Concat, Name<T>, Policy>;
+
+	/// Policies of single calls, by pallet and call name.
+	#[pallet::storage]
+	pub type CallPolicies<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Name<T>, Blake2_128Concat, Name<T>, Policy>;
+
+	/// Accounts getting the `allow_listed_boost` of policies.
+	#[pallet::storage]
+	pub type AllowList<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Policies by pallet name and, unless they apply to the whole pallet, call name.
+		pub policies: Vec<(Vec<u8>, Option<Vec<u8>>, Policy)>,
+		/// Allow-listed accounts.
+		pub allow_list: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { policies: Vec::new(), allow_list: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let name = |name: &Vec<u8>| -> Name<T> {
+				name.clone().try_into().expect("pallet and call names fit `MaxNameLen`")
+			};
+			for (pallet, call, policy) in &self.policies {
+				match call {
+					Some(call) => CallPolicies::<T>::insert(name(pallet), name(call), policy),
+					None => PalletPolicies::<T>::insert(name(pallet), policy),
+				}
+			}
+			for account in &self.allow_list {
+				AllowList::<T>::insert(account, ());
+			}
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The policy of a pallet, or of a call if `call` is set, was set or removed.
+		PolicySet { pallet: Name<T>, call: Option<Name<T>>, policy: Option<Policy> },
+		/// An account was added to or removed from the allow list.
+		AllowListUpdated { account: T::AccountId, allowed: bool },
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Sets the policy of `pallet`, or of its `call`, or removes it if `policy` is `None`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_policy(
+			origin: OriginFor<T>,
+			pallet: Name<T>,
+			call: Option<Name<T>>,
+			policy: Option<Policy>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			match (&call, policy) {
+				(Some(call), Some(policy)) => CallPolicies::<T>::insert(&pallet, call, policy),
+				(Some(call), None) => CallPolicies::<T>::remove(&pallet, call),
+				(None, Some(policy)) => PalletPolicies::<T>::insert(&pallet, policy),
+				(None, None) => PalletPolicies::<T>::remove(&pallet),
+			}
+			Self::deposit_event(Event::PolicySet { pallet, call, policy });
+			Ok(())
+		}
+
+		/// Adds `account` to the allow list, or removes it if `allowed` is false.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_allow_listed(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			allowed: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			if allowed {
+				AllowList::<T>::insert(&account, ());
+			} else {
+				AllowList::<T>::remove(&account);
+			}
+			Self::deposit_event(Event::AllowListUpdated { account, allowed });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Returns the policy of the call described by `metadata`, falling back to the one of its
+		/// pallet.
+		pub fn policy(metadata: &CallMetadata) -> Option<Policy> {
+			let pallet = Name::<T>::try_from(metadata.pallet_name.as_bytes().to_vec()).ok()?;
+			let call = Name::<T>::try_from(metadata.function_name.as_bytes().to_vec()).ok();
+			call.and_then(|call| CallPolicies::<T>::get(&pallet, call))
+				.or_else(|| PalletPolicies::<T>::get(&pallet))
+		}
+
+		/// Returns the adjustment of the validity of a transaction of `account` calling the call
+		/// described by `metadata`, to be combined with the validity reported by the other checks.
+		pub fn adjustment(account: &T::AccountId, metadata: &CallMetadata) -> ValidTransaction {
+			let policy = match Self::policy(metadata) {
+				Some(policy) => policy,
+				None => return ValidTransaction::default(),
+			};
+			let priority = if AllowList::<T>::contains_key(account) {
+				policy.allow_listed_boost
+			} else {
+				policy.boost
+			};
+			ValidTransaction {
+				priority,
+				longevity: policy.longevity.unwrap_or(TransactionLongevity::MAX),
+				..Default::default()
+			}
+		}
+	}
+}
+
+/// Applies the [`Policy`] of the call to the validity of signed transactions.
+///
+/// Carries no data and adds nothing to the signed payload.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct PrioritizeCalls<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> PrioritizeCalls<T> {
+	/// Creates the extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for PrioritizeCalls<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for PrioritizeCalls<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "PrioritizeCalls")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for PrioritizeCalls<T>
+where
+	<T as frame_system::Config>::Call: GetCallMetadata,
+{
+	const IDENTIFIER: &'static str = "PrioritizeCalls";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		Ok(Pallet::<T>::adjustment(who, &call.get_call_metadata()))
+	}
+
+	fn pre_dispatch(
+		self,
+		_who: &Self::AccountId,
+		_call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		// The priority only matters to the transaction pool.
+		Ok(())
+	}
+}
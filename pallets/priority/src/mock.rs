@@ -0,0 +1,79 @@
+use crate as pallet_priority;
+use frame_support::traits::{ConstU16, ConstU32, ConstU64, GenesisBuild};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// An allow-listed account.
+pub const SERVICE: u64 = 1;
+
+/// An account that isn't allow-listed.
+pub const USER: u64 = 2;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Priority: pallet_priority::{Pallet, Call, Storage, Config<T>, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_priority::Config for Test {
+	type Event = Event;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type MaxNameLen = ConstU32<32>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_priority::GenesisConfig::<Test> { policies: Vec::new(), allow_list: vec![SERVICE] }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let mut ext: sp_io::TestExternalities = storage.into();
+	// Events aren't deposited in the genesis block.
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Converts `name` to a pallet or call name.
+pub fn name(name: &str) -> pallet_priority::Name<Test> {
+	name.as_bytes().to_vec().try_into().unwrap()
+}
@@ -0,0 +1,110 @@
+use crate::{mock::*, Event, Policy, PrioritizeCalls};
+use frame_support::{assert_noop, assert_ok, traits::GetCallMetadata, weights::DispatchInfo};
+use sp_runtime::{
+	traits::SignedExtension,
+	transaction_validity::{TransactionLongevity, ValidTransaction},
+	DispatchError,
+};
+
+const PALLET_POLICY: Policy = Policy { boost: 10, allow_listed_boost: 100, longevity: None };
+const CALL_POLICY: Policy = Policy { boost: 20, allow_listed_boost: 200, longevity: Some(16) };
+
+fn remark() -> Call {
+	frame_system::Call::remark { remark: Vec::new() }.into()
+}
+
+fn validate(who: u64, call: &Call) -> ValidTransaction {
+	PrioritizeCalls::<Test>::new()
+		.validate(&who, call, &DispatchInfo::default(), 0)
+		.expect("the extension never rejects transactions")
+}
+
+fn set_policy(pallet: &str, call: Option<&str>, policy: Option<Policy>) {
+	assert_ok!(Priority::set_policy(Origin::root(), name(pallet), call.map(name), policy));
+}
+
+#[test]
+fn leaves_calls_without_policy_alone() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(validate(USER, &remark()), ValidTransaction::default());
+		assert_eq!(validate(SERVICE, &remark()), ValidTransaction::default());
+	});
+}
+
+#[test]
+fn applies_the_policy_of_the_pallet() {
+	new_test_ext().execute_with(|| {
+		set_policy("System", None, Some(PALLET_POLICY));
+
+		let validity = validate(USER, &remark());
+		assert_eq!(validity.priority, 10);
+		assert_eq!(validity.longevity, TransactionLongevity::MAX);
+	});
+}
+
+#[test]
+fn prefers_the_policy_of_the_call() {
+	new_test_ext().execute_with(|| {
+		set_policy("System", None, Some(PALLET_POLICY));
+		set_policy("System", Some("remark"), Some(CALL_POLICY));
+
+		let validity = validate(USER, &remark());
+		assert_eq!(validity.priority, 20);
+		assert_eq!(validity.longevity, 16);
+
+		let set_heap_pages = frame_system::Call::set_heap_pages { pages: 1 }.into();
+		assert_eq!(validate(USER, &set_heap_pages).priority, 10);
+	});
+}
+
+#[test]
+fn boosts_allow_listed_accounts_more() {
+	new_test_ext().execute_with(|| {
+		set_policy("System", Some("remark"), Some(CALL_POLICY));
+		assert_eq!(validate(SERVICE, &remark()).priority, 200);
+
+		assert_ok!(Priority::set_allow_listed(Origin::root(), SERVICE, false));
+		System::assert_last_event(
+			Event::<Test>::AllowListUpdated { account: SERVICE, allowed: false }.into(),
+		);
+		assert_eq!(validate(SERVICE, &remark()).priority, 20);
+	});
+}
+
+#[test]
+fn removes_policies() {
+	new_test_ext().execute_with(|| {
+		set_policy("System", Some("remark"), Some(CALL_POLICY));
+		set_policy("System", Some("remark"), None);
+
+		let event = Event::<Test>::PolicySet {
+			pallet: name("System"),
+			call: Some(name("remark")),
+			policy: None,
+		};
+		System::assert_last_event(event.into());
+		assert_eq!(validate(USER, &remark()), ValidTransaction::default());
+	});
+}
+
+#[test]
+fn only_the_admin_origin_changes_policies() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Priority::set_policy(Origin::signed(USER), name("System"), None, Some(PALLET_POLICY)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Priority::set_allow_listed(Origin::signed(USER), USER, true),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn looks_up_policies_by_metadata_names() {
+	new_test_ext().execute_with(|| {
+		set_policy("System", Some("remark"), Some(CALL_POLICY));
+		assert_eq!(Priority::policy(&remark().get_call_metadata()), Some(CALL_POLICY));
+	});
+}
@@ -0,0 +1,8 @@
+//! Dispatches random sequences of calls to the template pallet and checks its invariants.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pallet_template::invariants::{check, Operation};
+
+fuzz_target!(|operations: Vec<Operation>| check(&operations));
@@ -0,0 +1,225 @@
+//! Invariants of the pallet's dispatchables, checked against random sequences of calls by the
+//! proptest suite and the fuzz target in `fuzz/`.
+//!
+//! The storage accesses of a call are measured from the `state` traces of the externalities, so
+//! the weight check doesn't rely on what the pallet claims to access.
+
+use crate::{mock::*, Call as TemplateCall, Error, Event as TemplateEvent, Something};
+use frame_support::{
+	dispatch::{DispatchError, GetDispatchInfo},
+	traits::{Get, PalletInfoAccess, UnfilteredDispatchable},
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_core::hexdisplay::HexDisplay;
+use std::{
+	collections::BTreeSet,
+	fmt,
+	sync::{Arc, Mutex},
+};
+use tracing::{
+	field::{Field, Visit},
+	span, Metadata, Subscriber,
+};
+
+/// Weight of the dispatchables on top of their storage accesses.
+const BASE_WEIGHT: Weight = 10_000;
+
+/// Origin of a call.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum Caller {
+	Signed(u64),
+	Root,
+	None,
+}
+
+impl Caller {
+	fn origin(self) -> Origin {
+		match self {
+			Caller::Signed(who) => Origin::signed(who),
+			Caller::Root => Origin::root(),
+			Caller::None => Origin::none(),
+		}
+	}
+}
+
+/// A call of one of the pallet's dispatchables.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum Operation {
+	DoSomething { caller: Caller, something: u32 },
+	CauseError { caller: Caller },
+}
+
+/// What a call is expected to do, given the value of `Something` before it.
+struct Outcome {
+	result: Result<(), DispatchError>,
+	something: Option<u32>,
+	events: Vec<Event>,
+}
+
+impl Outcome {
+	/// A call failing with `error`, leaving the state as it was.
+	fn failed(error: impl Into<DispatchError>, something: Option<u32>) -> Self {
+		Self { result: Err(error.into()), something, events: vec![] }
+	}
+}
+
+impl Operation {
+	fn call(self) -> (TemplateCall<Test>, Caller) {
+		match self {
+			Operation::DoSomething { caller, something } =>
+				(TemplateCall::do_something { something }, caller),
+			Operation::CauseError { caller } => (TemplateCall::cause_error {}, caller),
+		}
+	}
+
+	fn expected(self, before: Option<u32>) -> Outcome {
+		let who = match self {
+			Operation::DoSomething { caller: Caller::Signed(who), .. } |
+			Operation::CauseError { caller: Caller::Signed(who) } => who,
+			_ => return Outcome::failed(DispatchError::BadOrigin, before),
+		};
+		match (self, before) {
+			(Operation::DoSomething { something, .. }, _) => Outcome {
+				result: Ok(()),
+				something: Some(something),
+				events: vec![TemplateEvent::SomethingStored(something, who).into()],
+			},
+			(Operation::CauseError { .. }, None) =>
+				Outcome::failed(Error::<Test>::NoneValue, before),
+			(Operation::CauseError { .. }, Some(u32::MAX)) =>
+				Outcome::failed(Error::<Test>::StorageOverflow, before),
+			(Operation::CauseError { .. }, Some(old)) =>
+				Outcome { result: Ok(()), something: Some(old + 1), events: vec![] },
+		}
+	}
+}
+
+/// Dispatches `operations` in a fresh `mock::new_test_ext`, and panics unless every call returns
+/// what is expected, changes the state and deposits the events accordingly, and has a weight
+/// covering the storage accesses it makes.
+pub fn check(operations: &[Operation]) {
+	// Price the storage accesses like the runtime does, for this thread's mock only.
+	DbWeight::set(&RocksDbWeight::get());
+	new_test_ext().execute_with(|| {
+		// Events are not deposited in the genesis block.
+		System::set_block_number(1);
+		for operation in operations {
+			check_operation(*operation);
+		}
+	});
+}
+
+fn check_operation(operation: Operation) {
+	let before = Something::<Test>::get();
+	let events_before = System::events().len();
+	let expected = operation.expected(before);
+
+	let (call, caller) = operation.call();
+	let weight = call.get_dispatch_info().weight;
+	let (result, accesses) = record_accesses(|| {
+		call.dispatch_bypass_filter(caller.origin()).map(|_| ()).map_err(|e| e.error)
+	});
+
+	assert_eq!(result, expected.result, "{:?} with `Something` at {:?}", operation, before);
+	assert_eq!(Something::<Test>::get(), expected.something, "{:?} from {:?}", operation, before);
+	let events: Vec<_> =
+		System::events()[events_before..].iter().map(|record| record.event.clone()).collect();
+	assert_eq!(events, expected.events, "{:?} from {:?}", operation, before);
+
+	let accesses = DbWeight::get()
+		.reads_writes(accesses.reads.len() as Weight, accesses.writes.len() as Weight);
+	assert!(
+		weight >= BASE_WEIGHT + accesses,
+		"the weight {} of {:?} doesn't cover its storage accesses",
+		weight,
+		operation
+	);
+}
+
+/// Keys of the pallet's storage read and written, as traced by the externalities.
+#[derive(Default)]
+struct Accesses {
+	reads: BTreeSet<String>,
+	writes: BTreeSet<String>,
+}
+
+/// Runs `f` and returns the keys of the pallet's storage it accessed. Events deposited in
+/// `frame_system` aren't priced by the weights, and aren't recorded.
+fn record_accesses<R>(f: impl FnOnce() -> R) -> (R, Accesses) {
+	let prefix = sp_io::hashing::twox_128(TemplateModule::name().as_bytes());
+	let recorder = AccessRecorder {
+		prefix: HexDisplay::from(&prefix).to_string(),
+		accesses: Default::default(),
+	};
+	let accesses = recorder.accesses.clone();
+
+	let result = tracing::subscriber::with_default(recorder, f);
+	let accesses = std::mem::take(&mut *accesses.lock().expect("the recorder doesn't panic"));
+	(result, accesses)
+}
+
+/// Records the keys under `prefix` that the externalities trace accesses to.
+struct AccessRecorder {
+	/// Hex encoded prefix of the recorded keys, as the keys are traced.
+	prefix: String,
+	accesses: Arc<Mutex<Accesses>>,
+}
+
+impl Subscriber for AccessRecorder {
+	fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+		metadata.target() == "state"
+	}
+
+	fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+		span::Id::from_u64(1)
+	}
+
+	fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+	fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+	fn event(&self, event: &tracing::Event<'_>) {
+		let mut access = StateAccess::default();
+		event.record(&mut access);
+		let key = match access.key {
+			Some(key) if key.starts_with(&self.prefix) => key,
+			_ => return,
+		};
+
+		let mut accesses = self.accesses.lock().expect("the recorder doesn't panic");
+		match access.method.as_deref() {
+			Some("Get" | "Exists" | "Hash") => accesses.reads.insert(key),
+			Some("Put" | "Append" | "ClearPrefix") => accesses.writes.insert(key),
+			_ => false,
+		};
+	}
+
+	fn enter(&self, _: &span::Id) {}
+
+	fn exit(&self, _: &span::Id) {}
+}
+
+/// The fields of a `state` trace naming the storage access and its key, or prefix.
+#[derive(Default)]
+struct StateAccess {
+	method: Option<String>,
+	key: Option<String>,
+}
+
+impl Visit for StateAccess {
+	fn record_str(&mut self, field: &Field, value: &str) {
+		if field.name() == "method" {
+			self.method = Some(value.into());
+		}
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+		match field.name() {
+			"method" => self.method = Some(format!("{:?}", value).trim_matches('"').into()),
+			"key" | "prefix" => self.key = Some(format!("{:?}", value)),
+			_ => (),
+		}
+	}
+}
@@ -5,12 +5,18 @@
 /// <https://docs.substrate.io/v3/runtime/frame>
 pub use pallet::*;
 
-#[cfg(test)]
-mod mock;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod mock;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod proptests;
+
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod invariants;
+
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
@@ -99,4 +105,18 @@ pub mod pallet {
 			}
 		}
 	}
+
+	impl<T: Config> Pallet<T> {
+		/// Checks that the pallet's storage decodes, e.g. after a runtime upgrade migrated it.
+		pub fn check_invariants() -> Result<(), &'static str> {
+			use codec::DecodeAll;
+
+			let key = Something::<T>::hashed_key();
+			match frame_support::storage::unhashed::get_raw(&key) {
+				Some(value) if u32::decode_all(&mut &value[..]).is_err() =>
+					Err("`Something` is not a u32"),
+				_ => Ok(()),
+			}
+		}
+	}
 }
@@ -1,5 +1,9 @@
 use crate as pallet_template;
-use frame_support::traits::{ConstU16, ConstU64};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU64},
+	weights::RuntimeDbWeight,
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
@@ -22,11 +26,16 @@ frame_support::construct_runtime!(
 	}
 );
 
+parameter_types! {
+	/// Storage accesses are free, unless the invariants price them to check the weights.
+	pub static DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 0, write: 0 };
+}
+
 impl system::Config for Test {
 	type BaseCallFilter = frame_support::traits::Everything;
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type Origin = Origin;
 	type Call = Call;
 	type Index = u64;
@@ -0,0 +1,51 @@
+use crate::{
+	invariants::{check, Caller, Operation},
+	mock::*,
+	Error,
+};
+use frame_support::{assert_noop, assert_ok};
+use proptest::prelude::*;
+
+/// Signed callers among a few accounts, and now and then root or no origin at all.
+fn caller() -> impl Strategy<Value = Caller> {
+	prop_oneof![
+		8 => (0..4u64).prop_map(Caller::Signed),
+		1 => Just(Caller::Root),
+		1 => Just(Caller::None),
+	]
+}
+
+/// Values biased towards the edges, so that sequences of calls reach `u32::MAX`.
+fn something() -> impl Strategy<Value = u32> {
+	prop_oneof![any::<u32>(), 0..4u32, u32::MAX - 4..=u32::MAX]
+}
+
+fn operation() -> impl Strategy<Value = Operation> {
+	prop_oneof![
+		(caller(), something())
+			.prop_map(|(caller, something)| Operation::DoSomething { caller, something }),
+		caller().prop_map(|caller| Operation::CauseError { caller }),
+	]
+}
+
+proptest! {
+	#[test]
+	fn random_calls_keep_the_invariants(operations in prop::collection::vec(operation(), 0..64)) {
+		check(&operations);
+	}
+
+	#[test]
+	fn storage_overflows_exactly_at_max(start in u32::MAX - 16..=u32::MAX, who in 0..4u64) {
+		new_test_ext().execute_with(|| {
+			assert_ok!(TemplateModule::do_something(Origin::signed(who), start));
+			for _ in start..u32::MAX {
+				assert_ok!(TemplateModule::cause_error(Origin::signed(who)));
+			}
+			assert_eq!(TemplateModule::something(), Some(u32::MAX));
+			assert_noop!(
+				TemplateModule::cause_error(Origin::signed(who)),
+				Error::<Test>::StorageOverflow
+			);
+		});
+	}
+}
@@ -18,3 +18,19 @@ fn correct_error_for_none_value() {
 		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
 	});
 }
+
+#[test]
+fn invariants_hold_unless_storage_is_corrupted() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(TemplateModule::check_invariants(), Ok(()));
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
+		assert_eq!(TemplateModule::check_invariants(), Ok(()));
+
+		// A value of another type, as a faulty migration could leave.
+		let key = crate::Something::<Test>::hashed_key();
+		frame_support::storage::unhashed::put_raw(&key, &[1, 2]);
+		assert_eq!(TemplateModule::check_invariants(), Err("`Something` is not a u32"));
+		frame_support::storage::unhashed::put_raw(&key, &42u64.to_le_bytes());
+		assert_eq!(TemplateModule::check_invariants(), Err("`Something` is not a u32"));
+	});
+}
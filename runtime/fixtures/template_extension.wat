@@ -0,0 +1,37 @@
+;; Contract storing `42` in `pallet_template::Something` through the chain extension when it is
+;; deployed, and returning the SCALE encoded `Option<u32>` read through it when it is called.
+(module
+	(import "seal0" "seal_call_chain_extension"
+		(func $call_chain_extension (param i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) input of `WRITE_SOMETHING`
+	(data (i32.const 0) "\2a\00\00\00")
+
+	;; [4, 8) length of the output buffer
+	(data (i32.const 4) "\10\00\00\00")
+
+	;; [8, 24) output buffer
+
+	(func (export "deploy")
+		(drop (call $call_chain_extension
+			(i32.const 2) ;; WRITE_SOMETHING
+			(i32.const 0) ;; input_ptr
+			(i32.const 4) ;; input_len
+			(i32.const 8) ;; output_ptr
+			(i32.const 4) ;; output_len_ptr
+		))
+	)
+
+	(func (export "call")
+		(drop (call $call_chain_extension
+			(i32.const 1) ;; READ_SOMETHING
+			(i32.const 0) ;; input_ptr
+			(i32.const 0) ;; input_len
+			(i32.const 8) ;; output_ptr
+			(i32.const 4) ;; output_len_ptr
+		))
+		(call $seal_return (i32.const 0) (i32.const 8) (i32.load (i32.const 4)))
+	)
+)
@@ -0,0 +1,105 @@
+//! Runtime APIs specific to the node template.
+
+use crate::{AccountId, AssetId, Balance, Call, CollectionId, Event, Hash, ItemId};
+use codec::{Decode, Encode};
+use frame_support::weights::Weight;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::Block as BlockT,
+	transaction_validity::{
+		TransactionLongevity, TransactionPriority, TransactionSource, TransactionValidityError,
+	},
+	DispatchResult, RuntimeDebug, RuntimeString,
+};
+use sp_std::vec::Vec;
+
+/// Outcome of [`DryRunApi::apply_as`].
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct DryRunOutcome {
+	/// Result of the dispatch.
+	pub result: DispatchResult,
+	/// Weight consumed by the dispatch, after refunds.
+	pub weight: Weight,
+}
+
+/// Outcome of [`PriorityApi::effective_priority`].
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct EffectivePriority {
+	/// Priority the transaction pool orders the transaction by.
+	pub priority: TransactionPriority,
+	/// Part of `priority` added by the policy of `pallet_priority`.
+	pub boost: TransactionPriority,
+	/// Number of blocks the transaction stays valid for.
+	pub longevity: TransactionLongevity,
+}
+
+/// Holding of an account in an asset of `pallet_assets`, as returned by
+/// [`AssetsApi::account_balances`].
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct AssetBalance {
+	/// The asset held.
+	pub asset_id: AssetId,
+	/// Balance of the account in the asset.
+	pub balance: Balance,
+	/// Name of the asset from its metadata, empty if it has none.
+	pub name: Vec<u8>,
+	/// Symbol of the asset from its metadata, empty if it has none.
+	pub symbol: Vec<u8>,
+	/// Number of decimals of the asset from its metadata, zero if it has none.
+	pub decimals: u8,
+}
+
+sp_api::decl_runtime_apis! {
+	/// Simulates calls for the `dev_dryRun` RPC.
+	///
+	/// Meant to be called on top of an initialized block, in a runtime API instance whose changes
+	/// are discarded afterwards.
+	pub trait DryRunApi {
+		/// Dispatches `call` with a signed origin of `signer`, without checking signature, nonce
+		/// or fees.
+		fn apply_as(signer: AccountId, call: Call) -> DryRunOutcome;
+
+		/// Returns the events deposited in the current block so far.
+		fn events() -> Vec<frame_system::EventRecord<Event, Hash>>;
+	}
+
+	/// Explains the priority of transactions, for debugging the `pallet_priority` policies.
+	pub trait PriorityApi {
+		/// Validates `tx` like the transaction pool does and returns its priority, including the
+		/// part added by the policy of its call.
+		fn effective_priority(
+			source: TransactionSource,
+			tx: <Block as BlockT>::Extrinsic,
+			block_hash: <Block as BlockT>::Hash,
+		) -> Result<EffectivePriority, TransactionValidityError>;
+	}
+
+	/// Lists the holdings of accounts, for the `assets_balances` RPC.
+	pub trait AssetsApi {
+		/// Returns the nonzero balances of `account` in all assets of `pallet_assets`, by asset id.
+		fn account_balances(account: AccountId) -> Vec<AssetBalance>;
+	}
+
+	/// Lists the unique items of accounts, for the `uniques_ownedItems` RPC.
+	pub trait UniquesApi {
+		/// Returns the collection and item ids of the items of `pallet_uniques` owned by
+		/// `account`, in ascending order.
+		fn owned_items(account: AccountId) -> Vec<(CollectionId, ItemId)>;
+	}
+
+	/// Checks `pallet_template` with the code of the runtime, for the `upgrade-rehearsal` command.
+	pub trait TemplateApi {
+		/// Returns the invariant of `pallet_template` its storage violates, if any.
+		fn check_invariants() -> Result<(), RuntimeString>;
+	}
+
+	/// Upgrades the runtime for the `upgrade-rehearsal` command, like governance would.
+	///
+	/// Meant to be called on top of an initialized block, in a runtime API instance whose changes
+	/// are never imported.
+	pub trait UpgradeApi {
+		/// Dispatches `System::set_code` with `code` as root, and returns the name of the error
+		/// it failed with, if any.
+		fn set_code(code: Vec<u8>) -> Result<(), RuntimeString>;
+	}
+}
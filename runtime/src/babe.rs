@@ -0,0 +1,51 @@
+//! Configuration of BABE, which assigns slots to authorities with a VRF instead of Aura's
+//! round-robin.
+//!
+//! Only compiled with the `babe` feature, which replaces Aura by BABE.
+
+use super::*;
+
+use sp_consensus_babe::{AllowedSlots, BabeEpochConfiguration};
+
+pub use sp_consensus_babe::AuthorityId as BabeId;
+
+/// Number of slots in an epoch. The authorities never change without a session pallet, so epochs
+/// only rotate the randomness of the slot assignment.
+pub const EPOCH_DURATION_IN_SLOTS: u64 = 10 * MINUTES as u64;
+
+/// Probability of a slot having a primary author, as `(numerator, denominator)`. Slots without one
+/// are claimed by their secondary author.
+pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
+
+/// The epoch configuration the chain starts with.
+pub const BABE_GENESIS_EPOCH_CONFIG: BabeEpochConfiguration = BabeEpochConfiguration {
+	c: PRIMARY_PROBABILITY,
+	allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
+};
+
+parameter_types! {
+	pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
+	pub const ExpectedBlockTime: u64 = MILLISECS_PER_BLOCK;
+}
+
+impl pallet_babe::Config for Runtime {
+	type EpochDuration = EpochDuration;
+	type ExpectedBlockTime = ExpectedBlockTime;
+	type EpochChangeTrigger = pallet_babe::SameAuthoritiesForever;
+	type DisabledValidators = ();
+
+	type KeyOwnerProofSystem = ();
+
+	type KeyOwnerProof =
+		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, BabeId)>>::Proof;
+
+	type KeyOwnerIdentification = <Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(
+		KeyTypeId,
+		BabeId,
+	)>>::IdentificationTuple;
+
+	type HandleEquivocation = ();
+
+	type WeightInfo = ();
+	type MaxAuthorities = ConstU32<32>;
+}
@@ -0,0 +1,56 @@
+//! Chain extension giving ink! contracts access to `pallet_template`.
+//!
+//! | `func_id` | input | output                      |
+//! |-----------|-------|-----------------------------|
+//! | `1`       | -     | `Option<u32>` (`Something`) |
+//! | `2`       | `u32` | -                           |
+//!
+//! Writes are dispatched as `do_something` signed by the calling contract's account.
+
+use super::*;
+
+use codec::Encode;
+use frame_support::{dispatch::DispatchError, weights::GetDispatchInfo};
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+
+/// `func_id` reading `pallet_template::Something`.
+pub const READ_SOMETHING: u32 = 1;
+
+/// `func_id` writing `pallet_template::Something`.
+pub const WRITE_SOMETHING: u32 = 2;
+
+/// The chain extension installed in [`pallet_contracts`].
+#[derive(Default)]
+pub struct TemplateExtension;
+
+impl ChainExtension<Runtime> for TemplateExtension {
+	fn call<E>(
+		&mut self,
+		env: Environment<E, InitState>,
+	) -> pallet_contracts::chain_extension::Result<RetVal>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		match env.func_id() {
+			READ_SOMETHING => {
+				let mut env = env.buf_in_buf_out();
+				env.charge_weight(RocksDbWeight::get().reads(1))?;
+				let something = pallet_template::Pallet::<Runtime>::something();
+				env.write(&something.encode(), false, None)?;
+			},
+			WRITE_SOMETHING => {
+				let mut env = env.buf_in_buf_out();
+				let something: u32 = env.read_as()?;
+				let call = pallet_template::Call::<Runtime>::do_something { something };
+				env.charge_weight(call.get_dispatch_info().weight)?;
+				let origin = frame_system::RawOrigin::Signed(env.ext().address().clone());
+				pallet_template::Pallet::<Runtime>::do_something(origin.into(), something)?;
+			},
+			_ => return Err(DispatchError::Other("unknown chain extension function")),
+		}
+		Ok(RetVal::Converging(0))
+	}
+}
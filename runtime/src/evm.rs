@@ -0,0 +1,199 @@
+//! Configuration of the Frontier pallets that provide the EVM execution environment.
+//!
+//! Only compiled with the `evm` feature.
+
+use super::*;
+
+use codec::{Decode, Encode};
+use frame_support::traits::FindAuthor;
+use pallet_ethereum::{Call::transact, Transaction as EthereumTransaction};
+use pallet_evm::{EnsureAddressTruncated, FeeCalculator, GasWeightMapping, HashedAddressMapping};
+use sp_core::{H160, U256};
+use sp_runtime::{
+	traits::{Dispatchable, PostDispatchInfoOf},
+	transaction_validity::TransactionValidityError,
+	ConsensusEngineId,
+};
+use sp_std::marker::PhantomData;
+
+mod precompiles;
+
+pub use precompiles::{NodeTemplatePrecompiles, BALANCES_PRECOMPILE, TEMPLATE_PRECOMPILE};
+
+/// The chain id reported by `eth_chainId` and required in EIP-155 signatures.
+pub const CHAIN_ID: u64 = 42;
+
+/// Approximate amount of gas the EVM executes per second of block execution time.
+pub const GAS_PER_SECOND: u64 = 40_000_000;
+
+/// Amount of weight charged for each unit of gas.
+pub const WEIGHT_PER_GAS: u64 = WEIGHT_PER_SECOND / GAS_PER_SECOND;
+
+/// Converts between gas and weight at the fixed [`WEIGHT_PER_GAS`] ratio.
+pub struct FixedGasWeightMapping;
+
+impl GasWeightMapping for FixedGasWeightMapping {
+	fn gas_to_weight(gas: u64) -> Weight {
+		gas.saturating_mul(WEIGHT_PER_GAS)
+	}
+
+	fn weight_to_gas(weight: Weight) -> u64 {
+		weight / WEIGHT_PER_GAS
+	}
+}
+
+/// Charges a flat gas price of one gwei.
+pub struct FixedGasPrice;
+
+impl FeeCalculator for FixedGasPrice {
+	fn min_gas_price() -> (U256, Weight) {
+		(U256::from(1_000_000_000u64), 0)
+	}
+}
+
+/// Uses the first 20 bytes of the block author's public key as the block coinbase.
+pub struct FindAuthorTruncated<F>(PhantomData<F>);
+
+impl<F: FindAuthor<u32>> FindAuthor<H160> for FindAuthorTruncated<F> {
+	fn find_author<'a, I>(digests: I) -> Option<H160>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		let author_index = F::find_author(digests)?;
+		let authority_key = authority_key(author_index)?;
+		Some(H160::from_slice(&authority_key[4..24]))
+	}
+}
+
+/// Public key of the block authoring authority at `index`, encoded as its raw bytes.
+#[cfg(not(any(feature = "babe", feature = "pow")))]
+fn authority_key(index: u32) -> Option<Vec<u8>> {
+	Aura::authorities().get(index as usize).map(Encode::encode)
+}
+
+/// Public key of the block authoring authority at `index`, encoded as its raw bytes.
+#[cfg(feature = "babe")]
+fn authority_key(index: u32) -> Option<Vec<u8>> {
+	Babe::authorities().get(index as usize).map(|(id, _)| id.encode())
+}
+
+/// Mined blocks have no authorities.
+#[cfg(feature = "pow")]
+fn authority_key(_index: u32) -> Option<Vec<u8>> {
+	None
+}
+
+parameter_types! {
+	pub const ChainId: u64 = CHAIN_ID;
+	/// Only the normal dispatch class share of a block can be spent on EVM execution.
+	pub BlockGasLimit: U256 =
+		U256::from(NORMAL_DISPATCH_RATIO * BlockWeights::get().max_block / WEIGHT_PER_GAS);
+	pub PrecompilesValue: NodeTemplatePrecompiles = NodeTemplatePrecompiles;
+}
+
+impl pallet_evm::Config for Runtime {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = FixedGasWeightMapping;
+	type BlockHashMapping = pallet_ethereum::EthereumBlockHashMapping<Self>;
+	type CallOrigin = EnsureAddressTruncated;
+	type WithdrawOrigin = EnsureAddressTruncated;
+	/// EVM accounts are backed by the substrate account `blake2_256("evm:" ++ address)`.
+	type AddressMapping = HashedAddressMapping<BlakeTwo256>;
+	type Currency = Balances;
+	type Event = Event;
+	type PrecompilesType = NodeTemplatePrecompiles;
+	type PrecompilesValue = PrecompilesValue;
+	type ChainId = ChainId;
+	type BlockGasLimit = BlockGasLimit;
+	type Runner = pallet_evm::runner::stack::Runner<Self>;
+	type OnChargeTransaction = ();
+	#[cfg(not(any(feature = "babe", feature = "pow")))]
+	type FindAuthor = FindAuthorTruncated<Aura>;
+	#[cfg(feature = "babe")]
+	type FindAuthor = FindAuthorTruncated<Babe>;
+	#[cfg(feature = "pow")]
+	type FindAuthor = FindAuthorTruncated<()>;
+}
+
+impl pallet_ethereum::Config for Runtime {
+	type Event = Event;
+	type StateRoot = pallet_ethereum::IntermediateStateRoot<Self>;
+}
+
+impl fp_self_contained::SelfContainedCall for Call {
+	type SignedInfo = H160;
+
+	fn is_self_contained(&self) -> bool {
+		match self {
+			Call::Ethereum(call) => call.is_self_contained(),
+			_ => false,
+		}
+	}
+
+	fn check_self_contained(&self) -> Option<Result<Self::SignedInfo, TransactionValidityError>> {
+		match self {
+			Call::Ethereum(call) => call.check_self_contained(),
+			_ => None,
+		}
+	}
+
+	fn validate_self_contained(&self, info: &Self::SignedInfo) -> Option<TransactionValidity> {
+		match self {
+			Call::Ethereum(call) => call.validate_self_contained(info),
+			_ => None,
+		}
+	}
+
+	fn pre_dispatch_self_contained(
+		&self,
+		info: &Self::SignedInfo,
+	) -> Option<Result<(), TransactionValidityError>> {
+		match self {
+			Call::Ethereum(call) => call.pre_dispatch_self_contained(info),
+			_ => None,
+		}
+	}
+
+	fn apply_self_contained(
+		self,
+		info: Self::SignedInfo,
+	) -> Option<sp_runtime::DispatchResultWithInfo<PostDispatchInfoOf<Self>>> {
+		match self {
+			call @ Call::Ethereum(transact { .. }) => Some(call.dispatch(Origin::from(
+				pallet_ethereum::RawOrigin::EthereumTransaction(info),
+			))),
+			_ => None,
+		}
+	}
+}
+
+/// Wraps Ethereum transactions received over `eth_sendRawTransaction` into extrinsics.
+#[derive(Clone)]
+pub struct TransactionConverter;
+
+impl fp_rpc::ConvertTransaction<UncheckedExtrinsic> for TransactionConverter {
+	fn convert_transaction(&self, transaction: EthereumTransaction) -> UncheckedExtrinsic {
+		UncheckedExtrinsic::new_unsigned(transact { transaction }.into())
+	}
+}
+
+impl fp_rpc::ConvertTransaction<opaque::UncheckedExtrinsic> for TransactionConverter {
+	fn convert_transaction(&self, transaction: EthereumTransaction) -> opaque::UncheckedExtrinsic {
+		let extrinsic = UncheckedExtrinsic::new_unsigned(transact { transaction }.into());
+		let encoded = extrinsic.encode();
+		opaque::UncheckedExtrinsic::decode(&mut &encoded[..])
+			.expect("Encoded extrinsic is always valid")
+	}
+}
+
+/// Returns the Ethereum transactions contained in `xts`.
+pub fn ethereum_transactions(
+	xts: Vec<<Block as BlockT>::Extrinsic>,
+) -> Vec<EthereumTransaction> {
+	xts.into_iter()
+		.filter_map(|xt| match xt.0.function {
+			Call::Ethereum(transact { transaction }) => Some(transaction),
+			_ => None,
+		})
+		.collect()
+}
@@ -0,0 +1,206 @@
+//! Precompiled contracts available to EVM code.
+//!
+//! Next to the standard Ethereum precompiles at addresses `0x01`-`0x04`, two precompiles expose
+//! runtime functionality to Solidity:
+//!
+//! - [`TEMPLATE_PRECOMPILE`]: `get()` reads and `set(uint256)` writes `pallet_template::Something`.
+//! - [`BALANCES_PRECOMPILE`]: `balanceOf(bytes32)` and `transfer(bytes32,uint256)` on
+//!   `pallet_balances`, where `bytes32` is a substrate account id.
+//!
+//! Writes are dispatched as regular calls signed by the substrate account backing the EVM caller,
+//! so they are subject to the same checks, events and weights as extrinsics. They revert when the
+//! precompile is reached by `DELEGATECALL` or `CALLCODE`, which would let the calling contract
+//! dispatch calls on behalf of whoever called it.
+
+use super::{FixedGasWeightMapping, Runtime};
+use crate::{AccountId, Balance, BlakeTwo256, Call, RocksDbWeight};
+use fp_evm::{
+	ExitError, ExitRevert, ExitSucceed, Precompile, PrecompileFailure, PrecompileHandle,
+	PrecompileOutput, PrecompileResult, PrecompileSet,
+};
+use frame_support::{
+	dispatch::Dispatchable,
+	weights::{GetDispatchInfo, Weight},
+};
+use pallet_evm::{AddressMapping, GasWeightMapping, HashedAddressMapping};
+use pallet_evm_precompile_simple::{ECRecover, Identity, Ripemd160, Sha256};
+use sp_core::{H160, U256};
+use sp_std::{vec, vec::Vec};
+
+/// Address of the `pallet_template` precompile.
+pub const TEMPLATE_PRECOMPILE: H160 = precompile_address(0x400);
+
+/// Address of the `pallet_balances` precompile.
+pub const BALANCES_PRECOMPILE: H160 = precompile_address(0x401);
+
+/// `get()`
+const SELECTOR_GET: [u8; 4] = [0x6d, 0x4c, 0xe6, 0x3c];
+/// `set(uint256)`
+const SELECTOR_SET: [u8; 4] = [0x60, 0xfe, 0x47, 0xb1];
+/// `balanceOf(bytes32)`
+const SELECTOR_BALANCE_OF: [u8; 4] = [0x6c, 0x7f, 0x15, 0x42];
+/// `transfer(bytes32,uint256)`
+const SELECTOR_TRANSFER: [u8; 4] = [0x6a, 0x46, 0x73, 0x94];
+
+/// The set of precompiles installed in the node template's EVM.
+#[derive(Clone, Default)]
+pub struct NodeTemplatePrecompiles;
+
+impl NodeTemplatePrecompiles {
+	/// Addresses of all precompiles in the set.
+	pub fn used_addresses() -> [H160; 6] {
+		[
+			precompile_address(1),
+			precompile_address(2),
+			precompile_address(3),
+			precompile_address(4),
+			TEMPLATE_PRECOMPILE,
+			BALANCES_PRECOMPILE,
+		]
+	}
+}
+
+impl PrecompileSet for NodeTemplatePrecompiles {
+	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
+		match handle.code_address() {
+			a if a == precompile_address(1) => Some(ECRecover::execute(handle)),
+			a if a == precompile_address(2) => Some(Sha256::execute(handle)),
+			a if a == precompile_address(3) => Some(Ripemd160::execute(handle)),
+			a if a == precompile_address(4) => Some(Identity::execute(handle)),
+			a if a == TEMPLATE_PRECOMPILE => Some(TemplatePrecompile::execute(handle)),
+			a if a == BALANCES_PRECOMPILE => Some(BalancesPrecompile::execute(handle)),
+			_ => None,
+		}
+	}
+
+	fn is_precompile(&self, address: H160) -> bool {
+		Self::used_addresses().contains(&address)
+	}
+}
+
+/// Reads and writes `pallet_template::Something`.
+pub struct TemplatePrecompile;
+
+impl Precompile for TemplatePrecompile {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let (selector, args) = split_selector(handle.input())?;
+
+		match selector {
+			SELECTOR_GET => {
+				record_weight(handle, RocksDbWeight::get().reads(1))?;
+				let value = pallet_template::Pallet::<Runtime>::something().unwrap_or_default();
+				Ok(succeed(encode_u256(value.into())))
+			},
+			SELECTOR_SET => {
+				let something = decode_u256(args, 0)?
+					.try_into()
+					.map_err(|_| revert("value does not fit into uint32"))?;
+				dispatch(handle, pallet_template::Call::<Runtime>::do_something { something })?;
+				Ok(succeed(Vec::new()))
+			},
+			_ => Err(revert("unknown selector")),
+		}
+	}
+}
+
+/// Queries balances and transfers funds between substrate accounts.
+pub struct BalancesPrecompile;
+
+impl Precompile for BalancesPrecompile {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let (selector, args) = split_selector(handle.input())?;
+
+		match selector {
+			SELECTOR_BALANCE_OF => {
+				record_weight(handle, RocksDbWeight::get().reads(1))?;
+				let who = decode_account_id(args, 0)?;
+				let balance = pallet_balances::Pallet::<Runtime>::free_balance(&who);
+				Ok(succeed(encode_u256(balance.into())))
+			},
+			SELECTOR_TRANSFER => {
+				let dest = decode_account_id(args, 0)?;
+				let value: Balance = decode_u256(args, 1)?
+					.try_into()
+					.map_err(|_| revert("value does not fit into the balance type"))?;
+				dispatch(
+					handle,
+					pallet_balances::Call::<Runtime>::transfer { dest: dest.into(), value },
+				)?;
+				Ok(succeed(Vec::new()))
+			},
+			_ => Err(revert("unknown selector")),
+		}
+	}
+}
+
+/// Dispatches `call` signed by the substrate account backing the EVM caller, charging its
+/// declared weight as gas up front.
+fn dispatch(
+	handle: &mut impl PrecompileHandle,
+	call: impl Into<Call>,
+) -> Result<(), PrecompileFailure> {
+	if handle.is_static() {
+		return Err(revert("cannot modify state in a static call"))
+	}
+	// The code of the precompile runs in the context of another contract, whose caller would
+	// otherwise be the origin of the call.
+	if handle.context().address != handle.code_address() {
+		return Err(revert("cannot modify state in a delegate call"))
+	}
+
+	let call: Call = call.into();
+	record_weight(handle, call.get_dispatch_info().weight)?;
+
+	let origin = HashedAddressMapping::<BlakeTwo256>::into_account_id(handle.context().caller);
+	call.dispatch(Some(origin).into()).map(|_| ()).map_err(|e| {
+		let message: &'static str = e.error.into();
+		revert(message)
+	})
+}
+
+fn record_weight(handle: &mut impl PrecompileHandle, weight: Weight) -> Result<(), ExitError> {
+	handle.record_cost(FixedGasWeightMapping::weight_to_gas(weight))
+}
+
+fn split_selector(input: &[u8]) -> Result<([u8; 4], &[u8]), PrecompileFailure> {
+	if input.len() < 4 {
+		return Err(revert("input is missing a function selector"))
+	}
+	let mut selector = [0u8; 4];
+	selector.copy_from_slice(&input[..4]);
+	Ok((selector, &input[4..]))
+}
+
+/// Returns the ABI encoded 32 byte word at position `index` of `args`.
+fn word(args: &[u8], index: usize) -> Result<&[u8], PrecompileFailure> {
+	args.get(index * 32..(index + 1) * 32).ok_or_else(|| revert("input is too short"))
+}
+
+fn decode_u256(args: &[u8], index: usize) -> Result<U256, PrecompileFailure> {
+	word(args, index).map(U256::from_big_endian)
+}
+
+fn decode_account_id(args: &[u8], index: usize) -> Result<AccountId, PrecompileFailure> {
+	let mut raw = [0u8; 32];
+	raw.copy_from_slice(word(args, index)?);
+	Ok(AccountId::from(raw))
+}
+
+fn encode_u256(value: U256) -> Vec<u8> {
+	let mut output = vec![0u8; 32];
+	value.to_big_endian(&mut output);
+	output
+}
+
+fn succeed(output: Vec<u8>) -> PrecompileOutput {
+	PrecompileOutput { exit_status: ExitSucceed::Returned, output }
+}
+
+fn revert(message: &str) -> PrecompileFailure {
+	PrecompileFailure::Revert { exit_status: ExitRevert::Reverted, output: message.into() }
+}
+
+const fn precompile_address(a: u64) -> H160 {
+	let b = a.to_be_bytes();
+	H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+}
@@ -2,6 +2,12 @@
 // `construct_runtime!` does a lot of recursion and requires us to increase the limit to 256.
 #![recursion_limit = "256"]
 
+#[cfg(all(feature = "babe", feature = "pow"))]
+compile_error!("The `babe` and `pow` features both replace Aura, enable only one of them.");
+
+#[cfg(all(feature = "npos", any(feature = "babe", feature = "pow")))]
+compile_error!("`npos` elects the Aura authorities and can't be combined with `babe` or `pow`.");
+
 // Make the WASM binary available.
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
@@ -10,12 +16,16 @@ use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
 use sp_api::impl_runtime_apis;
+#[cfg(not(any(feature = "babe", feature = "pow")))]
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	traits::{
+		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, StaticLookup,
+		Verify,
+	},
+	transaction_validity::{TransactionSource, TransactionValidity, TransactionValidityError},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -27,7 +37,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		AsEnsureOriginWithArg, ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem,
+		Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
@@ -36,9 +47,13 @@ pub use frame_support::{
 	StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+pub use pallet_assets::Call as AssetsCall;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_contracts::Call as ContractsCall;
+pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::CurrencyAdapter;
+pub use pallet_uniques::Call as UniquesCall;
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
@@ -46,6 +61,34 @@ pub use sp_runtime::{Perbill, Permill};
 /// Import the template pallet.
 pub use pallet_template;
 
+/// Import the priority pallet.
+pub use pallet_priority;
+
+/// Import the feeless pallet.
+pub use pallet_feeless;
+
+/// Import the asset rate pallet.
+pub use pallet_asset_rate;
+
+pub mod apis;
+#[cfg(feature = "babe")]
+pub mod babe;
+pub mod chain_extension;
+#[cfg(feature = "evm")]
+pub mod evm;
+#[cfg(feature = "npos")]
+pub mod npos;
+#[cfg(feature = "pow")]
+pub mod pow;
+
+// The runtime tests build Aura blocks with a fixed authority set.
+#[cfg(all(test, not(any(feature = "babe", feature = "pow", feature = "npos"))))]
+mod mock;
+#[cfg(all(test, not(any(feature = "babe", feature = "pow", feature = "npos"))))]
+mod tests;
+#[cfg(feature = "npos")]
+use pallet_session::historical as pallet_session_historical;
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -59,6 +102,15 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset of `pallet_assets`.
+pub type AssetId = u32;
+
+/// Identifier of a collection of `pallet_uniques`.
+pub type CollectionId = u32;
+
+/// Identifier of an item within a collection of `pallet_uniques`.
+pub type ItemId = u32;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -81,12 +133,28 @@ pub mod opaque {
 	/// Opaque block identifier type.
 	pub type BlockId = generic::BlockId<Block>;
 
+	#[cfg(not(any(feature = "babe", feature = "pow")))]
 	impl_opaque_keys! {
 		pub struct SessionKeys {
 			pub aura: Aura,
 			pub grandpa: Grandpa,
 		}
 	}
+
+	#[cfg(feature = "babe")]
+	impl_opaque_keys! {
+		pub struct SessionKeys {
+			pub babe: Babe,
+			pub grandpa: Grandpa,
+		}
+	}
+
+	#[cfg(feature = "pow")]
+	impl_opaque_keys! {
+		pub struct SessionKeys {
+			pub grandpa: Grandpa,
+		}
+	}
 }
 
 // To learn more about runtime versioning and what each of the following value means:
@@ -101,10 +169,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 111,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 4,
 	state_version: 1,
 };
 
@@ -185,8 +253,8 @@ impl frame_system::Config for Runtime {
 	type PalletInfo = PalletInfo;
 	/// What to do if a new account is created.
 	type OnNewAccount = ();
-	/// What to do if an account is fully reaped from the system.
-	type OnKilledAccount = ();
+	/// What to do if an account is fully reaped from the system: forget its free calls.
+	type OnKilledAccount = Feeless;
 	/// The data to be stored in an account.
 	type AccountData = pallet_balances::AccountData<Balance>;
 	/// Weight information for the extrinsics of this pallet.
@@ -200,9 +268,14 @@ impl frame_system::Config for Runtime {
 
 impl pallet_randomness_collective_flip::Config for Runtime {}
 
+#[cfg(not(any(feature = "babe", feature = "pow")))]
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
+	#[cfg(not(feature = "npos"))]
 	type DisabledValidators = ();
+	/// Validators disabled for an offence stop authoring until the next era.
+	#[cfg(feature = "npos")]
+	type DisabledValidators = Session;
 	type MaxAuthorities = ConstU32<32>;
 }
 
@@ -210,7 +283,12 @@ impl pallet_grandpa::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 
+	#[cfg(not(feature = "npos"))]
 	type KeyOwnerProofSystem = ();
+	/// Keys are proven to belong to a validator of a past session by the historical session
+	/// pallet.
+	#[cfg(feature = "npos")]
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -220,7 +298,15 @@ impl pallet_grandpa::Config for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
+	#[cfg(not(feature = "npos"))]
 	type HandleEquivocation = ();
+	/// Equivocations are reported as offences, for which the staking pallet slashes the offender.
+	#[cfg(feature = "npos")]
+	type HandleEquivocation = pallet_grandpa::EquivocationHandler<
+		Self::KeyOwnerIdentification,
+		Offences,
+		npos::ReportLongevity,
+	>;
 
 	type WeightInfo = ();
 	type MaxAuthorities = ConstU32<32>;
@@ -229,8 +315,17 @@ impl pallet_grandpa::Config for Runtime {
 impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
 	type Moment = u64;
+	#[cfg(not(any(feature = "babe", feature = "pow")))]
 	type OnTimestampSet = Aura;
+	#[cfg(feature = "babe")]
+	type OnTimestampSet = Babe;
+	#[cfg(feature = "pow")]
+	type OnTimestampSet = ();
+	#[cfg(not(feature = "pow"))]
 	type MinimumPeriod = ConstU64<{ SLOT_DURATION / 2 }>;
+	/// Mined blocks aren't bound to slots and may follow each other within a second.
+	#[cfg(feature = "pow")]
+	type MinimumPeriod = ConstU64<1>;
 	type WeightInfo = ();
 }
 
@@ -267,25 +362,225 @@ impl pallet_template::Config for Runtime {
 	type Event = Event;
 }
 
+/// Transaction pool priority of calls, adjusted by `PrioritizeCalls` in `SignedExtra`.
+impl pallet_priority::Config for Runtime {
+	type Event = Event;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxNameLen = ConstU32<64>;
+}
+
+/// Fees waived by `CheckFeeless` in `SignedExtra`.
+impl pallet_feeless::Config for Runtime {
+	type Event = Event;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxNameLen = ConstU32<64>;
+	/// Feeless calls are rate limited per day.
+	type RateLimitPeriod = ConstU32<DAYS>;
+	/// Free calls cost nothing but the deposit of their signer, so those of a block are limited.
+	type MaxFreeCallsPerBlock = ConstU32<100>;
+}
+
+/// Storage deposit charged by the contracts pallet for `items` storage items of `bytes` bytes.
+const fn deposit(items: u32, bytes: u32) -> Balance {
+	items as Balance * 1_000_000_000 + (bytes as Balance) * 10_000_000
+}
+
+/// Whether the contracts dry-run RPC returns the debug buffer of the call.
+const CONTRACTS_DEBUG_OUTPUT: bool = true;
+
+parameter_types! {
+	pub const DepositPerItem: Balance = deposit(1, 0);
+	pub const DepositPerByte: Balance = deposit(0, 1);
+	/// Up to 10% of a block may be spent on removing the storage of terminated contracts.
+	pub DeletionWeightLimit: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+}
+
+impl pallet_contracts::Config for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type Event = Event;
+	type Call = Call;
+	/// Contracts may not dispatch any runtime calls.
+	type CallFilter = frame_support::traits::Nothing;
+	type DepositPerItem = DepositPerItem;
+	type DepositPerByte = DepositPerByte;
+	type CallStack = [pallet_contracts::Frame<Self>; 31];
+	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
+	type ChainExtension = chain_extension::TemplateExtension;
+	type DeletionQueueDepth = ConstU32<128>;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type Schedule = Schedule;
+	type AddressGenerator = pallet_contracts::DefaultAddressGenerator;
+	type ContractAccessWeight = pallet_contracts::DefaultContractAccessWeight<BlockWeights>;
+	type MaxCodeLen = ConstU32<{ 128 * 1024 }>;
+	type RelaxedMaxCodeLen = ConstU32<{ 256 * 1024 }>;
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDeposit = ConstU128<{ deposit(1, 190) }>;
+	type AssetAccountDeposit = ConstU128<{ deposit(1, 16) }>;
+	type MetadataDepositBase = ConstU128<{ deposit(1, 68) }>;
+	type MetadataDepositPerByte = ConstU128<{ deposit(0, 1) }>;
+	type ApprovalDeposit = ConstU128<{ deposit(1, 0) }>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_asset_rate::Config for Runtime {
+	type Event = Event;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetId = AssetId;
+}
+
+/// Lets `ChargeAssetTxPayment` in `SignedExtra` charge fees in assets.
+impl pallet_asset_tx_payment::Config for Runtime {
+	type Fungibles = Assets;
+	/// Fees are converted at the rates of `pallet_asset_rate` and burned, like native fees.
+	type OnChargeAssetTransaction = pallet_asset_tx_payment::FungiblesAdapter<AssetRate, ()>;
+}
+
+impl pallet_uniques::Config for Runtime {
+	type Event = Event;
+	type CollectionId = CollectionId;
+	type ItemId = ItemId;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	/// Any account can create collections, by reserving the `CollectionDeposit`.
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+	type Locker = ();
+	type CollectionDeposit = ConstU128<{ deposit(1, 130) }>;
+	type ItemDeposit = ConstU128<{ deposit(1, 164) }>;
+	type MetadataDepositBase = ConstU128<{ deposit(1, 129) }>;
+	type AttributeDepositBase = ConstU128<{ deposit(1, 0) }>;
+	type DepositPerByte = ConstU128<{ deposit(0, 1) }>;
+	type StringLimit = ConstU32<128>;
+	type KeyLimit = ConstU32<32>;
+	type ValueLimit = ConstU32<64>;
+	type WeightInfo = pallet_uniques::weights::SubstrateWeight<Runtime>;
+}
+
+// `construct_runtime!` doesn't accept `#[cfg]` attributes on pallets, so pallets that are only
+// part of some builds are appended by the `with_*_pallets!` macros below. Each of them adds its
+// pallets (if its feature is enabled) and forwards the list to the next macro named in `[..]`,
+// ending with `construct_node_runtime!`. `with_consensus_pallets!` comes first and takes the list
+// in two parts, to keep the consensus pallet at the same index in every build.
+macro_rules! construct_node_runtime {
+	([] { $($pallets:tt)* }) => {
+		construct_runtime!(
+			pub enum Runtime where
+				Block = Block,
+				NodeBlock = opaque::Block,
+				UncheckedExtrinsic = UncheckedExtrinsic
+			{
+				$($pallets)*
+			}
+		);
+	};
+}
+
+#[cfg(not(any(feature = "babe", feature = "pow")))]
+macro_rules! with_consensus_pallets {
+	([$next:ident $(, $rest:ident)*] { $($before:tt)* } { $($after:tt)* }) => {
+		$next!([$($rest),*] {
+			$($before)*
+			Aura: pallet_aura,
+			$($after)*
+		});
+	};
+}
+
+#[cfg(feature = "babe")]
+macro_rules! with_consensus_pallets {
+	([$next:ident $(, $rest:ident)*] { $($before:tt)* } { $($after:tt)* }) => {
+		$next!([$($rest),*] {
+			$($before)*
+			Babe: pallet_babe,
+			$($after)*
+		});
+	};
+}
+
+#[cfg(feature = "pow")]
+macro_rules! with_consensus_pallets {
+	([$next:ident $(, $rest:ident)*] { $($before:tt)* } { $($after:tt)* }) => {
+		$next!([$($rest),*] {
+			$($before)*
+			Difficulty: pallet_difficulty,
+			$($after)*
+		});
+	};
+}
+
+#[cfg(feature = "npos")]
+macro_rules! with_staking_pallets {
+	([$next:ident $(, $rest:ident)*] { $($pallets:tt)* }) => {
+		$next!([$($rest),*] {
+			$($pallets)*
+			Authorship: pallet_authorship,
+			// Staking comes before Session, whose genesis asks it for the first validator set.
+			Staking: pallet_staking,
+			Session: pallet_session,
+			Historical: pallet_session_historical::{Pallet},
+			Offences: pallet_offences,
+		});
+	};
+}
+
+#[cfg(not(feature = "npos"))]
+macro_rules! with_staking_pallets {
+	([$next:ident $(, $rest:ident)*] { $($pallets:tt)* }) => {
+		$next!([$($rest),*] { $($pallets)* });
+	};
+}
+
+#[cfg(feature = "evm")]
+macro_rules! with_evm_pallets {
+	([$next:ident $(, $rest:ident)*] { $($pallets:tt)* }) => {
+		$next!([$($rest),*] {
+			$($pallets)*
+			EVM: pallet_evm,
+			Ethereum: pallet_ethereum,
+		});
+	};
+}
+
+#[cfg(not(feature = "evm"))]
+macro_rules! with_evm_pallets {
+	([$next:ident $(, $rest:ident)*] { $($pallets:tt)* }) => {
+		$next!([$($rest),*] { $($pallets)* });
+	};
+}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
-construct_runtime!(
-	pub enum Runtime where
-		Block = Block,
-		NodeBlock = opaque::Block,
-		UncheckedExtrinsic = UncheckedExtrinsic
-	{
-		System: frame_system,
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip,
-		Timestamp: pallet_timestamp,
-		Aura: pallet_aura,
-		Grandpa: pallet_grandpa,
-		Balances: pallet_balances,
-		TransactionPayment: pallet_transaction_payment,
-		Sudo: pallet_sudo,
-		// Include the custom logic from the pallet-template in the runtime.
-		TemplateModule: pallet_template,
-	}
-);
+with_consensus_pallets!([with_staking_pallets, with_evm_pallets, construct_node_runtime] {
+	System: frame_system,
+	RandomnessCollectiveFlip: pallet_randomness_collective_flip,
+	Timestamp: pallet_timestamp,
+} {
+	Grandpa: pallet_grandpa,
+	Balances: pallet_balances,
+	TransactionPayment: pallet_transaction_payment,
+	Sudo: pallet_sudo,
+	// Include the custom logic from the pallet-template in the runtime.
+	TemplateModule: pallet_template,
+	Contracts: pallet_contracts,
+	Priority: pallet_priority,
+	Feeless: pallet_feeless,
+	Assets: pallet_assets,
+	AssetRate: pallet_asset_rate,
+	AssetTxPayment: pallet_asset_tx_payment,
+	Uniques: pallet_uniques,
+});
 
 /// The address format for describing accounts.
 pub type Address = sp_runtime::MultiAddress<AccountId, ()>;
@@ -302,10 +597,20 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_feeless::CheckFeeless<
+		Runtime,
+		pallet_asset_tx_payment::ChargeAssetTxPayment<Runtime>,
+	>,
+	pallet_priority::PrioritizeCalls<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
+#[cfg(not(feature = "evm"))]
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// Unchecked extrinsic type as expected by this runtime. Ethereum transactions are accepted as
+/// self-contained unsigned extrinsics carrying their own signature.
+#[cfg(feature = "evm")]
+pub type UncheckedExtrinsic =
+	fp_self_contained::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Executive: handles dispatch to the various modules.
@@ -327,11 +632,35 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_assets, Assets]
 		[pallet_timestamp, Timestamp]
+		[pallet_uniques, Uniques]
 		[pallet_template, TemplateModule]
+		[pallet_contracts, Contracts]
 	);
 }
 
+/// Returns the signer and the call of `tx`, unless it is unsigned.
+fn signed_call(tx: &UncheckedExtrinsic) -> Option<(AccountId, &Call)> {
+	#[cfg(feature = "evm")]
+	let tx = &tx.0;
+	let (address, _, _) = tx.signature.as_ref()?;
+	let signer = <Runtime as frame_system::Config>::Lookup::lookup(address.clone()).ok()?;
+	Some((signer, &tx.function))
+}
+
+/// Returns the asset `tx` pays its fee in, unless it pays it in the native balance.
+fn fee_asset(tx: &UncheckedExtrinsic) -> Option<AssetId> {
+	#[cfg(feature = "evm")]
+	let tx = &tx.0;
+	let (_, _, extra) = tx.signature.as_ref()?;
+	let charge: &pallet_feeless::CheckFeeless<
+		Runtime,
+		pallet_asset_tx_payment::ChargeAssetTxPayment<Runtime>,
+	> = &extra.7;
+	charge.fee_asset()
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -390,6 +719,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[cfg(not(any(feature = "babe", feature = "pow")))]
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
 		fn slot_duration() -> sp_consensus_aura::SlotDuration {
 			sp_consensus_aura::SlotDuration::from_millis(Aura::slot_duration())
@@ -400,6 +730,55 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[cfg(feature = "babe")]
+	impl sp_consensus_babe::BabeApi<Block> for Runtime {
+		fn configuration() -> sp_consensus_babe::BabeGenesisConfiguration {
+			let epoch_config = Babe::epoch_config().unwrap_or(babe::BABE_GENESIS_EPOCH_CONFIG);
+			sp_consensus_babe::BabeGenesisConfiguration {
+				slot_duration: Babe::slot_duration(),
+				epoch_length: babe::EpochDuration::get(),
+				c: epoch_config.c,
+				genesis_authorities: Babe::authorities().to_vec(),
+				randomness: Babe::randomness(),
+				allowed_slots: epoch_config.allowed_slots,
+			}
+		}
+
+		fn current_epoch_start() -> sp_consensus_babe::Slot {
+			Babe::current_epoch_start()
+		}
+
+		fn current_epoch() -> sp_consensus_babe::Epoch {
+			Babe::current_epoch()
+		}
+
+		fn next_epoch() -> sp_consensus_babe::Epoch {
+			Babe::next_epoch()
+		}
+
+		fn generate_key_ownership_proof(
+			_slot: sp_consensus_babe::Slot,
+			_authority_id: babe::BabeId,
+		) -> Option<sp_consensus_babe::OpaqueKeyOwnershipProof> {
+			// NOTE: like GRANDPA's, the key owner proof type is a bottom type.
+			None
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			_equivocation_proof: sp_consensus_babe::EquivocationProof<<Block as BlockT>::Header>,
+			_key_owner_proof: sp_consensus_babe::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			None
+		}
+	}
+
+	#[cfg(feature = "pow")]
+	impl sp_consensus_pow::DifficultyApi<Block, sp_core::U256> for Runtime {
+		fn difficulty() -> sp_core::U256 {
+			Difficulty::difficulty()
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			opaque::SessionKeys::generate(seed)
@@ -422,23 +801,44 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
+			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+			key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			#[cfg(feature = "npos")]
+			{
+				let key_owner_proof = key_owner_proof.decode()?;
+				Grandpa::submit_unsigned_equivocation_report(equivocation_proof, key_owner_proof)
+			}
+			#[cfg(not(feature = "npos"))]
+			{
+				let _ = (equivocation_proof, key_owner_proof);
+				None
+			}
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			#[cfg(feature = "npos")]
+			{
+				use codec::Encode;
+
+				Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+					.map(|proof| proof.encode())
+					.map(fg_primitives::OpaqueKeyOwnershipProof::new)
+			}
+			// NOTE: without the `npos` feature this is the only implementation possible since
+			// we've defined our key owner proof type as a bottom type (i.e. a type with no
+			// values).
+			#[cfg(not(feature = "npos"))]
+			{
+				let _ = authority_id;
+				None
+			}
 		}
 	}
 
@@ -453,7 +853,18 @@ impl_runtime_apis! {
 			uxt: <Block as BlockT>::Extrinsic,
 			len: u32,
 		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
-			TransactionPayment::query_info(uxt, len)
+			use frame_support::traits::tokens::BalanceConversion;
+
+			let asset_id = fee_asset(&uxt);
+			let mut info = TransactionPayment::query_info(uxt, len);
+			// Report the fee in the asset it is paid in. Without rate the transaction is invalid
+			// anyway, and the native fee is reported.
+			if let Some(asset_id) = asset_id {
+				if let Ok(fee) = AssetRate::to_asset_balance(info.partial_fee, asset_id) {
+					info.partial_fee = fee;
+				}
+			}
+			info
 		}
 		fn query_fee_details(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -463,6 +874,304 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash>
+		for Runtime
+	{
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractExecResult<Balance> {
+			Contracts::bare_call(
+				origin,
+				dest,
+				value,
+				gas_limit,
+				storage_deposit_limit,
+				input_data,
+				CONTRACTS_DEBUG_OUTPUT,
+			)
+		}
+
+		fn instantiate(
+			origin: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			code: pallet_contracts_primitives::Code<Hash>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, Balance> {
+			Contracts::bare_instantiate(
+				origin,
+				value,
+				gas_limit,
+				storage_deposit_limit,
+				code,
+				data,
+				salt,
+				CONTRACTS_DEBUG_OUTPUT,
+			)
+		}
+
+		fn upload_code(
+			origin: AccountId,
+			code: Vec<u8>,
+			storage_deposit_limit: Option<Balance>,
+		) -> pallet_contracts_primitives::CodeUploadResult<Hash, Balance> {
+			Contracts::bare_upload_code(origin, code, storage_deposit_limit)
+		}
+
+		fn get_storage(
+			address: AccountId,
+			key: [u8; 32],
+		) -> pallet_contracts_primitives::GetStorageResult {
+			Contracts::get_storage(address, key)
+		}
+	}
+
+	impl apis::DryRunApi<Block> for Runtime {
+		fn apply_as(signer: AccountId, call: Call) -> apis::DryRunOutcome {
+			use frame_support::{dispatch::Dispatchable, weights::GetDispatchInfo};
+
+			let info = call.get_dispatch_info();
+			let (result, post_info) = match call.dispatch(Origin::signed(signer)) {
+				Ok(post_info) => (Ok(()), post_info),
+				Err(e) => (Err(e.error), e.post_info),
+			};
+			apis::DryRunOutcome { result, weight: post_info.calc_actual_weight(&info) }
+		}
+
+		fn events() -> Vec<frame_system::EventRecord<Event, Hash>> {
+			System::events()
+		}
+	}
+
+	impl apis::PriorityApi<Block> for Runtime {
+		fn effective_priority(
+			source: TransactionSource,
+			tx: <Block as BlockT>::Extrinsic,
+			block_hash: <Block as BlockT>::Hash,
+		) -> Result<apis::EffectivePriority, TransactionValidityError> {
+			use frame_support::traits::GetCallMetadata;
+
+			let boost = signed_call(&tx).map_or(0, |(signer, call)| {
+				Priority::adjustment(&signer, &call.get_call_metadata()).priority
+			});
+			let validity = Executive::validate_transaction(source, tx, block_hash)?;
+			Ok(apis::EffectivePriority {
+				priority: validity.priority,
+				boost,
+				longevity: validity.longevity,
+			})
+		}
+	}
+
+	impl apis::AssetsApi<Block> for Runtime {
+		fn account_balances(account: AccountId) -> Vec<apis::AssetBalance> {
+			use frame_support::{
+				storage::migration::storage_key_iter, traits::tokens::fungibles::InspectMetadata,
+				Blake2_128Concat,
+			};
+			use sp_runtime::traits::Zero;
+
+			// `pallet_assets` doesn't expose its storage items, so its assets are listed by the
+			// keys of its `Asset` map, and the accounts are looked up asset by asset.
+			type Details = pallet_assets::AssetDetails<Balance, AccountId, Balance>;
+			let assets = storage_key_iter::<AssetId, Details, Blake2_128Concat>(b"Assets", b"Asset")
+				.map(|(asset_id, _)| asset_id);
+			let mut balances: Vec<_> = assets
+				.filter_map(|asset_id| {
+					let balance = Assets::balance(asset_id, &account);
+					(!balance.is_zero()).then(|| apis::AssetBalance {
+						asset_id,
+						balance,
+						name: <Assets as InspectMetadata<AccountId>>::name(&asset_id),
+						symbol: <Assets as InspectMetadata<AccountId>>::symbol(&asset_id),
+						decimals: <Assets as InspectMetadata<AccountId>>::decimals(&asset_id),
+					})
+				})
+				.collect();
+			// The map is ordered by the hashes of the asset ids.
+			balances.sort_by_key(|balance| balance.asset_id);
+			balances
+		}
+	}
+
+	impl apis::UniquesApi<Block> for Runtime {
+		fn owned_items(account: AccountId) -> Vec<(CollectionId, ItemId)> {
+			use frame_support::traits::tokens::nonfungibles::InspectEnumerable;
+
+			let mut items: Vec<_> = Uniques::owned(&account).collect();
+			// The items are ordered by the hashes of their ids.
+			items.sort();
+			items
+		}
+	}
+
+	impl apis::TemplateApi<Block> for Runtime {
+		fn check_invariants() -> Result<(), sp_runtime::RuntimeString> {
+			TemplateModule::check_invariants().map_err(Into::into)
+		}
+	}
+
+	impl apis::UpgradeApi<Block> for Runtime {
+		fn set_code(code: Vec<u8>) -> Result<(), sp_runtime::RuntimeString> {
+			System::set_code(frame_system::RawOrigin::Root.into(), code).map(|_| ()).map_err(|e| {
+				let name: &'static str = e.error.into();
+				name.into()
+			})
+		}
+	}
+
+	#[cfg(feature = "evm")]
+	impl fp_rpc::EthereumRuntimeRPCApi<Block> for Runtime {
+		fn chain_id() -> u64 {
+			evm::CHAIN_ID
+		}
+
+		fn account_basic(address: sp_core::H160) -> pallet_evm::Account {
+			let (account, _) = EVM::account_basic(&address);
+			account
+		}
+
+		fn gas_price() -> sp_core::U256 {
+			use pallet_evm::FeeCalculator;
+
+			let (gas_price, _) = <Runtime as pallet_evm::Config>::FeeCalculator::min_gas_price();
+			gas_price
+		}
+
+		fn account_code_at(address: sp_core::H160) -> Vec<u8> {
+			EVM::account_codes(address)
+		}
+
+		fn author() -> sp_core::H160 {
+			<pallet_evm::Pallet<Runtime>>::find_author()
+		}
+
+		fn storage_at(address: sp_core::H160, index: sp_core::U256) -> sp_core::H256 {
+			let mut tmp = [0u8; 32];
+			index.to_big_endian(&mut tmp);
+			EVM::account_storages(address, sp_core::H256::from_slice(&tmp[..]))
+		}
+
+		fn call(
+			from: sp_core::H160,
+			to: sp_core::H160,
+			data: Vec<u8>,
+			value: sp_core::U256,
+			gas_limit: sp_core::U256,
+			max_fee_per_gas: Option<sp_core::U256>,
+			max_priority_fee_per_gas: Option<sp_core::U256>,
+			nonce: Option<sp_core::U256>,
+			estimate: bool,
+			access_list: Option<Vec<(sp_core::H160, Vec<sp_core::H256>)>>,
+		) -> Result<pallet_evm::CallInfo, sp_runtime::DispatchError> {
+			use pallet_evm::Runner;
+			use sp_runtime::traits::UniqueSaturatedInto;
+
+			let mut config = <Runtime as pallet_evm::Config>::config().clone();
+			config.estimate = estimate;
+
+			<Runtime as pallet_evm::Config>::Runner::call(
+				from,
+				to,
+				data,
+				value,
+				gas_limit.unique_saturated_into(),
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+				nonce,
+				access_list.unwrap_or_default(),
+				false,
+				&config,
+			)
+			.map_err(|err| err.error.into())
+		}
+
+		fn create(
+			from: sp_core::H160,
+			data: Vec<u8>,
+			value: sp_core::U256,
+			gas_limit: sp_core::U256,
+			max_fee_per_gas: Option<sp_core::U256>,
+			max_priority_fee_per_gas: Option<sp_core::U256>,
+			nonce: Option<sp_core::U256>,
+			estimate: bool,
+			access_list: Option<Vec<(sp_core::H160, Vec<sp_core::H256>)>>,
+		) -> Result<pallet_evm::CreateInfo, sp_runtime::DispatchError> {
+			use pallet_evm::Runner;
+			use sp_runtime::traits::UniqueSaturatedInto;
+
+			let mut config = <Runtime as pallet_evm::Config>::config().clone();
+			config.estimate = estimate;
+
+			<Runtime as pallet_evm::Config>::Runner::create(
+				from,
+				data,
+				value,
+				gas_limit.unique_saturated_into(),
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+				nonce,
+				access_list.unwrap_or_default(),
+				false,
+				&config,
+			)
+			.map_err(|err| err.error.into())
+		}
+
+		fn current_transaction_statuses() -> Option<Vec<fp_rpc::TransactionStatus>> {
+			Ethereum::current_transaction_statuses()
+		}
+
+		fn current_block() -> Option<pallet_ethereum::Block> {
+			Ethereum::current_block()
+		}
+
+		fn current_receipts() -> Option<Vec<pallet_ethereum::Receipt>> {
+			Ethereum::current_receipts()
+		}
+
+		fn current_all() -> (
+			Option<pallet_ethereum::Block>,
+			Option<Vec<pallet_ethereum::Receipt>>,
+			Option<Vec<fp_rpc::TransactionStatus>>,
+		) {
+			(
+				Ethereum::current_block(),
+				Ethereum::current_receipts(),
+				Ethereum::current_transaction_statuses(),
+			)
+		}
+
+		fn extrinsic_filter(
+			xts: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Vec<pallet_ethereum::Transaction> {
+			evm::ethereum_transactions(xts)
+		}
+
+		fn elasticity() -> Option<Permill> {
+			// The gas price is fixed, there is no EIP-1559 base fee to adjust.
+			None
+		}
+	}
+
+	#[cfg(feature = "evm")]
+	impl fp_rpc::ConvertTransactionRuntimeApi<Block> for Runtime {
+		fn convert_transaction(
+			transaction: pallet_ethereum::Transaction,
+		) -> <Block as BlockT>::Extrinsic {
+			UncheckedExtrinsic::new_unsigned(
+				pallet_ethereum::Call::<Runtime>::transact { transaction }.into(),
+			)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -0,0 +1,140 @@
+//! Test externalities of the whole runtime, built from a `GenesisConfig` and advanced block by
+//! block through `Executive`, the way an Aura author builds blocks.
+
+use super::*;
+use codec::Encode;
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_core::{ed25519, Pair};
+use sp_keyring::AccountKeyring;
+use sp_runtime::{
+	generic::Era,
+	traits::Header as _,
+	Digest, DigestItem, DispatchResult,
+};
+
+/// Balance of the accounts endowed by default.
+pub const ENDOWMENT: Balance = 1 << 60;
+
+/// Builds the externalities of a chain, with the block after genesis initialized.
+pub struct ExtBuilder {
+	balances: Vec<(AccountId, Balance)>,
+	authorities: Vec<AccountKeyring>,
+	sudo: Option<AccountId>,
+}
+
+impl Default for ExtBuilder {
+	/// Alice, Bob and Charlie endowed with [`ENDOWMENT`], Alice authoring the blocks and holding
+	/// the sudo key.
+	fn default() -> Self {
+		let endowed = [AccountKeyring::Alice, AccountKeyring::Bob, AccountKeyring::Charlie];
+		Self {
+			balances: endowed.iter().map(|who| (who.to_account_id(), ENDOWMENT)).collect(),
+			authorities: vec![AccountKeyring::Alice],
+			sudo: Some(AccountKeyring::Alice.to_account_id()),
+		}
+	}
+}
+
+impl ExtBuilder {
+	/// Replaces the endowed accounts and their balances.
+	pub fn balances(mut self, balances: Vec<(AccountId, Balance)>) -> Self {
+		self.balances = balances;
+		self
+	}
+
+	/// Replaces the Aura and GRANDPA authorities by those of the given development accounts.
+	pub fn authorities(mut self, authorities: Vec<AccountKeyring>) -> Self {
+		self.authorities = authorities;
+		self
+	}
+
+	/// Replaces the sudo key, `None` leaving the chain without one.
+	pub fn sudo(mut self, key: Option<AccountId>) -> Self {
+		self.sudo = key;
+		self
+	}
+
+	/// Builds the externalities from the genesis config, with block 1 initialized.
+	pub fn build(self) -> sp_io::TestExternalities {
+		let grandpa_id = |authority: &AccountKeyring| -> GrandpaId {
+			ed25519::Pair::from_string(&authority.to_seed(), None)
+				.expect("development seeds are valid")
+				.public()
+				.into()
+		};
+		let genesis = GenesisConfig {
+			balances: BalancesConfig { balances: self.balances },
+			aura: AuraConfig {
+				authorities: self.authorities.iter().map(|a| a.public().into()).collect(),
+			},
+			grandpa: GrandpaConfig {
+				authorities: self.authorities.iter().map(|a| (grandpa_id(a), 1)).collect(),
+			},
+			sudo: SudoConfig { key: self.sudo },
+			..Default::default()
+		};
+
+		let storage = genesis.build_storage().expect("the genesis config is valid");
+		let mut ext = sp_io::TestExternalities::new(storage);
+		ext.execute_with(|| initialize_block(1, System::block_hash(0)));
+		ext
+	}
+}
+
+/// Finalizes the current block and initializes the next one.
+pub fn next_block() {
+	let header = Executive::finalize_block();
+	initialize_block(header.number() + 1, header.hash());
+}
+
+/// Finalizes blocks until block `number` is initialized.
+pub fn run_to_block(number: BlockNumber) {
+	while System::block_number() < number {
+		next_block();
+	}
+}
+
+/// Initializes block `number` in the slot of the same number, and applies the timestamp inherent
+/// of the slot's start.
+fn initialize_block(number: BlockNumber, parent_hash: Hash) {
+	let slot = Slot::from(u64::from(number));
+	let digest = Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] };
+	Executive::initialize_block(&Header::new(
+		number,
+		Default::default(),
+		Default::default(),
+		parent_hash,
+		digest,
+	));
+
+	let timestamp = TimestampCall::set { now: *slot * SLOT_DURATION };
+	apply(UncheckedExtrinsic::new_unsigned(timestamp.into())).expect("the timestamp is set");
+}
+
+/// Signs `call` by `signer` with its next nonce and an immortal era.
+pub fn sign(signer: AccountKeyring, call: impl Into<Call>) -> UncheckedExtrinsic {
+	let account = signer.to_account_id();
+	let extra: SignedExtra = (
+		frame_system::CheckNonZeroSender::new(),
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(Era::Immortal),
+		frame_system::CheckNonce::from(System::account_nonce(&account)),
+		frame_system::CheckWeight::new(),
+		pallet_feeless::CheckFeeless::from(
+			pallet_asset_tx_payment::ChargeAssetTxPayment::from(0, None),
+		),
+		pallet_priority::PrioritizeCalls::new(),
+	);
+	let payload = SignedPayload::new(call.into(), extra).expect("the signed extensions are valid");
+	let signature = payload.using_encoded(|payload| signer.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	UncheckedExtrinsic::new_signed(call, account.into(), signature.into(), extra)
+}
+
+/// Applies `extrinsic` to the current block and returns the result of its dispatch. Panics if the
+/// extrinsic is invalid.
+pub fn apply(extrinsic: UncheckedExtrinsic) -> DispatchResult {
+	Executive::apply_extrinsic(extrinsic).expect("the extrinsic is valid")
+}
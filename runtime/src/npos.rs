@@ -0,0 +1,129 @@
+//! Configuration of nominated proof-of-stake: validators are elected by stake at the start of
+//! every era, get session keys through `pallet_session` and are slashed for the equivocations
+//! GRANDPA reports.
+//!
+//! Only compiled with the `npos` feature. Aura keeps authoring the blocks, but its authorities and
+//! GRANDPA's are the validators of the current session instead of a fixed set. Aura has no
+//! equivocation reporting, so only GRANDPA offences are slashed.
+
+use super::*;
+
+use frame_election_provider_support::{onchain, SequentialPhragmen};
+use sp_runtime::{curve::PiecewiseLinear, traits::OpaqueKeys};
+
+pub use pallet_staking::StakerStatus;
+
+/// Number of blocks in a session.
+pub const SESSION_PERIOD: BlockNumber = 10 * MINUTES;
+
+/// Number of sessions in an era. Validators are elected once per era.
+pub const SESSIONS_PER_ERA: sp_staking::SessionIndex = 6;
+
+pallet_staking_reward_curve::build! {
+	const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
+		min_inflation: 0_025_000,
+		max_inflation: 0_100_000,
+		ideal_stake: 0_500_000,
+		falloff: 0_050_000,
+		max_piece_count: 40,
+		test_precision: 0_005_000,
+	);
+}
+
+parameter_types! {
+	pub const SessionPeriod: BlockNumber = SESSION_PERIOD;
+	pub const SessionOffset: BlockNumber = 0;
+	pub const SessionsPerEra: sp_staking::SessionIndex = SESSIONS_PER_ERA;
+	/// Eras funds stay bonded for after being unbonded, a day.
+	pub const BondingDuration: sp_staking::EraIndex = 24;
+	/// Eras slashes wait for before being applied, so that root can cancel them.
+	pub const SlashDeferDuration: sp_staking::EraIndex = 6;
+	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
+	pub OffendingValidatorsThreshold: Perbill = Perbill::from_percent(17);
+	/// Equivocation reports stay valid for as long as the offender could still be slashed.
+	pub const ReportLongevity: u64 =
+		BondingDuration::get() as u64 * SessionsPerEra::get() as u64 * SESSION_PERIOD as u64;
+}
+
+impl pallet_authorship::Config for Runtime {
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+	type UncleGenerations = ConstU32<0>;
+	type FilterUncle = ();
+	/// Block authors earn era points, which weight the reward payouts.
+	type EventHandler = Staking;
+}
+
+impl pallet_session::Config for Runtime {
+	type Event = Event;
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = pallet_staking::StashOf<Self>;
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, Staking>;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
+}
+
+/// Keeps the validator sets of past sessions, to prove the ownership of keys in offence reports.
+impl pallet_session::historical::Config for Runtime {
+	type FullIdentification = pallet_staking::Exposure<AccountId, Balance>;
+	type FullIdentificationOf = pallet_staking::ExposureOf<Runtime>;
+}
+
+/// Elects the validators within the block starting an era.
+pub struct OnChainSeqPhragmen;
+
+impl onchain::Config for OnChainSeqPhragmen {
+	type System = Runtime;
+	type Solver = SequentialPhragmen<AccountId, Perbill>;
+	type DataProvider = Staking;
+	type WeightInfo = frame_election_provider_support::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_staking::Config for Runtime {
+	type MaxNominations = ConstU32<16>;
+	type Currency = Balances;
+	type CurrencyBalance = Balance;
+	type UnixTime = Timestamp;
+	type CurrencyToVote = frame_support::traits::U128CurrencyToVote;
+	/// Rewards beyond the validators' share of the era payout are not minted.
+	type RewardRemainder = ();
+	type Event = Event;
+	/// Slashed funds are burned.
+	type Slash = ();
+	type Reward = ();
+	type SessionsPerEra = SessionsPerEra;
+	type BondingDuration = BondingDuration;
+	type SlashDeferDuration = SlashDeferDuration;
+	type SlashCancelOrigin = frame_system::EnsureRoot<AccountId>;
+	type SessionInterface = Self;
+	type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
+	type NextNewSession = Session;
+	type MaxNominatorRewardedPerValidator = ConstU32<256>;
+	type OffendingValidatorsThreshold = OffendingValidatorsThreshold;
+	type ElectionProvider = onchain::UnboundedExecution<OnChainSeqPhragmen>;
+	type GenesisElectionProvider = Self::ElectionProvider;
+	/// Small validator and nominator sets are iterated in full, without a bags list.
+	type VoterList = pallet_staking::UseNominatorsAndValidatorsMap<Self>;
+	type MaxUnlockingChunks = ConstU32<32>;
+	type OnStakerSlash = ();
+	type BenchmarkingConfig = pallet_staking::TestBenchmarkingConfig;
+	type WeightInfo = pallet_staking::weights::SubstrateWeight<Runtime>;
+}
+
+/// Turns the equivocations GRANDPA reports into slashes.
+impl pallet_offences::Config for Runtime {
+	type Event = Event;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	type OnOffenceHandler = Staking;
+}
+
+/// Lets GRANDPA submit its equivocation reports as unsigned transactions.
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = Call;
+}
@@ -0,0 +1,22 @@
+//! Configuration of the difficulty pallet for proof-of-work block authoring.
+//!
+//! Only compiled with the `pow` feature, which replaces Aura by blake2 proof-of-work mined by the
+//! node. Blocks are finalized by GRANDPA, if it runs.
+
+use super::*;
+
+use sp_core::U256;
+
+/// Difficulty of the genesis block, about a second of hashing on a single core.
+pub const INITIAL_DIFFICULTY: u64 = 1_000_000;
+
+parameter_types! {
+	pub MinDifficulty: U256 = U256::from(10_000u64);
+}
+
+impl pallet_difficulty::Config for Runtime {
+	type Event = Event;
+	type TargetBlockTime = ConstU64<MILLISECS_PER_BLOCK>;
+	type AdjustmentWindow = ConstU32<10>;
+	type MinDifficulty = MinDifficulty;
+}
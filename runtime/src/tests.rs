@@ -0,0 +1,111 @@
+mod contracts;
+mod limits;
+
+use crate::{mock::*, *};
+use codec::Encode;
+use frame_support::{assert_ok, weights::GetDispatchInfo};
+use sp_keyring::AccountKeyring::{Alice, Bob, Charlie, Dave};
+use sp_runtime::MultiAddress;
+
+#[test]
+fn blocks_follow_the_aura_slots() {
+	ExtBuilder::default().authorities(vec![Alice, Bob]).build().execute_with(|| {
+		assert_eq!(Aura::authorities().len(), 2);
+		assert_eq!(Grandpa::grandpa_authorities().len(), 2);
+
+		run_to_block(3);
+		assert_eq!(System::block_number(), 3);
+		assert_eq!(*Aura::current_slot(), 3);
+		assert_eq!(Timestamp::now(), 3 * SLOT_DURATION);
+	});
+}
+
+#[test]
+fn transfers_charge_fees_to_the_sender() {
+	let balances = vec![(Alice.to_account_id(), ENDOWMENT)];
+	ExtBuilder::default().balances(balances).build().execute_with(|| {
+		let transfer =
+			BalancesCall::transfer { dest: MultiAddress::Id(Dave.to_account_id()), value: 1_000 };
+		assert_ok!(apply(sign(Alice, transfer)));
+		next_block();
+
+		assert_eq!(Balances::free_balance(Dave.to_account_id()), 1_000);
+		let fee = ENDOWMENT - 1_000 - Balances::free_balance(Alice.to_account_id());
+		assert!(fee > 0);
+		assert_eq!(System::account_nonce(Alice.to_account_id()), 1);
+	});
+}
+
+#[test]
+fn template_module_calls_are_charged_even_when_they_fail() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(apply(sign(Bob, pallet_template::Call::do_something { something: 7 })));
+		assert_eq!(TemplateModule::something(), Some(7));
+		System::assert_has_event(
+			pallet_template::Event::SomethingStored(7, Bob.to_account_id()).into(),
+		);
+
+		next_block();
+		let call = pallet_template::Call::do_something { something: u32::MAX };
+		assert_ok!(apply(sign(Bob, call)));
+
+		let balance = Balances::free_balance(Bob.to_account_id());
+		let extrinsic = sign(Bob, pallet_template::Call::cause_error {});
+		// Failing calls don't refund any of their weight.
+		let fee = TransactionPayment::compute_fee(
+			extrinsic.encoded_size() as u32,
+			&extrinsic.get_dispatch_info(),
+			0,
+		);
+		assert!(fee > 0);
+		assert_eq!(
+			apply(extrinsic),
+			Err(pallet_template::Error::<Runtime>::StorageOverflow.into())
+		);
+		assert_eq!(TemplateModule::something(), Some(u32::MAX));
+		assert_eq!(System::account_nonce(Bob.to_account_id()), 3);
+		assert_eq!(Balances::free_balance(Bob.to_account_id()), balance - fee);
+	});
+}
+
+#[test]
+fn sudo_dispatches_root_calls_for_the_sudo_key_only() {
+	ExtBuilder::default().build().execute_with(|| {
+		let set_balance = BalancesCall::set_balance {
+			who: MultiAddress::Id(Dave.to_account_id()),
+			new_free: ENDOWMENT,
+			new_reserved: 0,
+		};
+		let sudo = SudoCall::sudo { call: Box::new(set_balance.into()) };
+
+		assert_eq!(
+			apply(sign(Charlie, sudo.clone())),
+			Err(pallet_sudo::Error::<Runtime>::RequireSudo.into())
+		);
+		assert_eq!(Balances::free_balance(Dave.to_account_id()), 0);
+
+		assert_ok!(apply(sign(Alice, sudo)));
+		System::assert_has_event(pallet_sudo::Event::Sudid { sudo_result: Ok(()) }.into());
+		assert_eq!(Balances::free_balance(Dave.to_account_id()), ENDOWMENT);
+	});
+}
+
+#[test]
+fn chains_without_sudo_key_reject_sudo_calls() {
+	ExtBuilder::default().sudo(None).build().execute_with(|| {
+		let remark = SystemCall::remark { remark: vec![] };
+		assert_eq!(
+			apply(sign(Alice, SudoCall::sudo { call: Box::new(remark.into()) })),
+			Err(pallet_sudo::Error::<Runtime>::RequireSudo.into())
+		);
+	});
+}
+
+#[test]
+fn signed_extensions_expose_the_asset_fees_are_paid_in() {
+	let charge = |asset_id| -> pallet_feeless::CheckFeeless<Runtime, _> {
+		pallet_asset_tx_payment::ChargeAssetTxPayment::<Runtime>::from(1_000, asset_id).into()
+	};
+	assert_eq!(charge(Some(7)).fee_asset(), Some(7));
+	assert_eq!(charge(None).fee_asset(), None);
+}
@@ -0,0 +1,50 @@
+//! Contracts reach `pallet_template` through the [`chain_extension::TemplateExtension`].
+
+use crate::{mock::*, *};
+use codec::Encode;
+use pallet_contracts_primitives::Code;
+use sp_keyring::AccountKeyring::Alice;
+
+/// Contract writing `42` through the chain extension on deployment and returning what it reads
+/// through it when called.
+const TEMPLATE_EXTENSION: &str = include_str!("../../fixtures/template_extension.wat");
+
+#[test]
+fn contracts_write_and_read_something_through_the_chain_extension() {
+	ExtBuilder::default().build().execute_with(|| {
+		let code = wat::parse_str(TEMPLATE_EXTENSION).expect("the fixture is valid");
+		let gas_limit = BlockWeights::get().max_block;
+		let instantiated = Contracts::bare_instantiate(
+			Alice.to_account_id(),
+			0,
+			gas_limit,
+			None,
+			Code::Upload(code.into()),
+			vec![],
+			vec![],
+			false,
+		)
+		.result
+		.expect("the contract is instantiated");
+		assert!(!instantiated.result.did_revert());
+
+		let contract = instantiated.account_id;
+		assert_eq!(TemplateModule::something(), Some(42));
+		System::assert_has_event(
+			pallet_template::Event::SomethingStored(42, contract.clone()).into(),
+		);
+
+		let output = Contracts::bare_call(
+			Alice.to_account_id(),
+			contract,
+			0,
+			gas_limit,
+			None,
+			vec![],
+			false,
+		)
+		.result
+		.expect("the contract is called");
+		assert_eq!(output.data.0, Some(42u32).encode());
+	});
+}
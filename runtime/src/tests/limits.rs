@@ -0,0 +1,72 @@
+//! Blocks filled with as many extrinsics of a call as they take stay within the weight and length
+//! limits of `BlockWeights` and `BlockLength`.
+
+use crate::{mock::*, *};
+use frame_support::{assert_ok, weights::DispatchClass};
+use sp_keyring::AccountKeyring::{Alice, Bob};
+use sp_runtime::{
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	MultiAddress,
+};
+
+/// Applies extrinsics of `call` signed by Alice until the block is full, and returns how many it
+/// took.
+fn fill_block(call: Call) -> u32 {
+	let mut count = 0;
+	loop {
+		match Executive::apply_extrinsic(sign(Alice, call.clone())) {
+			Ok(result) => {
+				assert_ok!(result);
+				count += 1;
+			},
+			Err(TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources)) =>
+				return count,
+			Err(e) => panic!("{:?} was rejected: {:?}", call, e),
+		}
+	}
+}
+
+/// Fills a block with `call`, checks that it stays within the limits and returns the number of
+/// extrinsics it took.
+fn assert_maximal_block_within_limits(call: impl Into<Call>) -> u32 {
+	ExtBuilder::default().build().execute_with(|| {
+		let count = fill_block(call.into());
+		assert!(count > 0, "not a single extrinsic fits in a block");
+
+		let weights = BlockWeights::get();
+		let consumed = System::block_weight();
+		let normal = weights.get(DispatchClass::Normal).max_total.unwrap_or(weights.max_block);
+		assert!(*consumed.get(DispatchClass::Normal) <= normal);
+		assert!(consumed.total() <= weights.max_block);
+		assert!(System::all_extrinsics_len() <= *BlockLength::get().max.get(DispatchClass::Normal));
+
+		// The hooks of the full block still fit too.
+		next_block();
+		count
+	})
+}
+
+#[test]
+fn template_module_calls_fill_blocks_within_limits() {
+	assert_maximal_block_within_limits(pallet_template::Call::do_something { something: 42 });
+}
+
+#[test]
+fn transfers_fill_blocks_within_limits() {
+	let transfer =
+		BalancesCall::transfer_keep_alive { dest: MultiAddress::Id(Bob.to_account_id()), value: 1 };
+	assert_maximal_block_within_limits(transfer);
+}
+
+#[test]
+fn remarks_fill_blocks_within_limits() {
+	assert_maximal_block_within_limits(SystemCall::remark { remark: vec![] });
+}
+
+#[test]
+fn large_remarks_fill_blocks_up_to_the_length_limit() {
+	let remark = vec![0; 256 * 1024];
+	let count = assert_maximal_block_within_limits(SystemCall::remark { remark });
+	// 3.75 MiB of normal extrinsics hold 14 remarks of 256 KiB and their signatures, not 15.
+	assert_eq!(count, 14);
+}